@@ -0,0 +1,122 @@
+// Balance testing for the 5VS5dotGG match engine.
+//
+// Loads rosters from JSON or CSV and runs Monte Carlo tournaments through
+// esport-simulation, the engine `simulateMatch` runs on-chain, so a roster's results
+// here are the results it would get in the program with the same seeds.
+mod matrix;
+mod roster;
+mod season;
+mod sensitivity;
+mod tournament;
+
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+use esport_simulation::MatchType;
+use esport_types::rating::DEFAULT_K_FACTOR;
+use esport_types::RatingModel;
+use serde::Serialize;
+
+#[derive(Parser)]
+#[command(name = "esport-balance", about = "Monte Carlo balance testing of the match engine")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args)]
+struct Common {
+    #[arg(help = "Roster file (.csv, otherwise JSON)")]
+    rosters: PathBuf,
+
+    #[arg(long, default_value = "bo1", help = "Match type label, as stored on the match (bo1, bo3, ...)")]
+    format: String,
+
+    #[arg(long, default_value_t = 1, help = "Base seed; the same seed replays the same run")]
+    seed: u64,
+
+    #[arg(long, help = "Print the report as JSON")]
+    json: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Head-to-head win rates between every pair of teams
+    Matrix {
+        #[command(flatten)]
+        common: Common,
+
+        #[arg(long, default_value_t = 500, help = "Series per pairing with each team as team 1")]
+        series: u32,
+    },
+    /// Average win rate change per position and attribute when the attribute is raised
+    Sensitivity {
+        #[command(flatten)]
+        common: Common,
+
+        #[arg(long, default_value_t = 10, help = "Attribute increase, capped at 100")]
+        delta: u8,
+
+        #[arg(long, default_value_t = 200, help = "Series per pairing with each team as team 1")]
+        series: u32,
+    },
+    /// Rating distribution after double round robin seasons
+    Season {
+        #[command(flatten)]
+        common: Common,
+
+        #[arg(long, default_value_t = 100, help = "Seasons to play, each from default ratings")]
+        seasons: u32,
+
+        #[arg(
+            long,
+            default_value = "glicko2",
+            value_parser = parse_model,
+            help = "Rating model: glicko2, elo or elo:<k-factor>"
+        )]
+        model: RatingModel,
+    },
+}
+
+// Same choice as GameConfig.elo_k_factor, with "elo" using the default K-factor
+fn parse_model(label: &str) -> Result<RatingModel, String> {
+    match label.split_once(':') {
+        None if label.eq_ignore_ascii_case("glicko2") => Ok(RatingModel::Glicko2),
+        None if label.eq_ignore_ascii_case("elo") => Ok(RatingModel::Elo { k_factor: DEFAULT_K_FACTOR }),
+        Some((elo, k_factor)) if elo.eq_ignore_ascii_case("elo") => match k_factor.parse() {
+            Ok(0) | Err(_) => Err(format!("invalid K-factor: {k_factor}")),
+            Ok(k_factor) => Ok(RatingModel::Elo { k_factor }),
+        },
+        _ => Err(format!("unknown rating model: {label}")),
+    }
+}
+
+fn output<T: Serialize>(report: &T, json: bool, print: impl FnOnce(&T)) -> Result<(), Box<dyn Error>> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(report)?);
+    } else {
+        print(report);
+    }
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    match Cli::parse().command {
+        Command::Matrix { common, series } => {
+            let teams = roster::load(&common.rosters)?;
+            let report = matrix::run(MatchType::parse(&common.format), &teams, series, common.seed);
+            output(&report, common.json, matrix::MatrixReport::print)
+        }
+        Command::Sensitivity { common, delta, series } => {
+            let teams = roster::load(&common.rosters)?;
+            let report = sensitivity::run(MatchType::parse(&common.format), &teams, delta, series, common.seed);
+            output(&report, common.json, sensitivity::SensitivityReport::print)
+        }
+        Command::Season { common, seasons, model } => {
+            let teams = roster::load(&common.rosters)?;
+            let report = season::run(MatchType::parse(&common.format), &teams, seasons, model, common.seed);
+            output(&report, common.json, season::SeasonReport::print)
+        }
+    }
+}
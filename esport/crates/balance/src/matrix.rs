@@ -0,0 +1,120 @@
+// Head-to-head win rates between every pair of teams
+use esport_simulation::{Lineup, MatchType};
+use serde::Serialize;
+
+use crate::roster::Team;
+use crate::tournament::{head_to_head, pairing, Seeds};
+
+#[derive(Serialize)]
+pub struct MatrixReport {
+    pub series_per_side: u32,
+    pub teams: Vec<String>,
+    // win_rates[i][j]: share of series team i won against team j; None on the diagonal
+    pub win_rates: Vec<Vec<Option<f64>>>,
+    // Share of all its series each team won
+    pub overall: Vec<f64>,
+}
+
+pub fn run(format: MatchType, teams: &[Team], series: u32, seed: u64) -> MatrixReport {
+    let lineups: Vec<Lineup> = teams.iter().map(|team| team.lineup).collect();
+    let count = lineups.len();
+    let played = series * 2;
+
+    let mut win_rates = vec![vec![None; count]; count];
+    let mut wins = vec![0; count];
+    for i in 0..count {
+        for j in i + 1..count {
+            let i_wins = head_to_head(
+                format,
+                &lineups[i],
+                &lineups[j],
+                series,
+                Seeds::new(seed, pairing(i, j)),
+                Seeds::new(seed, pairing(j, i)),
+            );
+            wins[i] += i_wins;
+            wins[j] += played - i_wins;
+            win_rates[i][j] = Some(i_wins as f64 / played as f64);
+            win_rates[j][i] = Some((played - i_wins) as f64 / played as f64);
+        }
+    }
+
+    MatrixReport {
+        series_per_side: series,
+        teams: teams.iter().map(|team| team.name.clone()).collect(),
+        win_rates,
+        overall: wins.iter().map(|&w| w as f64 / (played as f64 * (count - 1) as f64)).collect(),
+    }
+}
+
+impl MatrixReport {
+    pub fn print(&self) {
+        let width = self.teams.iter().map(String::len).max().unwrap_or(0).max(4);
+
+        print!("{:width$}", "");
+        for column in 0..self.teams.len() {
+            print!(" {:>6}", format!("#{}", column + 1));
+        }
+        println!(" {:>7}", "overall");
+
+        for (row, name) in self.teams.iter().enumerate() {
+            print!("{name:width$}");
+            for rate in &self.win_rates[row] {
+                match rate {
+                    Some(rate) => print!(" {:>5.1}%", rate * 100.0),
+                    None => print!(" {:>6}", "-"),
+                }
+            }
+            println!(" {:>6.1}%", self.overall[row] * 100.0);
+        }
+
+        println!();
+        for (index, name) in self.teams.iter().enumerate() {
+            println!("#{} {name}", index + 1);
+        }
+        println!("{} series per side per pairing", self.series_per_side);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::roster;
+
+    // The sample rosters with a fixed seed: any change to the engine or the seeding that
+    // moves these numbers changes every recorded balance report
+    #[test]
+    fn sample_matrix() {
+        let teams = roster::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("rosters/sample.csv")).unwrap();
+        let report = run(MatchType::parse("bo3"), &teams, 100, 42);
+
+        let wins = |rates: &[Option<f64>]| -> Vec<Option<u32>> {
+            rates.iter().map(|rate| rate.map(|rate| (rate * 200.0).round() as u32)).collect()
+        };
+        let wins: Vec<_> = report.win_rates.iter().map(|row| wins(row)).collect();
+        assert_eq!(
+            wins,
+            [
+                [None, Some(103), Some(35), Some(62)],
+                [Some(97), None, Some(38), Some(64)],
+                [Some(165), Some(162), None, Some(142)],
+                [Some(138), Some(136), Some(58), None],
+            ]
+        );
+        let overall: Vec<u32> = report.overall.iter().map(|rate| (rate * 600.0).round() as u32).collect();
+        assert_eq!(overall, [200, 199, 469, 332]);
+
+        // Each pairing's rates add up, and the same seed gives the same report
+        for i in 0..teams.len() {
+            for j in 0..teams.len() {
+                if let (Some(a), Some(b)) = (report.win_rates[i][j], report.win_rates[j][i]) {
+                    assert_eq!(a + b, 1.0);
+                }
+            }
+        }
+        assert_eq!(run(MatchType::parse("bo3"), &teams, 100, 42).win_rates, report.win_rates);
+        assert_ne!(run(MatchType::parse("bo3"), &teams, 100, 43).win_rates, report.win_rates);
+    }
+}
@@ -0,0 +1,276 @@
+// Rosters to test, read from JSON or CSV.
+//
+// JSON is an array of teams:
+//   [{"name": "Alpha", "strategy": "Aggressive", "players": [
+//       {"name": "Ace", "role": "Carry", "position": "Carry", "mechanical": 90, ...}, ...]}]
+// CSV has one row per player, the team's rows in lineup order:
+//   team,strategy,player,role,position,mechanical,game_knowledge,team_communication,
+//   adaptability,consistency,form,potential
+//
+// Labels are matched like on-chain (`split push` is SplitPush). A player without a role
+// is flex; a player without a position is slotted in their own role.
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use esport_simulation::{Attributes, Lineup, Player};
+use esport_types::{Role, StrategyType};
+use serde::Deserialize;
+
+// A team under test
+#[derive(Clone, Debug)]
+pub struct Team {
+    pub name: String,
+    pub lineup: Lineup,
+}
+
+#[derive(Deserialize)]
+struct TeamRecord {
+    name: String,
+    #[serde(default)]
+    strategy: StrategyType,
+    players: Vec<PlayerRecord>,
+}
+
+#[derive(Deserialize)]
+struct PlayerRecord {
+    name: String,
+    role: Option<Role>,
+    position: Option<Role>,
+    mechanical: u8,
+    game_knowledge: u8,
+    team_communication: u8,
+    adaptability: u8,
+    consistency: u8,
+    form: u8,
+    potential: u8,
+}
+
+// The csv crate can't flatten records with numeric fields, so rows repeat PlayerRecord
+#[derive(Deserialize)]
+struct CsvRow {
+    team: String,
+    strategy: Option<StrategyType>,
+    player: String,
+    role: Option<Role>,
+    position: Option<Role>,
+    mechanical: u8,
+    game_knowledge: u8,
+    team_communication: u8,
+    adaptability: u8,
+    consistency: u8,
+    form: u8,
+    potential: u8,
+}
+
+impl PlayerRecord {
+    fn to_player(&self) -> Result<Player, Box<dyn Error>> {
+        let attributes = Attributes {
+            mechanical: self.mechanical,
+            game_knowledge: self.game_knowledge,
+            team_communication: self.team_communication,
+            adaptability: self.adaptability,
+            consistency: self.consistency,
+            form: self.form,
+            potential: self.potential,
+        };
+        if attributes.to_array().iter().any(|&attribute| attribute > 100) {
+            return Err(format!("player {}: attributes must be 0-100", self.name).into());
+        }
+
+        Ok(Player {
+            role: self.role,
+            position: self.position.or(self.role),
+            attributes,
+        })
+    }
+}
+
+impl TeamRecord {
+    fn to_team(&self) -> Result<Team, Box<dyn Error>> {
+        let players: [&PlayerRecord; 5] = self
+            .players
+            .iter()
+            .collect::<Vec<_>>()
+            .try_into()
+            .map_err(|_| format!("team {}: needs exactly 5 players, found {}", self.name, self.players.len()))?;
+
+        let mut lineup = Lineup { strategy: self.strategy, ..Lineup::default() };
+        for (slot, record) in lineup.players.iter_mut().zip(players) {
+            *slot = record.to_player()?;
+        }
+
+        Ok(Team {
+            name: self.name.clone(),
+            lineup,
+        })
+    }
+}
+
+// Load the teams of a roster file, CSV if the extension says so and JSON otherwise
+pub fn load(path: &Path) -> Result<Vec<Team>, Box<dyn Error>> {
+    let is_csv = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    parse(File::open(path)?, is_csv)
+}
+
+fn parse(reader: impl Read, is_csv: bool) -> Result<Vec<Team>, Box<dyn Error>> {
+    let records = if is_csv {
+        read_csv(reader)?
+    } else {
+        serde_json::from_reader(reader)?
+    };
+
+    let teams = records.iter().map(TeamRecord::to_team).collect::<Result<Vec<_>, _>>()?;
+    if teams.len() < 2 {
+        return Err("a roster file needs at least two teams".into());
+    }
+    for (index, team) in teams.iter().enumerate() {
+        if teams[..index].iter().any(|other| other.name == team.name) {
+            return Err(format!("team {} appears twice", team.name).into());
+        }
+    }
+
+    Ok(teams)
+}
+
+// Group CSV rows into teams, keeping the order teams first appear in
+fn read_csv(reader: impl Read) -> Result<Vec<TeamRecord>, Box<dyn Error>> {
+    let mut records: Vec<TeamRecord> = Vec::new();
+    let mut indices = HashMap::new();
+
+    for row in csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(reader).deserialize() {
+        let row: CsvRow = row?;
+        let index = *indices.entry(row.team.clone()).or_insert_with(|| {
+            records.push(TeamRecord { name: row.team.clone(), strategy: StrategyType::None, players: Vec::new() });
+            records.len() - 1
+        });
+
+        let team = &mut records[index];
+        if let Some(strategy) = row.strategy {
+            team.strategy = strategy;
+        }
+        team.players.push(PlayerRecord {
+            name: row.player,
+            role: row.role,
+            position: row.position,
+            mechanical: row.mechanical,
+            game_knowledge: row.game_knowledge,
+            team_communication: row.team_communication,
+            adaptability: row.adaptability,
+            consistency: row.consistency,
+            form: row.form,
+            potential: row.potential,
+        });
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "team,strategy,player,role,position,mechanical,game_knowledge,team_communication,\
+                          adaptability,consistency,form,potential\n";
+
+    // CSV rows of a team of `players` players, all rated `value`
+    fn csv_team(name: &str, players: usize, value: u8) -> String {
+        (0..players)
+            .map(|i| {
+                let role = Role::ALL[i % 5].label();
+                format!("{name},split push,{name} {i},{role},,{value},{value},{value},{value},{value},{value},{value}\n")
+            })
+            .collect()
+    }
+
+    fn load_csv(rows: &str) -> Result<Vec<Team>, Box<dyn Error>> {
+        parse(format!("{HEADER}{rows}").as_bytes(), true)
+    }
+
+    fn error(result: Result<Vec<Team>, Box<dyn Error>>) -> String {
+        result.expect_err("expected an error").to_string()
+    }
+
+    #[test]
+    fn loads_csv() {
+        let mut rows = csv_team("Alpha", 5, 80) + &csv_team("Bravo", 5, 60);
+        // A flex player without a role; the strategy only needs to be on one row
+        rows = rows.replacen("Alpha,split push,Alpha 4,Support,,", "Alpha,,Alpha 4,,Support,", 1);
+        let teams = load_csv(&rows).unwrap();
+
+        assert_eq!(teams.len(), 2);
+        assert_eq!(teams[0].name, "Alpha");
+        assert_eq!(teams[0].lineup.strategy, StrategyType::SplitPush);
+        assert_eq!(teams[0].lineup.players[0].position, Some(Role::Top));
+        assert_eq!(teams[0].lineup.players[4].role, None);
+        assert_eq!(teams[0].lineup.players[4].position, Some(Role::Support));
+        assert_eq!(teams[1].lineup.players[2].attributes.mechanical, 60);
+    }
+
+    #[test]
+    fn loads_json() {
+        let player = |name: &str, role: &str| {
+            format!(
+                r#"{{"name": "{name}", "role": "{role}", "mechanical": 90, "game_knowledge": 80,
+                    "team_communication": 70, "adaptability": 60, "consistency": 50, "form": 40, "potential": 30}}"#
+            )
+        };
+        let team = |name: &str, strategy: &str| {
+            let players: Vec<String> = ["Top", "jungler", "Mid", "ADC", "Support"].iter().map(|role| player(name, role)).collect();
+            format!(r#"{{"name": "{name}", "strategy": "{strategy}", "players": [{}]}}"#, players.join(","))
+        };
+        let json = format!("[{}, {}]", team("Alpha", "objective"), team("Bravo", "Defensive"));
+        let teams = parse(json.as_bytes(), false).unwrap();
+
+        assert_eq!(teams[0].lineup.strategy, StrategyType::ObjectiveControl);
+        assert_eq!(teams[1].lineup.players[3].role, Some(Role::Carry));
+        assert_eq!(teams[1].lineup.players[1].attributes.to_array(), [90, 80, 70, 60, 50, 40, 30]);
+    }
+
+    #[test]
+    fn rejects_wrong_player_counts() {
+        let rows = csv_team("Alpha", 4, 80) + &csv_team("Bravo", 5, 60);
+        assert_eq!(error(load_csv(&rows)), "team Alpha: needs exactly 5 players, found 4");
+        let rows = csv_team("Alpha", 5, 80) + &csv_team("Bravo", 6, 60);
+        assert_eq!(error(load_csv(&rows)), "team Bravo: needs exactly 5 players, found 6");
+        assert_eq!(error(parse(r#"[{"name": "Alpha", "players": []}]"#.as_bytes(), false)), "team Alpha: needs exactly 5 players, found 0");
+    }
+
+    #[test]
+    fn rejects_attributes_over_100() {
+        let rows = csv_team("Alpha", 5, 100) + &csv_team("Bravo", 5, 101);
+        assert_eq!(error(load_csv(&rows)), "player Bravo 0: attributes must be 0-100");
+        // Past a u8 the row doesn't parse at all
+        assert!(load_csv(&(csv_team("Alpha", 5, 80) + &csv_team("Bravo", 5, 80).replacen(",80,", ",256,", 1))).is_err());
+    }
+
+    #[test]
+    fn rejects_duplicate_and_missing_teams() {
+        // CSV rows of the same team name are grouped, so a repeat shows up as extra players
+        let rows = csv_team("Alpha", 5, 80) + &csv_team("Alpha", 5, 60);
+        assert_eq!(error(load_csv(&rows)), "team Alpha: needs exactly 5 players, found 10");
+
+        let team = |name: &str| {
+            let player = r#"{"name": "P", "mechanical": 1, "game_knowledge": 1, "team_communication": 1,
+                "adaptability": 1, "consistency": 1, "form": 1, "potential": 1}"#;
+            format!(r#"{{"name": "{name}", "players": [{player}, {player}, {player}, {player}, {player}]}}"#)
+        };
+        let json = format!("[{}, {}, {}]", team("Alpha"), team("Bravo"), team("Alpha"));
+        assert_eq!(error(parse(json.as_bytes(), false)), "team Alpha appears twice");
+
+        assert_eq!(error(load_csv(&csv_team("Alpha", 5, 80))), "a roster file needs at least two teams");
+        // Labels that don't parse are errors, not flex players or no strategy
+        let rows = csv_team("Alpha", 5, 80) + &csv_team("Bravo", 5, 80);
+        assert!(load_csv(&rows.replace("split push", "turtle")).is_err());
+        assert!(load_csv(&rows.replacen("Mid", "wizard", 1)).is_err());
+    }
+
+    #[test]
+    fn loads_the_sample_rosters() {
+        let teams = load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("rosters/sample.csv")).unwrap();
+        assert_eq!(teams.len(), 4);
+        assert_eq!(teams[0].name, "Baseline");
+    }
+}
@@ -0,0 +1,157 @@
+// Ratings after simulated seasons.
+//
+// Every season starts all teams at the default rating and plays a double round robin
+// (each pairing once per side) in a shuffled order. Ratings are kept as the integers
+// TeamData stores and updated with the same rules as `simulateMatch`, so rounding drift
+// matches the program.
+use esport_simulation::{simulate, MatchType};
+use esport_types::rating::{self, Rating};
+use esport_types::RatingModel;
+use serde::Serialize;
+
+use crate::roster::Team;
+use crate::tournament::{pairing, Seeds};
+
+// Width of a rating histogram bucket
+const BUCKET: u16 = 50;
+
+// Stream key of a season's schedule, clear of every pairing key
+const SCHEDULE_STREAM: u64 = u64::MAX;
+
+#[derive(Clone, Copy)]
+struct StoredRating {
+    rating: u16,
+    deviation: u16,
+    volatility: u32,
+}
+
+impl StoredRating {
+    const DEFAULT: Self = Self {
+        rating: rating::DEFAULT_RATING,
+        deviation: rating::DEFAULT_RATING_DEVIATION,
+        volatility: rating::DEFAULT_VOLATILITY,
+    };
+
+    fn current(&self) -> Rating {
+        Rating::from_stored(self.rating, self.deviation, self.volatility)
+    }
+
+    fn apply(&mut self, rating: Rating) {
+        self.rating = rating.stored_rating();
+        self.deviation = rating.stored_deviation();
+        self.volatility = rating.stored_volatility();
+    }
+}
+
+#[derive(Serialize)]
+pub struct TeamSeasons {
+    pub name: String,
+    pub mean_rating: f64,
+    pub min_rating: u16,
+    pub max_rating: u16,
+    pub mean_wins: f64, // Series won per season, out of 2 * (teams - 1)
+}
+
+#[derive(Serialize)]
+pub struct SeasonReport {
+    pub seasons: u32,
+    pub model: String,
+    pub teams: Vec<TeamSeasons>,
+    // (lowest rating of the bucket, final ratings in it) across all teams and seasons
+    pub histogram: Vec<(u16, u32)>,
+}
+
+pub fn model_label(model: RatingModel) -> String {
+    match model {
+        RatingModel::Elo { k_factor } => format!("elo:{k_factor}"),
+        RatingModel::Glicko2 => "glicko2".to_string(),
+    }
+}
+
+pub fn run(format: MatchType, teams: &[Team], seasons: u32, model: RatingModel, seed: u64) -> SeasonReport {
+    let count = teams.len();
+    let mut finals: Vec<Vec<u16>> = vec![Vec::new(); count];
+    let mut wins = vec![0u32; count];
+
+    // Seeds continue across seasons, so season N+1 replays no series of season N
+    let mut streams: Vec<Seeds> = (0..count * count)
+        .map(|key| Seeds::new(seed, pairing(key / count, key % count)))
+        .collect();
+    let mut schedule_seeds = Seeds::new(seed, SCHEDULE_STREAM);
+
+    for _ in 0..seasons {
+        let mut schedule: Vec<(usize, usize)> =
+            (0..count).flat_map(|i| (0..count).filter(move |&j| j != i).map(move |j| (i, j))).collect();
+        // Fisher-Yates
+        for index in (1..schedule.len()).rev() {
+            let other = schedule_seeds.rng().next_below(index as u32 + 1) as usize;
+            schedule.swap(index, other);
+        }
+
+        let mut ratings = vec![StoredRating::DEFAULT; count];
+        for (team1, team2) in schedule {
+            let seed = streams[team1 * count + team2].next_seed();
+            let team1_won = simulate(format, &teams[team1].lineup, &teams[team2].lineup, &seed).team1_won();
+            wins[if team1_won { team1 } else { team2 }] += 1;
+
+            let (new1, new2) = rating::rate_match(model, ratings[team1].current(), ratings[team2].current(), team1_won);
+            ratings[team1].apply(new1);
+            ratings[team2].apply(new2);
+        }
+
+        for (team, rating) in ratings.iter().enumerate() {
+            finals[team].push(rating.rating);
+        }
+    }
+
+    let mut histogram: Vec<(u16, u32)> = Vec::new();
+    let mut all: Vec<u16> = finals.iter().flatten().map(|rating| rating / BUCKET * BUCKET).collect();
+    all.sort_unstable();
+    for bucket in all {
+        match histogram.last_mut() {
+            Some((start, count)) if *start == bucket => *count += 1,
+            _ => histogram.push((bucket, 1)),
+        }
+    }
+
+    SeasonReport {
+        seasons,
+        model: model_label(model),
+        teams: teams
+            .iter()
+            .zip(&finals)
+            .zip(&wins)
+            .map(|((team, ratings), &wins)| TeamSeasons {
+                name: team.name.clone(),
+                mean_rating: ratings.iter().map(|&r| r as f64).sum::<f64>() / seasons.max(1) as f64,
+                min_rating: ratings.iter().copied().min().unwrap_or(rating::DEFAULT_RATING),
+                max_rating: ratings.iter().copied().max().unwrap_or(rating::DEFAULT_RATING),
+                mean_wins: wins as f64 / seasons.max(1) as f64,
+            })
+            .collect(),
+        histogram,
+    }
+}
+
+impl SeasonReport {
+    pub fn print(&self) {
+        let width = self.teams.iter().map(|team| team.name.len()).max().unwrap_or(0).max(4);
+
+        println!("{} seasons rated with {}", self.seasons, self.model);
+        println!("{:width$} {:>8} {:>6} {:>6} {:>11}", "team", "mean", "min", "max", "wins/season");
+        for team in &self.teams {
+            println!(
+                "{:width$} {:>8.1} {:>6} {:>6} {:>11.2}",
+                team.name, team.mean_rating, team.min_rating, team.max_rating, team.mean_wins
+            );
+        }
+
+        println!();
+        println!("Final ratings");
+        let largest = self.histogram.iter().map(|&(_, count)| count).max().unwrap_or(1);
+        for &(start, count) in &self.histogram {
+            let bar = "#".repeat((count as usize * 40).div_ceil(largest as usize));
+            println!("{:>5}-{:<5} {:>6} {bar}", start, start + BUCKET - 1, count);
+        }
+    }
+}
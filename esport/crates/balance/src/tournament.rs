@@ -0,0 +1,70 @@
+// Seeded series between lineups, played with the on-chain engine.
+//
+// Every pairing draws its match seeds from its own stream, keyed by the pairing, so a
+// run is reproducible from the base seed and a variant of one lineup faces exactly the
+// same seeds as the original (common random numbers keep sensitivity deltas low-noise).
+use esport_simulation::{simulate, Lineup, MatchRng, MatchType};
+
+// Stream of 32-byte match seeds for one pairing
+pub struct Seeds(MatchRng);
+
+impl Seeds {
+    pub fn new(base: u64, stream: u64) -> Self {
+        let mut seed = [0; 32];
+        seed[..8].copy_from_slice(&base.to_le_bytes());
+        seed[8..16].copy_from_slice(&stream.wrapping_mul(0x9E37_79B9_7F4A_7C15).to_le_bytes());
+        Self(MatchRng::new(&seed))
+    }
+
+    pub fn next_seed(&mut self) -> [u8; 32] {
+        let mut seed = [0; 32];
+        for chunk in seed.chunks_exact_mut(8) {
+            chunk.copy_from_slice(&self.0.next_u64().to_le_bytes());
+        }
+        seed
+    }
+
+    pub fn rng(&mut self) -> &mut MatchRng {
+        &mut self.0
+    }
+}
+
+// Stream key of `team1` hosting `team2`
+pub fn pairing(team1: usize, team2: usize) -> u64 {
+    ((team1 as u64) << 32) | team2 as u64
+}
+
+// Series won by `lineup` out of `series * 2` against `opponent`: `series` as team 1 and
+// `series` as team 2, since team 2 takes games tied on strength
+pub fn head_to_head(
+    format: MatchType,
+    lineup: &Lineup,
+    opponent: &Lineup,
+    series: u32,
+    mut home: Seeds,
+    mut away: Seeds,
+) -> u32 {
+    let home_wins = (0..series)
+        .filter(|_| simulate(format, lineup, opponent, &home.next_seed()).team1_won())
+        .count();
+    let away_wins = (0..series)
+        .filter(|_| !simulate(format, opponent, lineup, &away.next_seed()).team1_won())
+        .count();
+    (home_wins + away_wins) as u32
+}
+
+// Share of series `teams[index]` (or `lineup` standing in for it) wins against every other team
+pub fn win_rate(format: MatchType, teams: &[Lineup], index: usize, lineup: &Lineup, series: u32, seed: u64) -> f64 {
+    let mut wins = 0;
+    for (opponent_index, opponent) in teams.iter().enumerate().filter(|(i, _)| *i != index) {
+        wins += head_to_head(
+            format,
+            lineup,
+            opponent,
+            series,
+            Seeds::new(seed, pairing(index, opponent_index)),
+            Seeds::new(seed, pairing(opponent_index, index)),
+        );
+    }
+    wins as f64 / (series as f64 * 2.0 * (teams.len() - 1) as f64)
+}
@@ -0,0 +1,37 @@
+// Decoding of fetched account data. Component accounts are Anchor accounts, so the data
+// starts with the account discriminator, which is checked.
+use anchor_lang::AccountDeserialize;
+
+pub use match_queue::MatchQueue;
+pub use match_state::MatchState;
+pub use player_stats::PlayerStats;
+pub use team_data::TeamData;
+
+pub fn team_data(data: &[u8]) -> anchor_lang::Result<TeamData> {
+    TeamData::try_deserialize(&mut &data[..])
+}
+
+pub fn player_stats(data: &[u8]) -> anchor_lang::Result<PlayerStats> {
+    PlayerStats::try_deserialize(&mut &data[..])
+}
+
+pub fn match_queue(data: &[u8]) -> anchor_lang::Result<MatchQueue> {
+    MatchQueue::try_deserialize(&mut &data[..])
+}
+
+pub fn match_state(data: &[u8]) -> anchor_lang::Result<MatchState> {
+    MatchState::try_deserialize(&mut &data[..])
+}
+
+// Header of a world account: its id and how many entities it has, which is the id the
+// next `add_entity` creates
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WorldHeader {
+    pub id: u64,
+    pub entities: u64,
+}
+
+pub fn world_header(data: &[u8]) -> Option<WorldHeader> {
+    let field = |at: usize| Some(u64::from_le_bytes(data.get(at..at + 8)?.try_into().ok()?));
+    Some(WorldHeader { id: field(8)?, entities: field(16)? })
+}
@@ -0,0 +1,33 @@
+// Rust client for the 5VS5dotGG programs.
+//
+// Builds the world program instructions that create entities and components and run the
+// esport systems, deriving every component account from its entity and laying out the
+// component and extra accounts in the order each system reads them. Fetched component
+// accounts are decoded with `accounts`. The crate only builds instructions; sending them
+// is left to whichever RPC client the caller uses.
+use anchor_lang::solana_program::instruction::AccountMeta;
+
+pub mod accounts;
+pub mod matches;
+pub mod matchmaking;
+pub mod pda;
+pub mod player;
+pub mod queue;
+pub mod team;
+pub mod world;
+
+pub use esport_types::{
+    ChosenAttributes, MatchAction, MatchmakingAction, PlayerAction, QueueAction, RandomnessSource, Role, StrategyType,
+    TeamAction,
+};
+pub use matches::{LineupEntities, LineupHolders, MatchEntities, MatchSystem};
+pub use matchmaking::MatchmakingSystem;
+pub use player::PlayerSystem;
+pub use queue::QueueSystem;
+pub use team::TeamSystem;
+pub use world::{ComponentRef, WORLD_PROGRAM_ID};
+
+// Every system takes the game config as its first extra account
+fn game_config_meta() -> AccountMeta {
+    AccountMeta::new_readonly(pda::game_config(), false)
+}
@@ -0,0 +1,298 @@
+// match_system calls. Every action takes the full Components set, in order: the match
+// queue, the match, team 1, team 2, team 1's five lineup players and team 2's five.
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::{system_program, sysvar};
+use esport_types::{MatchAction, RandomnessSource};
+
+use crate::world::{self, ComponentRef};
+use crate::{game_config_meta, pda};
+
+pub use match_state::seed_commitment;
+
+// A team and the entities of its five lineup players, in lineup order
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LineupEntities {
+    pub team: Pubkey,
+    pub players: [Pubkey; 5],
+}
+
+// Entities taking part in a match
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MatchEntities {
+    pub queue: Pubkey,
+    pub match_entity: Pubkey,
+    pub team1: LineupEntities,
+    pub team2: LineupEntities,
+}
+
+impl MatchEntities {
+    // The fourteen components of match_system's Components, in order
+    pub fn components(&self) -> [ComponentRef; 14] {
+        let player = |entity| ComponentRef::new(player_stats::ID, entity);
+        let [a1, a2, a3, a4, a5] = self.team1.players;
+        let [b1, b2, b3, b4, b5] = self.team2.players;
+        [
+            ComponentRef::new(match_queue::ID, self.queue),
+            ComponentRef::new(match_state::ID, self.match_entity),
+            ComponentRef::new(team_data::ID, self.team1.team),
+            ComponentRef::new(team_data::ID, self.team2.team),
+            player(a1),
+            player(a2),
+            player(a3),
+            player(a4),
+            player(a5),
+            player(b1),
+            player(b2),
+            player(b3),
+            player(b4),
+            player(b5),
+        ]
+    }
+
+    // The match's MatchState account, which keys its fee vault and its results
+    pub fn match_state(&self) -> Pubkey {
+        pda::component(&match_state::ID, &self.match_entity)
+    }
+}
+
+// Holders of both lineups' NFTs: each team owner and their five mints in lineup order
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LineupHolders {
+    pub team1_owner: Pubkey,
+    pub team1_mints: [Pubkey; 5],
+    pub team2_owner: Pubkey,
+    pub team2_mints: [Pubkey; 5],
+}
+
+impl LineupHolders {
+    // The owners' token accounts for the ten NFTs, team 1 first, as lockLineups and
+    // simulateMatch expect them
+    pub fn token_accounts(&self) -> Vec<AccountMeta> {
+        let team1 = self.team1_mints.iter().map(|mint| (&self.team1_owner, mint));
+        let team2 = self.team2_mints.iter().map(|mint| (&self.team2_owner, mint));
+        team1
+            .chain(team2)
+            .map(|(owner, mint)| AccountMeta::new_readonly(pda::token_account(owner, mint), false))
+            .collect()
+    }
+}
+
+// Builds match_system instructions signed by `authority` for one match in `world`
+#[derive(Clone, Copy, Debug)]
+pub struct MatchSystem {
+    pub world: Pubkey,
+    pub authority: Pubkey,
+    pub entities: MatchEntities,
+}
+
+impl MatchSystem {
+    pub fn new(world: Pubkey, authority: Pubkey, entities: MatchEntities) -> Self {
+        Self { world, authority, entities }
+    }
+
+    fn call(&self, action: MatchAction, extra_accounts: Vec<AccountMeta>) -> Instruction {
+        let mut accounts = vec![game_config_meta()];
+        accounts.extend(extra_accounts);
+
+        world::apply(
+            match_system::ID,
+            self.authority,
+            self.world,
+            &self.entities.components(),
+            &accounts,
+            serde_json::to_vec(&action).expect("actions serialize to JSON"),
+        )
+    }
+
+    fn fee_accounts(&self) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(pda::fee_vault(&self.entities.match_state()), false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ]
+    }
+
+    // Turn the accepted queue entry `pending_match_id` into this match, played with
+    // randomness from `randomness`
+    pub fn schedule_match(&self, pending_match_id: u64, randomness: RandomnessSource) -> Instruction {
+        self.call(MatchAction::ScheduleMatch { pending_match_id, randomness }, Vec::new())
+    }
+
+    pub fn pay_entry_fee(&self) -> Instruction {
+        let mut accounts = self.fee_accounts();
+        accounts.push(AccountMeta::new_readonly(sysvar::instructions::ID, false));
+        self.call(MatchAction::PayEntryFee, accounts)
+    }
+
+    pub fn lock_lineups(&self, holders: &LineupHolders) -> Instruction {
+        self.call(MatchAction::LockLineups, holders.token_accounts())
+    }
+
+    pub fn consent_match(&self) -> Instruction {
+        self.call(MatchAction::ConsentMatch, Vec::new())
+    }
+
+    // Commit `seed_commitment(seed, owner)` for the signing owner
+    pub fn commit_seed(&self, seed_commitment: [u8; 32]) -> Instruction {
+        self.call(MatchAction::CommitSeed { seed_commitment }, Vec::new())
+    }
+
+    pub fn reveal_seed(&self, seed: [u8; 32]) -> Instruction {
+        self.call(MatchAction::RevealSeed { seed }, Vec::new())
+    }
+
+    // Play the match. For a `Vrf` match the oracle's Ed25519 instruction over
+    // `match_system::randomness::vrf_message` must directly precede this one in the transaction.
+    pub fn simulate_match(&self, holders: &LineupHolders, randomness: RandomnessSource) -> Instruction {
+        let mut accounts = holders.token_accounts();
+        if randomness == RandomnessSource::Vrf {
+            accounts.push(AccountMeta::new_readonly(sysvar::instructions::ID, false));
+        }
+        self.call(MatchAction::SimulateMatch, accounts)
+    }
+
+    pub fn forfeit(&self) -> Instruction {
+        self.call(MatchAction::Forfeit, Vec::new())
+    }
+
+    // Once the current step's deadline passed: win against an owner who didn't consent,
+    // commit, reveal or simulate in time, or void the match when the signer didn't either
+    pub fn claim_forfeit(&self) -> Instruction {
+        self.call(MatchAction::ClaimForfeit, Vec::new())
+    }
+
+    pub fn dispute_match(&self) -> Instruction {
+        self.call(MatchAction::DisputeMatch, Vec::new())
+    }
+
+    // Admin or match operator: let a disputed result stand, or void it and refund the fees
+    pub fn resolve_dispute(&self, refund: bool) -> Instruction {
+        self.call(MatchAction::ResolveDispute { refund }, Vec::new())
+    }
+
+    // Pay out the match fees. `payees` are both team owners when the match was voided or
+    // forfeited before lineups were locked; otherwise the metadata accounts of the ten lineup NFTs
+    // followed by every creator listed in them, in order.
+    pub fn settle_fees(&self, treasury: Pubkey, payees: &[Pubkey]) -> Instruction {
+        let mut accounts = self.fee_accounts();
+        accounts.push(AccountMeta::new(treasury, false));
+        accounts.push(AccountMeta::new_readonly(sysvar::instructions::ID, false));
+        accounts.extend(payees.iter().map(|payee| AccountMeta::new(*payee, false)));
+        self.call(MatchAction::SettleFees, accounts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::world::testing::{action, components, extra_accounts};
+
+    fn lineup() -> LineupEntities {
+        LineupEntities { team: Pubkey::new_unique(), players: std::array::from_fn(|_| Pubkey::new_unique()) }
+    }
+
+    fn setup() -> (MatchSystem, LineupHolders) {
+        let entities = MatchEntities {
+            queue: Pubkey::new_unique(),
+            match_entity: Pubkey::new_unique(),
+            team1: lineup(),
+            team2: lineup(),
+        };
+        let holders = LineupHolders {
+            team1_owner: Pubkey::new_unique(),
+            team1_mints: std::array::from_fn(|_| Pubkey::new_unique()),
+            team2_owner: Pubkey::new_unique(),
+            team2_mints: std::array::from_fn(|_| Pubkey::new_unique()),
+        };
+        (MatchSystem::new(Pubkey::new_unique(), Pubkey::new_unique(), entities), holders)
+    }
+
+    fn token_accounts(holders: &LineupHolders) -> Vec<(Pubkey, bool)> {
+        let team1 = holders.team1_mints.iter().map(|mint| pda::token_account(&holders.team1_owner, mint));
+        let team2 = holders.team2_mints.iter().map(|mint| pda::token_account(&holders.team2_owner, mint));
+        team1.chain(team2).map(|account| (account, false)).collect()
+    }
+
+    #[test]
+    fn every_action_passes_the_fourteen_components() {
+        let (system, _) = setup();
+        let entities = system.entities;
+        let player = |entity| pda::component(&player_stats::ID, &entity);
+
+        let mut expected = vec![
+            pda::component(&match_queue::ID, &entities.queue),
+            entities.match_state(),
+            pda::component(&team_data::ID, &entities.team1.team),
+            pda::component(&team_data::ID, &entities.team2.team),
+        ];
+        expected.extend(entities.team1.players.map(player));
+        expected.extend(entities.team2.players.map(player));
+
+        let instruction = system.schedule_match(4, RandomnessSource::Vrf);
+        assert_eq!(components(&instruction), expected);
+        assert_eq!(extra_accounts(&instruction), [(pda::game_config(), false)]);
+        assert_eq!(
+            action(&instruction),
+            json!({ "action": "scheduleMatch", "pendingMatchId": 4, "randomness": "vrf" })
+        );
+
+        for instruction in [
+            system.consent_match(),
+            system.commit_seed([1; 32]),
+            system.reveal_seed([2; 32]),
+            system.forfeit(),
+            system.claim_forfeit(),
+            system.dispute_match(),
+            system.resolve_dispute(true),
+        ] {
+            assert_eq!(components(&instruction), expected);
+            assert_eq!(extra_accounts(&instruction), [(pda::game_config(), false)]);
+        }
+    }
+
+    #[test]
+    fn fee_actions_pass_the_fee_vault() {
+        let (system, _) = setup();
+        let vault = pda::fee_vault(&system.entities.match_state());
+
+        assert_eq!(
+            extra_accounts(&system.pay_entry_fee()),
+            [
+                (pda::game_config(), false),
+                (vault, true),
+                (system_program::ID, false),
+                (sysvar::instructions::ID, false),
+            ]
+        );
+
+        let (treasury, payees) = (Pubkey::new_unique(), [Pubkey::new_unique(), Pubkey::new_unique()]);
+        assert_eq!(
+            extra_accounts(&system.settle_fees(treasury, &payees)),
+            [
+                (pda::game_config(), false),
+                (vault, true),
+                (system_program::ID, false),
+                (treasury, true),
+                (sysvar::instructions::ID, false),
+                (payees[0], true),
+                (payees[1], true),
+            ]
+        );
+    }
+
+    #[test]
+    fn lineup_actions_pass_the_token_accounts_team1_first() {
+        let (system, holders) = setup();
+        let mut expected = vec![(pda::game_config(), false)];
+        expected.extend(token_accounts(&holders));
+
+        assert_eq!(extra_accounts(&system.lock_lineups(&holders)), expected);
+        assert_eq!(extra_accounts(&system.simulate_match(&holders, RandomnessSource::CommitReveal)), expected);
+
+        // A VRF match reads the oracle's signature from the instructions sysvar
+        expected.push((sysvar::instructions::ID, false));
+        assert_eq!(extra_accounts(&system.simulate_match(&holders, RandomnessSource::Vrf)), expected);
+    }
+}
@@ -0,0 +1,71 @@
+// matchmaking_system calls on the match queue and one team's TeamData, derived from their
+// entities
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use esport_types::MatchmakingAction;
+
+use crate::game_config_meta;
+use crate::world::{self, ComponentRef};
+
+// Builds matchmaking_system instructions signed by `authority` on `queue` in `world`
+#[derive(Clone, Copy, Debug)]
+pub struct MatchmakingSystem {
+    pub world: Pubkey,
+    pub authority: Pubkey,
+    pub queue: Pubkey,
+}
+
+impl MatchmakingSystem {
+    pub fn new(world: Pubkey, authority: Pubkey, queue: Pubkey) -> Self {
+        Self { world, authority, queue }
+    }
+
+    fn call(&self, team: Pubkey, action: MatchmakingAction) -> Instruction {
+        world::apply(
+            matchmaking_system::ID,
+            self.authority,
+            self.world,
+            &[ComponentRef::new(match_queue::ID, self.queue), ComponentRef::new(team_data::ID, team)],
+            &[game_config_meta()],
+            serde_json::to_vec(&action).expect("actions serialize to JSON"),
+        )
+    }
+
+    // Queue the signer's team at its current rating
+    pub fn enqueue(&self, team: Pubkey, match_type: impl Into<String>) -> Instruction {
+        self.call(team, MatchmakingAction::Enqueue { match_type: match_type.into() })
+    }
+
+    // Leave the queue; anyone can do it for a disbanded team
+    pub fn leave(&self, team: Pubkey) -> Instruction {
+        self.call(team, MatchmakingAction::Leave)
+    }
+
+    // Pair queued teams. The system doesn't read `team`, but the world program needs a
+    // TeamData component; any team's will do.
+    pub fn matchmake(&self, team: Pubkey) -> Instruction {
+        self.call(team, MatchmakingAction::Matchmake)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::pda;
+    use crate::world::testing::{action, components, extra_accounts};
+
+    #[test]
+    fn actions_pass_the_queue_then_the_team() {
+        let [world, authority, queue, team] = std::array::from_fn(|_| Pubkey::new_unique());
+        let system = MatchmakingSystem::new(world, authority, queue);
+        let expected = [pda::component(&match_queue::ID, &queue), pda::component(&team_data::ID, &team)];
+
+        for instruction in [system.enqueue(team, "ranked"), system.leave(team), system.matchmake(team)] {
+            assert_eq!(components(&instruction), expected);
+            assert_eq!(extra_accounts(&instruction), [(pda::game_config(), false)]);
+        }
+        assert_eq!(action(&system.enqueue(team, "ranked")), json!({ "action": "enqueue", "matchType": "ranked" }));
+    }
+}
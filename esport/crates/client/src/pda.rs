@@ -0,0 +1,101 @@
+// Addresses derived by the world program, the components and the esport systems
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address;
+
+use crate::world::WORLD_PROGRAM_ID;
+
+// Metaplex token metadata program
+pub const METADATA_PROGRAM_ID: Pubkey = anchor_lang::pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+pub fn world(world_id: u64) -> Pubkey {
+    Pubkey::find_program_address(&[b"world", &world_id.to_be_bytes()], &WORLD_PROGRAM_ID).0
+}
+
+// Entity `entity_id` of a world, numbered in creation order
+pub fn entity(world_id: u64, entity_id: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"entity", &world_id.to_be_bytes(), &entity_id.to_be_bytes()],
+        &WORLD_PROGRAM_ID,
+    )
+    .0
+}
+
+// Data account of a component attached to an entity
+pub fn component(component_id: &Pubkey, entity: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"", entity.as_ref()], component_id).0
+}
+
+pub fn game_config() -> Pubkey {
+    Pubkey::find_program_address(&[esport::GAME_CONFIG_SEED], &esport::ID).0
+}
+
+// Stake vault of a team, keyed by its TeamData account
+pub fn stake_vault(team_data: &Pubkey) -> Pubkey {
+    team_system::stake::stake_vault_address(team_data).0
+}
+
+// Entry fee vault of a match, keyed by its MatchState account
+pub fn fee_vault(match_state: &Pubkey) -> Pubkey {
+    match_system::fees::fee_vault_address(match_state).0
+}
+
+// Metaplex metadata account of an NFT mint
+pub fn metadata(nft_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"metadata", METADATA_PROGRAM_ID.as_ref(), nft_mint.as_ref()], &METADATA_PROGRAM_ID).0
+}
+
+// Player record of an NFT mint, naming the PlayerStats mintPlayer bound to it
+pub fn player_record(nft_mint: &Pubkey) -> Pubkey {
+    player_system::registry::player_record_address(nft_mint).0
+}
+
+// Associated token account of an NFT holder
+pub fn token_account(owner: &Pubkey, nft_mint: &Pubkey) -> Pubkey {
+    get_associated_token_address(owner, nft_mint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn derived(seeds: &[&[u8]], program_id: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(seeds, program_id).0
+    }
+
+    #[test]
+    fn world_addresses() {
+        // World and entity ids are big-endian
+        let (three, two_five_eight) = ([0, 0, 0, 0, 0, 0, 0, 3], [0, 0, 0, 0, 0, 0, 1, 2]);
+        assert_eq!(world(3), derived(&[b"world", &three], &WORLD_PROGRAM_ID));
+        assert_eq!(entity(3, 258), derived(&[b"entity", &three, &two_five_eight], &WORLD_PROGRAM_ID));
+        assert_ne!(entity(3, 1), entity(1, 3));
+
+        let (component_id, entity) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_eq!(component(&component_id, &entity), derived(&[b"", entity.as_ref()], &component_id));
+    }
+
+    #[test]
+    fn program_addresses() {
+        let key = Pubkey::new_unique();
+        assert_eq!(game_config(), derived(&[b"game_config"], &esport::ID));
+        assert_eq!(stake_vault(&key), derived(&[b"stake_vault", key.as_ref()], &team_system::ID));
+        assert_eq!(fee_vault(&key), derived(&[b"fee_vault", key.as_ref()], &match_system::ID));
+        assert_eq!(player_record(&key), derived(&[b"player", key.as_ref()], &player_system::ID));
+    }
+
+    #[test]
+    fn nft_addresses() {
+        let (owner, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        assert_eq!(
+            metadata(&mint),
+            derived(&[b"metadata", METADATA_PROGRAM_ID.as_ref(), mint.as_ref()], &METADATA_PROGRAM_ID)
+        );
+        assert_eq!(
+            token_account(&owner, &mint),
+            derived(
+                &[owner.as_ref(), anchor_spl::token::ID.as_ref(), mint.as_ref()],
+                &anchor_spl::associated_token::ID
+            )
+        );
+    }
+}
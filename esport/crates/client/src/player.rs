@@ -0,0 +1,96 @@
+// player_system calls. The player entity's PlayerStats is derived from it, and the
+// metadata, player record and token accounts from the NFT mint.
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::{system_program, sysvar};
+use esport_types::{ChosenAttributes, PlayerAction, Role};
+
+use crate::world::{self, ComponentRef};
+use crate::{game_config_meta, pda};
+
+// Builds player_system instructions signed by `authority` in `world`
+#[derive(Clone, Copy, Debug)]
+pub struct PlayerSystem {
+    pub world: Pubkey,
+    pub authority: Pubkey,
+}
+
+impl PlayerSystem {
+    pub fn new(world: Pubkey, authority: Pubkey) -> Self {
+        Self { world, authority }
+    }
+
+    fn call(&self, player: Pubkey, action: PlayerAction, extra_accounts: Vec<AccountMeta>) -> Instruction {
+        let mut accounts = vec![game_config_meta()];
+        accounts.extend(extra_accounts);
+
+        world::apply(
+            player_system::ID,
+            self.authority,
+            self.world,
+            &[ComponentRef::new(player_stats::ID, player)],
+            &accounts,
+            serde_json::to_vec(&action).expect("actions serialize to JSON"),
+        )
+    }
+
+    fn mint_accounts(player_nft_mint: &Pubkey) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new_readonly(pda::metadata(player_nft_mint), false),
+            AccountMeta::new(pda::player_record(player_nft_mint), false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(sysvar::instructions::ID, false),
+        ]
+    }
+
+    // Bind the player entity's empty PlayerStats to an NFT held by the signer, with a role
+    // and attributes rolled from the NFT
+    pub fn mint_player(&self, player: Pubkey, player_nft_mint: Pubkey) -> Instruction {
+        let mut accounts = Self::mint_accounts(&player_nft_mint);
+        accounts.push(AccountMeta::new_readonly(pda::token_account(&self.authority, &player_nft_mint), false));
+        self.call(
+            player,
+            PlayerAction::MintPlayer { player_nft_mint, attributes: ChosenAttributes::default() },
+            accounts,
+        )
+    }
+
+    // Mint authority only: bind the PlayerStats with the given role and attributes, in
+    // PlayerStats order. The authority doesn't need to hold the NFT.
+    pub fn mint_player_with(&self, player: Pubkey, player_nft_mint: Pubkey, role: Role, attributes: [u8; 7]) -> Instruction {
+        self.call(
+            player,
+            PlayerAction::MintPlayer { player_nft_mint, attributes: ChosenAttributes::new(role, attributes) },
+            Self::mint_accounts(&player_nft_mint),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::testing::{action, components, extra_accounts};
+
+    #[test]
+    fn mint_player_passes_the_nft_accounts() {
+        let [world, authority, player, mint] = std::array::from_fn(|_| Pubkey::new_unique());
+        let system = PlayerSystem::new(world, authority);
+        let mut expected = vec![
+            (pda::game_config(), false),
+            (pda::metadata(&mint), false),
+            (pda::player_record(&mint), true),
+            (system_program::ID, false),
+            (sysvar::instructions::ID, false),
+        ];
+
+        // The mint authority doesn't hold the NFT, so passes no token account
+        let instruction = system.mint_player_with(player, mint, Role::Support, [50; 7]);
+        assert_eq!(components(&instruction), [pda::component(&player_stats::ID, &player)]);
+        assert_eq!(extra_accounts(&instruction), expected);
+        assert_eq!(action(&instruction)["action"], "mintPlayer");
+
+        let instruction = system.mint_player(player, mint);
+        expected.push((pda::token_account(&authority, &mint), false));
+        assert_eq!(extra_accounts(&instruction), expected);
+    }
+}
@@ -0,0 +1,91 @@
+// queue_system calls. Every action takes the match queue, the challenger's TeamData and
+// the opponent's, derived from their entities.
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use esport_types::QueueAction;
+
+use crate::game_config_meta;
+use crate::world::{self, ComponentRef};
+
+// Builds queue_system instructions signed by `authority` for challenges between `team1`
+// (the challenger) and `team2` in `world`
+#[derive(Clone, Copy, Debug)]
+pub struct QueueSystem {
+    pub world: Pubkey,
+    pub authority: Pubkey,
+    pub queue: Pubkey,
+    pub team1: Pubkey,
+    pub team2: Pubkey,
+}
+
+impl QueueSystem {
+    pub fn new(world: Pubkey, authority: Pubkey, queue: Pubkey, team1: Pubkey, team2: Pubkey) -> Self {
+        Self { world, authority, queue, team1, team2 }
+    }
+
+    fn call(&self, action: QueueAction) -> Instruction {
+        world::apply(
+            queue_system::ID,
+            self.authority,
+            self.world,
+            &[
+                ComponentRef::new(match_queue::ID, self.queue),
+                ComponentRef::new(team_data::ID, self.team1),
+                ComponentRef::new(team_data::ID, self.team2),
+            ],
+            &[game_config_meta()],
+            serde_json::to_vec(&action).expect("actions serialize to JSON"),
+        )
+    }
+
+    // Team 1's owner challenges team 2
+    pub fn challenge(&self, match_type: impl Into<String>) -> Instruction {
+        self.call(QueueAction::Challenge { match_type: match_type.into() })
+    }
+
+    // Team 2's owner answers a challenge
+    pub fn accept(&self, pending_match_id: u64) -> Instruction {
+        self.call(QueueAction::Accept { pending_match_id })
+    }
+
+    pub fn decline(&self, pending_match_id: u64) -> Instruction {
+        self.call(QueueAction::Decline { pending_match_id })
+    }
+
+    // Team 1's owner withdraws a challenge team 2 hasn't accepted
+    pub fn cancel(&self, pending_match_id: u64) -> Instruction {
+        self.call(QueueAction::Cancel { pending_match_id })
+    }
+
+    // Anyone: drop pending matches and matchmaking tickets past their deadline
+    pub fn expire(&self) -> Instruction {
+        self.call(QueueAction::Expire)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::pda;
+    use crate::world::testing::{action, components, extra_accounts};
+
+    #[test]
+    fn actions_pass_the_queue_then_both_teams() {
+        let [world, authority, queue, team1, team2] = std::array::from_fn(|_| Pubkey::new_unique());
+        let system = QueueSystem::new(world, authority, queue, team1, team2);
+        let expected = [
+            pda::component(&match_queue::ID, &queue),
+            pda::component(&team_data::ID, &team1),
+            pda::component(&team_data::ID, &team2),
+        ];
+
+        let actions = [system.challenge("bo1"), system.accept(1), system.decline(1), system.cancel(1), system.expire()];
+        for instruction in actions {
+            assert_eq!(components(&instruction), expected);
+            assert_eq!(extra_accounts(&instruction), [(pda::game_config(), false)]);
+        }
+        assert_eq!(action(&system.cancel(7)), json!({ "action": "cancel", "pendingMatchId": 7 }));
+    }
+}
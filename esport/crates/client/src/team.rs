@@ -0,0 +1,197 @@
+// team_system calls. Entities are passed by entity key; the TeamData and PlayerStats
+// component accounts and every extra account are derived from them.
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::{system_program, sysvar};
+use esport_types::{Role, StrategyType, TeamAction};
+
+use crate::world::{self, ComponentRef};
+use crate::{game_config_meta, pda};
+
+// Builds team_system instructions signed by `authority` in `world`
+#[derive(Clone, Copy, Debug)]
+pub struct TeamSystem {
+    pub world: Pubkey,
+    pub authority: Pubkey,
+}
+
+impl TeamSystem {
+    pub fn new(world: Pubkey, authority: Pubkey) -> Self {
+        Self { world, authority }
+    }
+
+    // Components in the order of team_system's Components: TeamData, then PlayerStats
+    // when the action involves a player
+    fn call(&self, team: Pubkey, player: Option<Pubkey>, action: TeamAction, extra_accounts: Vec<AccountMeta>) -> Instruction {
+        let mut components = vec![ComponentRef::new(team_data::ID, team)];
+        components.extend(player.map(|player| ComponentRef::new(player_stats::ID, player)));
+
+        let mut accounts = vec![game_config_meta()];
+        accounts.extend(extra_accounts);
+
+        world::apply(
+            team_system::ID,
+            self.authority,
+            self.world,
+            &components,
+            &accounts,
+            serde_json::to_vec(&action).expect("actions serialize to JSON"),
+        )
+    }
+
+    // Create a team owned by the signer, staking `stake_amount` lamports (the configured
+    // minimum when None) into the team's stake vault
+    pub fn create_team(&self, team: Pubkey, team_name: impl Into<String>, stake_amount: Option<u64>) -> Instruction {
+        let vault = pda::stake_vault(&pda::component(&team_data::ID, &team));
+        self.call(
+            team,
+            None,
+            TeamAction::CreateTeam { team_name: team_name.into(), stake_amount },
+            vec![
+                AccountMeta::new(vault, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new_readonly(sysvar::instructions::ID, false),
+            ],
+        )
+    }
+
+    // Add a player NFT held by the signer to the roster
+    pub fn add_player(&self, team: Pubkey, player: Pubkey, player_nft_mint: Pubkey, position: Role) -> Instruction {
+        self.call(
+            team,
+            Some(player),
+            TeamAction::AddPlayerToTeam { player_nft_mint, position },
+            vec![
+                AccountMeta::new_readonly(pda::token_account(&self.authority, &player_nft_mint), false),
+                AccountMeta::new_readonly(pda::player_record(&player_nft_mint), false),
+            ],
+        )
+    }
+
+    pub fn remove_player(&self, team: Pubkey, player: Pubkey, player_nft_mint: Pubkey) -> Instruction {
+        self.call(team, Some(player), TeamAction::RemovePlayerFromTeam { player_nft_mint }, Vec::new())
+    }
+
+    pub fn set_strategy(&self, team: Pubkey, strategy_type: StrategyType, strategy_description: impl Into<String>) -> Instruction {
+        self.call(
+            team,
+            None,
+            TeamAction::SetStrategy { strategy_type, strategy_description: strategy_description.into() },
+            Vec::new(),
+        )
+    }
+
+    // Disband the team, refunding the stake minus penalties to the signer; `player` is
+    // released at the same time, the rest of the roster through `release_player`
+    pub fn disband_team(&self, team: Pubkey, player: Option<Pubkey>, treasury: Pubkey) -> Instruction {
+        let vault = pda::stake_vault(&pda::component(&team_data::ID, &team));
+        self.call(
+            team,
+            player,
+            TeamAction::DisbandTeam,
+            vec![
+                AccountMeta::new(vault, false),
+                AccountMeta::new_readonly(system_program::ID, false),
+                AccountMeta::new(treasury, false),
+                AccountMeta::new_readonly(sysvar::instructions::ID, false),
+            ],
+        )
+    }
+
+    // Free a player from a disbanded team, or from a team that no longer lists it. A player
+    // sold while rostered is released by its new holder, who passes the NFT mint so their
+    // token account is included.
+    pub fn release_player(&self, team: Pubkey, player: Pubkey, held_nft_mint: Option<Pubkey>) -> Instruction {
+        let extra_accounts = held_nft_mint
+            .map(|mint| AccountMeta::new_readonly(pda::token_account(&self.authority, &mint), false))
+            .into_iter()
+            .collect();
+        self.call(team, Some(player), TeamAction::ReleasePlayer, extra_accounts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::world::testing::{action, components, extra_accounts};
+
+    fn setup() -> (TeamSystem, Pubkey, Pubkey, Pubkey) {
+        let system = TeamSystem::new(Pubkey::new_unique(), Pubkey::new_unique());
+        (system, Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique())
+    }
+
+    #[test]
+    fn create_team_funds_the_stake_vault() {
+        let (system, team, _, _) = setup();
+        let instruction = system.create_team(team, "Alpha", Some(5));
+        let team_data = pda::component(&team_data::ID, &team);
+
+        assert_eq!(components(&instruction), [team_data]);
+        assert_eq!(
+            extra_accounts(&instruction),
+            [
+                (pda::game_config(), false),
+                (pda::stake_vault(&team_data), true),
+                (system_program::ID, false),
+                (sysvar::instructions::ID, false),
+            ]
+        );
+        assert_eq!(action(&instruction), json!({ "action": "createTeam", "teamName": "Alpha", "stakeAmount": 5 }));
+    }
+
+    #[test]
+    fn player_actions_pass_the_player_stats() {
+        let (system, team, player, mint) = setup();
+        let player_components = [pda::component(&team_data::ID, &team), pda::component(&player_stats::ID, &player)];
+
+        let instruction = system.add_player(team, player, mint, Role::Mid);
+        assert_eq!(components(&instruction), player_components);
+        assert_eq!(
+            extra_accounts(&instruction),
+            [
+                (pda::game_config(), false),
+                (pda::token_account(&system.authority, &mint), false),
+                (pda::player_record(&mint), false),
+            ]
+        );
+
+        let instruction = system.remove_player(team, player, mint);
+        assert_eq!(components(&instruction), player_components);
+        assert_eq!(extra_accounts(&instruction), [(pda::game_config(), false)]);
+
+        let instruction = system.set_strategy(team, StrategyType::Defensive, "Hold");
+        assert_eq!(components(&instruction), player_components[..1]);
+        assert_eq!(extra_accounts(&instruction), [(pda::game_config(), false)]);
+    }
+
+    #[test]
+    fn disband_and_release() {
+        let (system, team, player, mint) = setup();
+        let treasury = Pubkey::new_unique();
+        let team_data = pda::component(&team_data::ID, &team);
+
+        let instruction = system.disband_team(team, Some(player), treasury);
+        assert_eq!(components(&instruction), [team_data, pda::component(&player_stats::ID, &player)]);
+        assert_eq!(
+            extra_accounts(&instruction),
+            [
+                (pda::game_config(), false),
+                (pda::stake_vault(&team_data), true),
+                (system_program::ID, false),
+                (treasury, true),
+                (sysvar::instructions::ID, false),
+            ]
+        );
+        assert_eq!(components(&system.disband_team(team, None, treasury)), [team_data]);
+
+        let instruction = system.release_player(team, player, None);
+        assert_eq!(extra_accounts(&instruction), [(pda::game_config(), false)]);
+        let instruction = system.release_player(team, player, Some(mint));
+        assert_eq!(
+            extra_accounts(&instruction),
+            [(pda::game_config(), false), (pda::token_account(&system.authority, &mint), false)]
+        );
+    }
+}
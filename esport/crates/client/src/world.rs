@@ -0,0 +1,169 @@
+// Instructions of the Bolt world program: entities, components and system calls.
+//
+// The world program is an Anchor program, so instruction data is the 8-byte sighash of
+// the instruction name followed by the Borsh-encoded arguments.
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::{system_program, sysvar};
+use anchor_lang::AnchorSerialize;
+
+use crate::pda;
+
+// The Bolt world program, as deployed on devnet and loaded by the tests
+pub const WORLD_PROGRAM_ID: Pubkey = anchor_lang::pubkey!("WorLD15A7CrDwLcLy4fRqtaTb9fbd8o8iqiEMUDse2n");
+
+// A component of an entity, as listed in a system's Components
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ComponentRef {
+    pub component_id: Pubkey,
+    pub entity: Pubkey,
+}
+
+impl ComponentRef {
+    pub fn new(component_id: Pubkey, entity: Pubkey) -> Self {
+        Self { component_id, entity }
+    }
+
+    pub fn address(&self) -> Pubkey {
+        pda::component(&self.component_id, &self.entity)
+    }
+}
+
+fn instruction_data(name: &str, args: impl AnchorSerialize) -> Vec<u8> {
+    let mut data = hash(format!("global:{name}").as_bytes()).to_bytes()[..8].to_vec();
+    args.serialize(&mut data).expect("serializing into a Vec can't fail");
+    data
+}
+
+// Create the world's next entity. `entity_id` is the world's entity count before the call.
+pub fn add_entity(payer: Pubkey, world: Pubkey, world_id: u64, entity_id: u64) -> Instruction {
+    Instruction {
+        program_id: WORLD_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(pda::entity(world_id, entity_id), false),
+            AccountMeta::new(world, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: instruction_data("add_entity", None::<Vec<u8>>),
+    }
+}
+
+// Create a component account for an entity. Components created with `authority` set to
+// the world program can be written by any approved system.
+pub fn initialize_component(payer: Pubkey, component: ComponentRef, authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: WORLD_PROGRAM_ID,
+        accounts: vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(component.address(), false),
+            AccountMeta::new_readonly(component.entity, false),
+            AccountMeta::new_readonly(component.component_id, false),
+            AccountMeta::new_readonly(authority, false),
+            AccountMeta::new_readonly(sysvar::instructions::ID, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: instruction_data("initialize_component", ()),
+    }
+}
+
+// Run a system on the given components, in the order of the system's Components struct.
+// The world program passes the component accounts to the system, followed by the extra
+// accounts; the world program ID separates the two lists.
+pub fn apply(
+    system_id: Pubkey,
+    authority: Pubkey,
+    world: Pubkey,
+    components: &[ComponentRef],
+    extra_accounts: &[AccountMeta],
+    args: Vec<u8>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(system_id, false),
+        // Writable: stakes and entry fees are paid from the authority
+        AccountMeta::new(authority, true),
+        AccountMeta::new_readonly(sysvar::instructions::ID, false),
+        AccountMeta::new_readonly(world, false),
+    ];
+    for component in components {
+        accounts.push(AccountMeta::new_readonly(component.component_id, false));
+        accounts.push(AccountMeta::new(component.address(), false));
+    }
+    if !extra_accounts.is_empty() {
+        accounts.push(AccountMeta::new_readonly(WORLD_PROGRAM_ID, false));
+        accounts.extend_from_slice(extra_accounts);
+    }
+
+    Instruction {
+        program_id: WORLD_PROGRAM_ID,
+        accounts,
+        data: instruction_data("apply", args),
+    }
+}
+
+// The parts of an `apply` instruction the systems see
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+
+    // Component addresses, in Components order
+    pub fn components(instruction: &Instruction) -> Vec<Pubkey> {
+        let end = separator(instruction);
+        instruction.accounts[4..end].chunks(2).map(|pair| pair[1].pubkey).collect()
+    }
+
+    // Extra accounts as (address, writable), starting with the game config
+    pub fn extra_accounts(instruction: &Instruction) -> Vec<(Pubkey, bool)> {
+        let start = separator(instruction) + 1;
+        instruction.accounts[start..].iter().map(|meta| (meta.pubkey, meta.is_writable)).collect()
+    }
+
+    // The system's arguments, a Borsh byte vector holding the JSON action
+    pub fn action(instruction: &Instruction) -> serde_json::Value {
+        serde_json::from_slice(&instruction.data[12..]).unwrap()
+    }
+
+    fn separator(instruction: &Instruction) -> usize {
+        let accounts = &instruction.accounts;
+        accounts.iter().position(|meta| meta.pubkey == WORLD_PROGRAM_ID).unwrap_or(accounts.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_lists_components_then_extras() {
+        let (system, authority, world) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let component = || ComponentRef::new(Pubkey::new_unique(), Pubkey::new_unique());
+        let components = [component(), component()];
+        let extra = AccountMeta::new(Pubkey::new_unique(), false);
+        let instruction = apply(system, authority, world, &components, std::slice::from_ref(&extra), vec![1, 2]);
+
+        let accounts: Vec<(Pubkey, bool, bool)> =
+            instruction.accounts.iter().map(|meta| (meta.pubkey, meta.is_signer, meta.is_writable)).collect();
+        assert_eq!(
+            accounts,
+            [
+                (system, false, false),
+                (authority, true, true),
+                (sysvar::instructions::ID, false, false),
+                (world, false, false),
+                (components[0].component_id, false, false),
+                (components[0].address(), false, true),
+                (components[1].component_id, false, false),
+                (components[1].address(), false, true),
+                (WORLD_PROGRAM_ID, false, false),
+                (extra.pubkey, false, true),
+            ]
+        );
+        assert_eq!(instruction.data[..8], hash(b"global:apply").to_bytes()[..8]);
+        assert_eq!(instruction.data[8..], [2, 0, 0, 0, 1, 2]);
+
+        // Without extras there is no separator
+        let instruction = apply(system, authority, world, &components, &[], Vec::new());
+        assert_eq!(instruction.accounts.len(), 8);
+    }
+}
@@ -0,0 +1,197 @@
+// Read-only HTTP/JSON API over the index, for the frontend.
+//
+//   GET /teams?active=1&limit=50      leaderboard, highest rating first
+//   GET /teams/<address>              team with its roster and rating history
+//   GET /teams/<address>/ratings      rating after each match, oldest first
+//   GET /teams/<address>/events       team event timeline, oldest first
+//   GET /players?team=<address>       players, optionally of one team
+//   GET /players/<nft mint>
+//   GET /matches?team=&status=&limit= matches, newest first
+//   GET /matches/<match key>          match with its lineups and event timeline
+//
+// Teams and matches are keyed by their TeamData and MatchState accounts, as in events.
+use std::error::Error;
+
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, Params};
+use serde_json::{json, Map, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 500;
+
+const TEAM_COLUMNS: &str = "address, name, owner, rating, rating_deviation, wins, losses, strategy, \
+     strategy_description, stake_amount, penalties, refund, active, created_at, created_slot";
+
+enum Reply {
+    Found(Value),
+    NotFound,
+    BadRequest(String),
+    Failed(String),
+}
+
+// Serve until the process exits, one request at a time
+pub fn serve(conn: &Connection, listen: &str) -> Result<(), Box<dyn Error>> {
+    let server = Server::http(listen).map_err(|error| -> Box<dyn Error> { error })?;
+    for request in server.incoming_requests() {
+        let reply = if *request.method() == Method::Get {
+            route(conn, request.url()).unwrap_or_else(|error| Reply::Failed(error.to_string()))
+        } else {
+            Reply::BadRequest("only GET is supported".into())
+        };
+        respond(request, reply);
+    }
+    Ok(())
+}
+
+fn respond(request: Request, reply: Reply) {
+    let (status, body) = match reply {
+        Reply::Found(value) => (200, value),
+        Reply::NotFound => (404, json!({ "error": "not found" })),
+        Reply::BadRequest(error) => (400, json!({ "error": error })),
+        Reply::Failed(error) => (500, json!({ "error": error })),
+    };
+    let header = |name: &str, value: &str| Header::from_bytes(name, value).expect("valid header");
+    let response = Response::from_string(body.to_string())
+        .with_status_code(status)
+        .with_header(header("Content-Type", "application/json"))
+        .with_header(header("Access-Control-Allow-Origin", "*"));
+    if let Err(error) = request.respond(response) {
+        eprintln!("response failed: {error}");
+    }
+}
+
+// Query string parameters; keys and values are base58, numbers and labels, so nothing is
+// percent-decoded
+fn query(url: &str) -> (Vec<&str>, Vec<(&str, &str)>) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let segments = path.split('/').filter(|segment| !segment.is_empty()).collect();
+    let params = query.split('&').filter_map(|pair| pair.split_once('=')).collect();
+    (segments, params)
+}
+
+fn param<'a>(params: &[(&'a str, &'a str)], key: &str) -> Option<&'a str> {
+    params.iter().find(|(name, _)| *name == key).map(|(_, value)| *value).filter(|value| !value.is_empty())
+}
+
+fn limit(params: &[(&str, &str)]) -> Result<u32, String> {
+    match param(params, "limit") {
+        None => Ok(DEFAULT_LIMIT),
+        Some(limit) => match limit.parse::<u32>() {
+            Ok(limit) => Ok(limit.min(MAX_LIMIT)),
+            Err(_) => Err(format!("invalid limit: {limit}")),
+        },
+    }
+}
+
+fn route(conn: &Connection, url: &str) -> rusqlite::Result<Reply> {
+    let (segments, params) = query(url);
+    let limit = match limit(&params) {
+        Ok(limit) => limit,
+        Err(error) => return Ok(Reply::BadRequest(error)),
+    };
+
+    let reply = match segments.as_slice() {
+        ["teams"] => {
+            let active = param(&params, "active").map(|active| active == "1" || active == "true");
+            Reply::Found(rows(
+                conn,
+                &format!(
+                    "SELECT {TEAM_COLUMNS} FROM teams WHERE ?1 IS NULL OR active = ?1
+                     ORDER BY rating DESC, wins DESC, address LIMIT ?2"
+                ),
+                (active, limit),
+            )?)
+        }
+        ["teams", address] => match row(conn, &format!("SELECT {TEAM_COLUMNS} FROM teams WHERE address = ?1"), [address])? {
+            Some(mut team) => {
+                team["roster"] = rows(
+                    conn,
+                    "SELECT nft_mint, position, active FROM roster WHERE team = ?1 ORDER BY rowid",
+                    [address],
+                )?;
+                team["ratings"] = ratings(conn, address)?;
+                Reply::Found(team)
+            }
+            None => Reply::NotFound,
+        },
+        ["teams", address, "ratings"] => Reply::Found(ratings(conn, address)?),
+        ["teams", address, "events"] => Reply::Found(rows(
+            conn,
+            "SELECT signature, slot, block_time, name, match_key FROM events WHERE team = ?1
+             ORDER BY slot, signature, idx",
+            [address],
+        )?),
+        ["players"] => Reply::Found(rows(
+            conn,
+            "SELECT * FROM players WHERE ?1 IS NULL OR current_team = ?1 ORDER BY nft_mint LIMIT ?2",
+            (param(&params, "team"), limit),
+        )?),
+        ["players", mint] => match row(conn, "SELECT * FROM players WHERE nft_mint = ?1", [mint])? {
+            Some(player) => Reply::Found(player),
+            None => Reply::NotFound,
+        },
+        ["matches"] => Reply::Found(rows(
+            conn,
+            "SELECT * FROM matches
+             WHERE (?1 IS NULL OR team1 = ?1 OR team2 = ?1) AND (?2 IS NULL OR status = ?2)
+             ORDER BY COALESCE(finished_slot, scheduled_slot, 0) DESC, COALESCE(scheduled_at, 0) DESC LIMIT ?3",
+            (param(&params, "team"), param(&params, "status"), limit),
+        )?),
+        ["matches", key] => match row(conn, "SELECT * FROM matches WHERE match_key = ?1", [key])? {
+            Some(mut found) => {
+                let lineup = |side: u8| {
+                    rows(conn, "SELECT slot, nft_mint FROM lineups WHERE match_key = ?1 AND side = ?2 ORDER BY slot", (key, side))
+                };
+                found["team1_lineup"] = lineup(1)?;
+                found["team2_lineup"] = lineup(2)?;
+                found["events"] = rows(
+                    conn,
+                    "SELECT signature, slot, block_time, name, team FROM events WHERE match_key = ?1
+                     ORDER BY slot, signature, idx",
+                    [key],
+                )?;
+                Reply::Found(found)
+            }
+            None => Reply::NotFound,
+        },
+        _ => Reply::NotFound,
+    };
+    Ok(reply)
+}
+
+fn ratings(conn: &Connection, team: &str) -> rusqlite::Result<Value> {
+    rows(
+        conn,
+        "SELECT match_key, slot, block_time, rating, delta, win FROM rating_history WHERE team = ?1 ORDER BY slot",
+        [team],
+    )
+}
+
+// Rows as JSON objects keyed by column name
+fn rows(conn: &Connection, sql: &str, params: impl Params) -> rusqlite::Result<Value> {
+    let mut statement = conn.prepare(sql)?;
+    let columns: Vec<String> = statement.column_names().into_iter().map(String::from).collect();
+    let rows = statement.query_map(params, |row| {
+        let mut object = Map::new();
+        for (index, column) in columns.iter().enumerate() {
+            let value = match row.get_ref(index)? {
+                ValueRef::Null => Value::Null,
+                ValueRef::Integer(integer) => json!(integer),
+                ValueRef::Real(real) => json!(real),
+                ValueRef::Text(text) => json!(String::from_utf8_lossy(text)),
+                ValueRef::Blob(blob) => json!(blob),
+            };
+            object.insert(column.clone(), value);
+        }
+        Ok(Value::Object(object))
+    })?;
+    rows.collect::<rusqlite::Result<Vec<_>>>().map(Value::Array)
+}
+
+fn row(conn: &Connection, sql: &str, params: impl Params) -> rusqlite::Result<Option<Value>> {
+    match rows(conn, sql, params)? {
+        Value::Array(mut found) if !found.is_empty() => Ok(Some(found.swap_remove(0))),
+        _ => Ok(None),
+    }
+}
@@ -0,0 +1,825 @@
+// SQLite database of teams, players, matches and rating history.
+//
+// Events build the history: every indexed transaction and event is recorded, so
+// re-ingesting the same transactions is a no-op, and rating_history has one row per team
+// per played or forfeited match. Component account snapshots overwrite the current state
+// of teams, players and matches with what is on-chain, which also fills in what events
+// don't carry (player attributes and records, rating deviation).
+use std::path::Path;
+
+use anchor_lang::prelude::Pubkey;
+use esport_client::accounts::{self, MatchState, PlayerStats, TeamData};
+use esport_types::events::{self, Event};
+use esport_types::rating::{DEFAULT_RATING, DEFAULT_RATING_DEVIATION};
+use esport_types::MatchStatus;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::logs;
+use crate::source::{ProgramAccount, Transaction};
+
+fn schema() -> String {
+    format!(
+        "
+        CREATE TABLE IF NOT EXISTS transactions (
+            signature TEXT PRIMARY KEY,
+            slot INTEGER NOT NULL,
+            block_time INTEGER
+        );
+        CREATE TABLE IF NOT EXISTS events (
+            signature TEXT NOT NULL,
+            idx INTEGER NOT NULL,
+            slot INTEGER NOT NULL,
+            block_time INTEGER,
+            name TEXT NOT NULL,
+            match_key TEXT,
+            team TEXT,
+            PRIMARY KEY (signature, idx)
+        );
+        CREATE INDEX IF NOT EXISTS events_match ON events (match_key);
+        CREATE INDEX IF NOT EXISTS events_team ON events (team);
+        CREATE TABLE IF NOT EXISTS cursors (
+            program TEXT PRIMARY KEY,
+            signature TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS teams (
+            address TEXT PRIMARY KEY,
+            name TEXT NOT NULL DEFAULT '',
+            owner TEXT NOT NULL DEFAULT '',
+            rating INTEGER NOT NULL DEFAULT {DEFAULT_RATING},
+            rating_deviation INTEGER NOT NULL DEFAULT {DEFAULT_RATING_DEVIATION},
+            wins INTEGER NOT NULL DEFAULT 0,
+            losses INTEGER NOT NULL DEFAULT 0,
+            strategy TEXT,
+            strategy_description TEXT,
+            stake_amount INTEGER NOT NULL DEFAULT 0,
+            penalties INTEGER NOT NULL DEFAULT 0,
+            refund INTEGER,
+            active INTEGER NOT NULL DEFAULT 1,
+            created_at INTEGER,
+            created_slot INTEGER
+        );
+        CREATE TABLE IF NOT EXISTS roster (
+            team TEXT NOT NULL,
+            nft_mint TEXT NOT NULL,
+            position TEXT NOT NULL,
+            active INTEGER NOT NULL DEFAULT 1,
+            PRIMARY KEY (team, nft_mint)
+        );
+        CREATE TABLE IF NOT EXISTS players (
+            nft_mint TEXT PRIMARY KEY,
+            address TEXT,
+            role TEXT,
+            mechanical INTEGER,
+            game_knowledge INTEGER,
+            team_communication INTEGER,
+            adaptability INTEGER,
+            consistency INTEGER,
+            form INTEGER,
+            potential INTEGER,
+            matches_played INTEGER NOT NULL DEFAULT 0,
+            wins INTEGER NOT NULL DEFAULT 0,
+            losses INTEGER NOT NULL DEFAULT 0,
+            current_team TEXT
+        );
+        CREATE TABLE IF NOT EXISTS matches (
+            match_key TEXT PRIMARY KEY,
+            team1 TEXT NOT NULL,
+            team2 TEXT NOT NULL,
+            pending_match_id INTEGER,
+            match_type TEXT,
+            status TEXT NOT NULL,
+            entry_fee INTEGER NOT NULL DEFAULT 0,
+            team1_fee_paid INTEGER NOT NULL DEFAULT 0,
+            team2_fee_paid INTEGER NOT NULL DEFAULT 0,
+            team2_consented INTEGER NOT NULL DEFAULT 0,
+            fees_settled INTEGER NOT NULL DEFAULT 0,
+            team1_score INTEGER,
+            team2_score INTEGER,
+            winner TEXT,
+            seed TEXT,
+            penalty INTEGER,
+            scheduled_slot INTEGER,
+            finished_slot INTEGER,
+            scheduled_at INTEGER,
+            finished_at INTEGER
+        );
+        CREATE INDEX IF NOT EXISTS matches_team1 ON matches (team1);
+        CREATE INDEX IF NOT EXISTS matches_team2 ON matches (team2);
+        CREATE TABLE IF NOT EXISTS lineups (
+            match_key TEXT NOT NULL,
+            side INTEGER NOT NULL,
+            slot INTEGER NOT NULL,
+            nft_mint TEXT NOT NULL,
+            PRIMARY KEY (match_key, side, slot)
+        );
+        CREATE TABLE IF NOT EXISTS rating_history (
+            team TEXT NOT NULL,
+            match_key TEXT NOT NULL,
+            slot INTEGER NOT NULL,
+            block_time INTEGER,
+            rating INTEGER NOT NULL,
+            delta INTEGER NOT NULL,
+            win INTEGER NOT NULL,
+            PRIMARY KEY (team, match_key)
+        );
+        "
+    )
+}
+
+// What an ingest added
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Summary {
+    pub transactions: usize,
+    pub events: usize,
+    pub accounts: usize,
+}
+
+impl Summary {
+    pub fn add(&mut self, other: Summary) {
+        self.transactions += other.transactions;
+        self.events += other.events;
+        self.accounts += other.accounts;
+    }
+}
+
+// Transaction an event was emitted in
+struct Context {
+    slot: u64,
+    block_time: Option<i64>,
+}
+
+pub struct Db {
+    conn: Connection,
+}
+
+impl Db {
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        // WAL lets the API read while a sync writes from another connection
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        conn.execute_batch(&schema())?;
+        Ok(Self { conn })
+    }
+
+    pub fn conn(&self) -> &Connection {
+        &self.conn
+    }
+
+    pub fn is_indexed(&self, signature: &str) -> rusqlite::Result<bool> {
+        self.conn
+            .query_row("SELECT 1 FROM transactions WHERE signature = ?1", [signature], |_| Ok(()))
+            .optional()
+            .map(|row| row.is_some())
+    }
+
+    // Newest indexed signature of `program`
+    pub fn cursor(&self, program: &Pubkey) -> rusqlite::Result<Option<String>> {
+        self.conn
+            .query_row("SELECT signature FROM cursors WHERE program = ?1", [program.to_string()], |row| row.get(0))
+            .optional()
+    }
+
+    pub fn set_cursor(&self, program: &Pubkey, signature: &str) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT INTO cursors (program, signature) VALUES (?1, ?2)
+             ON CONFLICT (program) DO UPDATE SET signature = excluded.signature",
+            params![program.to_string(), signature],
+        )?;
+        Ok(())
+    }
+
+    // Record a transaction and apply its events, once per signature
+    pub fn index_transaction(&mut self, transaction: &Transaction) -> rusqlite::Result<Summary> {
+        let Some(signature) = transaction.signature() else {
+            return Ok(Summary::default());
+        };
+        let tx = self.conn.transaction()?;
+        let inserted = tx.execute(
+            "INSERT OR IGNORE INTO transactions (signature, slot, block_time) VALUES (?1, ?2, ?3)",
+            params![signature, transaction.slot, transaction.block_time],
+        )?;
+        if inserted == 0 {
+            return Ok(Summary::default());
+        }
+
+        let context = Context { slot: transaction.slot, block_time: transaction.block_time };
+        let events = logs::events(transaction.logs());
+        for (index, event) in events.iter().enumerate() {
+            let (match_key, team) = subjects(event);
+            tx.execute(
+                "INSERT INTO events (signature, idx, slot, block_time, name, match_key, team)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    signature,
+                    index,
+                    context.slot,
+                    context.block_time,
+                    event.name(),
+                    match_key.map(|key| key.to_string()),
+                    team.map(|team| team.to_string()),
+                ],
+            )?;
+            apply(&tx, &context, event)?;
+        }
+        tx.commit()?;
+        Ok(Summary { transactions: 1, events: events.len(), accounts: 0 })
+    }
+
+    // Overwrite the indexed state with a component account snapshot. Accounts of other
+    // programs and uninitialized components are skipped.
+    pub fn index_account(&mut self, account: &ProgramAccount) -> rusqlite::Result<Summary> {
+        let Some(data) = account.data() else {
+            return Ok(Summary::default());
+        };
+        let owner = account.account.owner.as_str();
+        let address = account.pubkey.as_str();
+        let tx = self.conn.transaction()?;
+
+        let indexed = if owner == team_data::ID.to_string() {
+            match accounts::team_data(&data) {
+                Ok(team) if team.owner != Pubkey::default() => snapshot_team(&tx, address, &team).map(|_| true)?,
+                _ => false,
+            }
+        } else if owner == player_stats::ID.to_string() {
+            match accounts::player_stats(&data) {
+                Ok(player) if player.nft_mint != Pubkey::default() => snapshot_player(&tx, address, &player).map(|_| true)?,
+                _ => false,
+            }
+        } else if owner == match_state::ID.to_string() {
+            match accounts::match_state(&data) {
+                Ok(state) if state.status != MatchStatus::None => snapshot_match(&tx, address, &state).map(|_| true)?,
+                _ => false,
+            }
+        } else {
+            false
+        };
+        tx.commit()?;
+        Ok(Summary { transactions: 0, events: 0, accounts: usize::from(indexed) })
+    }
+}
+
+fn status(status: MatchStatus) -> String {
+    format!("{status:?}")
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+// The match and team an event is about, for the event timelines
+fn subjects(event: &Event) -> (Option<Pubkey>, Option<Pubkey>) {
+    match event {
+        Event::TeamCreated(e) => (None, Some(e.team)),
+        Event::PlayerAdded(e) => (None, Some(e.team)),
+        Event::PlayerRemoved(e) => (None, Some(e.team)),
+        Event::PlayerReleased(e) => (None, Some(e.team)),
+        Event::StrategySet(e) => (None, Some(e.team)),
+        Event::TeamDisbanded(e) => (None, Some(e.team)),
+        Event::MatchScheduled(e) => (Some(e.match_key), None),
+        Event::EntryFeePaid(e) => (Some(e.match_key), Some(e.team)),
+        Event::LineupsLocked(e) => (Some(e.match_key), None),
+        Event::MatchConsented(e) => (Some(e.match_key), Some(e.team)),
+        Event::SeedCommitted(e) => (Some(e.match_key), Some(e.team)),
+        Event::SeedRevealed(e) => (Some(e.match_key), Some(e.team)),
+        Event::MatchSimulated(e) => (Some(e.match_key), None),
+        Event::MatchForfeited(e) => (Some(e.match_key), Some(e.winner)),
+        Event::MatchVoided(e) => (Some(e.match_key), None),
+        Event::MatchDisputed(e) => (Some(e.match_key), Some(e.team)),
+        Event::DisputeResolved(e) => (Some(e.match_key), None),
+        Event::FeesSettled(e) => (Some(e.match_key), None),
+    }
+}
+
+// A played or forfeited match's rating change for one team
+fn record_result(
+    tx: &rusqlite::Transaction,
+    context: &Context,
+    team: &Pubkey,
+    match_key: &Pubkey,
+    rating: u16,
+    delta: i32,
+    win: bool,
+) -> rusqlite::Result<()> {
+    tx.execute(
+        "INSERT OR IGNORE INTO rating_history (team, match_key, slot, block_time, rating, delta, win)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![team.to_string(), match_key.to_string(), context.slot, context.block_time, rating, delta, win],
+    )?;
+    tx.execute(
+        "INSERT INTO teams (address, rating, wins, losses) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT (address) DO UPDATE SET
+             rating = excluded.rating, wins = wins + excluded.wins, losses = losses + excluded.losses",
+        params![team.to_string(), rating, u32::from(win), u32::from(!win)],
+    )?;
+    Ok(())
+}
+
+fn apply(tx: &rusqlite::Transaction, context: &Context, event: &Event) -> rusqlite::Result<()> {
+    match event {
+        Event::TeamCreated(e) => {
+            tx.execute(
+                "INSERT INTO teams (address, name, owner, stake_amount, active, created_at, created_slot)
+                 VALUES (?1, ?2, ?3, ?4, 1, ?5, ?6)
+                 ON CONFLICT (address) DO UPDATE SET
+                     name = excluded.name, owner = excluded.owner, stake_amount = excluded.stake_amount,
+                     active = 1, created_at = excluded.created_at, created_slot = excluded.created_slot",
+                params![e.team.to_string(), e.name, e.owner.to_string(), e.stake_amount, context.block_time, context.slot],
+            )?;
+        }
+        Event::PlayerAdded(e) => {
+            tx.execute(
+                "INSERT INTO roster (team, nft_mint, position) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (team, nft_mint) DO UPDATE SET position = excluded.position, active = 1",
+                params![e.team.to_string(), e.player_nft_mint.to_string(), e.position],
+            )?;
+            tx.execute(
+                "INSERT INTO players (nft_mint, current_team) VALUES (?1, ?2)
+                 ON CONFLICT (nft_mint) DO UPDATE SET current_team = excluded.current_team",
+                params![e.player_nft_mint.to_string(), e.team.to_string()],
+            )?;
+        }
+        Event::PlayerRemoved(events::PlayerRemoved { team, player_nft_mint })
+        | Event::PlayerReleased(events::PlayerReleased { team, player_nft_mint }) => {
+            tx.execute(
+                "DELETE FROM roster WHERE team = ?1 AND nft_mint = ?2",
+                params![team.to_string(), player_nft_mint.to_string()],
+            )?;
+            tx.execute(
+                "UPDATE players SET current_team = NULL WHERE nft_mint = ?1 AND current_team = ?2",
+                params![player_nft_mint.to_string(), team.to_string()],
+            )?;
+        }
+        Event::StrategySet(e) => {
+            tx.execute(
+                "UPDATE teams SET strategy = ?2, strategy_description = ?3 WHERE address = ?1",
+                params![e.team.to_string(), e.strategy_type.label(), e.strategy_description],
+            )?;
+        }
+        Event::TeamDisbanded(e) => {
+            tx.execute(
+                "UPDATE teams SET active = 0, refund = ?2, penalties = ?3, stake_amount = 0 WHERE address = ?1",
+                params![e.team.to_string(), e.refund, e.penalties],
+            )?;
+        }
+        Event::MatchScheduled(e) => {
+            tx.execute(
+                "INSERT INTO matches
+                     (match_key, team1, team2, pending_match_id, match_type, status, entry_fee, scheduled_slot, scheduled_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                 ON CONFLICT (match_key) DO NOTHING",
+                params![
+                    e.match_key.to_string(),
+                    e.team1.to_string(),
+                    e.team2.to_string(),
+                    e.pending_match_id,
+                    e.match_type,
+                    status(MatchStatus::Scheduled),
+                    e.entry_fee,
+                    context.slot,
+                    context.block_time,
+                ],
+            )?;
+        }
+        Event::EntryFeePaid(e) => {
+            tx.execute(
+                "UPDATE matches SET
+                     team1_fee_paid = team1_fee_paid OR team1 = ?2,
+                     team2_fee_paid = team2_fee_paid OR team2 = ?2
+                 WHERE match_key = ?1",
+                params![e.match_key.to_string(), e.team.to_string()],
+            )?;
+        }
+        Event::LineupsLocked(e) => {
+            tx.execute(
+                "UPDATE matches SET status = ?2 WHERE match_key = ?1",
+                params![e.match_key.to_string(), status(MatchStatus::LineupsLocked)],
+            )?;
+            insert_lineups(tx, &e.match_key.to_string(), &e.team1_lineup, &e.team2_lineup)?;
+        }
+        Event::MatchConsented(e) => {
+            tx.execute(
+                "UPDATE matches SET team2_consented = team2_consented OR team2 = ?2 WHERE match_key = ?1",
+                params![e.match_key.to_string(), e.team.to_string()],
+            )?;
+        }
+        // Seeds only show in the timeline until the match is played
+        Event::SeedCommitted(_) | Event::SeedRevealed(_) => {}
+        Event::MatchSimulated(e) => {
+            let winner = match e.team1_score.cmp(&e.team2_score) {
+                std::cmp::Ordering::Greater => Some(e.team1.to_string()),
+                std::cmp::Ordering::Less => Some(e.team2.to_string()),
+                std::cmp::Ordering::Equal => None,
+            };
+            tx.execute(
+                "UPDATE matches SET status = ?2, team1_score = ?3, team2_score = ?4, winner = ?5, seed = ?6,
+                     finished_slot = ?7, finished_at = ?8
+                 WHERE match_key = ?1",
+                params![
+                    e.match_key.to_string(),
+                    status(MatchStatus::Finished),
+                    e.team1_score,
+                    e.team2_score,
+                    winner,
+                    hex(&e.seed),
+                    context.slot,
+                    context.block_time,
+                ],
+            )?;
+            let team1_won = e.team1_score > e.team2_score;
+            let team2_won = e.team2_score > e.team1_score;
+            record_result(tx, context, &e.team1, &e.match_key, e.team1_rating, e.team1_rating_delta, team1_won)?;
+            record_result(tx, context, &e.team2, &e.match_key, e.team2_rating, e.team2_rating_delta, team2_won)?;
+        }
+        Event::MatchForfeited(e) => {
+            let team1_won = e.winner == e.team1;
+            tx.execute(
+                "UPDATE matches SET status = ?2, team1_score = ?3, team2_score = ?4, winner = ?5, penalty = ?6,
+                     finished_slot = ?7, finished_at = ?8
+                 WHERE match_key = ?1",
+                params![
+                    e.match_key.to_string(),
+                    status(MatchStatus::Forfeited),
+                    u8::from(team1_won),
+                    u8::from(!team1_won),
+                    e.winner.to_string(),
+                    e.penalty,
+                    context.slot,
+                    context.block_time,
+                ],
+            )?;
+            record_result(tx, context, &e.team1, &e.match_key, e.team1_rating, e.team1_rating_delta, team1_won)?;
+            record_result(tx, context, &e.team2, &e.match_key, e.team2_rating, e.team2_rating_delta, !team1_won)?;
+        }
+        Event::MatchVoided(e) => {
+            tx.execute(
+                "UPDATE matches SET status = ?2, finished_slot = ?3, finished_at = ?4 WHERE match_key = ?1",
+                params![e.match_key.to_string(), status(MatchStatus::Voided), context.slot, context.block_time],
+            )?;
+        }
+        Event::MatchDisputed(e) => {
+            tx.execute(
+                "UPDATE matches SET status = ?2 WHERE match_key = ?1",
+                params![e.match_key.to_string(), status(MatchStatus::Disputed)],
+            )?;
+        }
+        Event::DisputeResolved(e) => {
+            let resolved = if e.refund { MatchStatus::Voided } else { MatchStatus::Finished };
+            tx.execute(
+                "UPDATE matches SET status = ?2 WHERE match_key = ?1",
+                params![e.match_key.to_string(), status(resolved)],
+            )?;
+
+            // A voided result is taken back on-chain, and so out of the ratings and records
+            if e.refund {
+                tx.execute(
+                    "UPDATE teams SET rating = teams.rating - h.delta, wins = teams.wins - h.win,
+                         losses = teams.losses - (1 - h.win)
+                     FROM rating_history h WHERE h.match_key = ?1 AND h.team = teams.address",
+                    [e.match_key.to_string()],
+                )?;
+                tx.execute("DELETE FROM rating_history WHERE match_key = ?1", [e.match_key.to_string()])?;
+            }
+        }
+        Event::FeesSettled(e) => {
+            tx.execute("UPDATE matches SET fees_settled = 1 WHERE match_key = ?1", [e.match_key.to_string()])?;
+        }
+    }
+    Ok(())
+}
+
+fn insert_lineups(tx: &rusqlite::Transaction, match_key: &str, team1: &[Pubkey; 5], team2: &[Pubkey; 5]) -> rusqlite::Result<()> {
+    for (side, lineup) in [(1, team1), (2, team2)] {
+        for (slot, mint) in lineup.iter().enumerate() {
+            tx.execute(
+                "INSERT OR REPLACE INTO lineups (match_key, side, slot, nft_mint) VALUES (?1, ?2, ?3, ?4)",
+                params![match_key, side, slot, mint.to_string()],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn snapshot_team(tx: &rusqlite::Transaction, address: &str, team: &TeamData) -> rusqlite::Result<()> {
+    tx.execute(
+        "INSERT INTO teams
+             (address, name, owner, rating, rating_deviation, strategy, strategy_description, stake_amount,
+              penalties, active, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+         ON CONFLICT (address) DO UPDATE SET
+             name = excluded.name, owner = excluded.owner, rating = excluded.rating,
+             rating_deviation = excluded.rating_deviation, strategy = excluded.strategy,
+             strategy_description = excluded.strategy_description, stake_amount = excluded.stake_amount,
+             penalties = excluded.penalties, active = excluded.active, created_at = excluded.created_at",
+        params![
+            address,
+            team.name,
+            team.owner.to_string(),
+            team.rating,
+            team.rating_deviation,
+            team.strategy.strategy_type.label(),
+            team.strategy.description,
+            team.stake_amount,
+            team.penalties,
+            team.active,
+            team.created_at,
+        ],
+    )?;
+    tx.execute("DELETE FROM roster WHERE team = ?1", [address])?;
+    for player in &team.roster {
+        tx.execute(
+            "INSERT INTO roster (team, nft_mint, position, active) VALUES (?1, ?2, ?3, ?4)",
+            params![address, player.nft_mint.to_string(), player.position, player.active],
+        )?;
+    }
+    Ok(())
+}
+
+fn snapshot_player(tx: &rusqlite::Transaction, address: &str, player: &PlayerStats) -> rusqlite::Result<()> {
+    let current_team = (player.current_team != Pubkey::default()).then(|| player.current_team.to_string());
+    tx.execute(
+        "INSERT INTO players
+             (nft_mint, address, role, mechanical, game_knowledge, team_communication, adaptability, consistency,
+              form, potential, matches_played, wins, losses, current_team)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+         ON CONFLICT (nft_mint) DO UPDATE SET
+             address = excluded.address, role = excluded.role, mechanical = excluded.mechanical,
+             game_knowledge = excluded.game_knowledge, team_communication = excluded.team_communication,
+             adaptability = excluded.adaptability, consistency = excluded.consistency, form = excluded.form,
+             potential = excluded.potential, matches_played = excluded.matches_played, wins = excluded.wins,
+             losses = excluded.losses, current_team = excluded.current_team",
+        params![
+            player.nft_mint.to_string(),
+            address,
+            player.role,
+            player.mechanical,
+            player.game_knowledge,
+            player.team_communication,
+            player.adaptability,
+            player.consistency,
+            player.form,
+            player.potential,
+            player.matches_played,
+            player.wins,
+            player.losses,
+            current_team,
+        ],
+    )?;
+    Ok(())
+}
+
+fn snapshot_match(tx: &rusqlite::Transaction, address: &str, state: &MatchState) -> rusqlite::Result<()> {
+    let played = matches!(
+        state.status,
+        MatchStatus::Finished | MatchStatus::Forfeited | MatchStatus::Disputed | MatchStatus::Voided
+    );
+    let winner = (state.winner != Pubkey::default()).then(|| state.winner.to_string());
+    let time = |at: i64| (at != 0).then_some(at);
+    tx.execute(
+        "INSERT INTO matches
+             (match_key, team1, team2, pending_match_id, match_type, status, entry_fee, team1_fee_paid,
+              team2_fee_paid, team2_consented, fees_settled, team1_score, team2_score, winner, seed,
+              scheduled_at, finished_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
+         ON CONFLICT (match_key) DO UPDATE SET
+             team1 = excluded.team1, team2 = excluded.team2, pending_match_id = excluded.pending_match_id,
+             match_type = excluded.match_type, status = excluded.status, entry_fee = excluded.entry_fee,
+             team1_fee_paid = excluded.team1_fee_paid, team2_fee_paid = excluded.team2_fee_paid,
+             team2_consented = excluded.team2_consented, fees_settled = excluded.fees_settled,
+             team1_score = excluded.team1_score, team2_score = excluded.team2_score, winner = excluded.winner,
+             seed = excluded.seed, scheduled_at = excluded.scheduled_at, finished_at = excluded.finished_at",
+        params![
+            address,
+            state.team1.to_string(),
+            state.team2.to_string(),
+            state.pending_match_id,
+            state.match_type,
+            status(state.status),
+            state.entry_fee,
+            state.team1_fee_paid,
+            state.team2_fee_paid,
+            state.team2_consented,
+            state.fees_settled,
+            played.then_some(state.team1_score),
+            played.then_some(state.team2_score),
+            winner,
+            (state.seed != [0; 32]).then(|| hex(&state.seed)),
+            time(state.scheduled_at),
+            time(state.finished_at),
+        ],
+    )?;
+    if state.lineups_locked() {
+        insert_lineups(tx, address, &state.team1_lineup, &state.team2_lineup)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::AccountSerialize;
+    use base64::prelude::{Engine, BASE64_STANDARD};
+
+    use super::*;
+    use crate::source::{AccountInfo, Fixture};
+
+    // Recorded logs of two teams playing a disputed best of three and a forfeit, with a
+    // failed transaction and a direct, forged team_system call mixed in
+    const MATCH_LOGS: &str = include_str!("../fixtures/match-logs.json");
+
+    fn key(n: u8) -> String {
+        Pubkey::new_from_array([n; 32]).to_string()
+    }
+
+    fn ingest() -> (Db, Summary) {
+        let mut db = Db::open(Path::new(":memory:")).unwrap();
+        let fixture: Fixture = serde_json::from_str(MATCH_LOGS).unwrap();
+        let summary = fixture.ingest(&mut db).unwrap();
+        (db, summary)
+    }
+
+    fn count(db: &Db, sql: &str, match_key: &str) -> usize {
+        db.conn().query_row(sql, [match_key], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn fixture_builds_history() {
+        let (db, summary) = ingest();
+        assert_eq!((summary.transactions, summary.events), (33, 34));
+
+        // Neither the failed nor the forged team exists
+        let names: Vec<String> = db
+            .conn()
+            .prepare("SELECT name FROM teams ORDER BY name")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(names, ["Alpha", "Bravo"]);
+
+        let team = |address: String| {
+            db.conn()
+                .query_row(
+                    "SELECT rating, wins, losses, active, strategy, penalties, refund,
+                         (SELECT COUNT(*) FROM roster WHERE team = address)
+                     FROM teams WHERE address = ?1",
+                    [address],
+                    |row| {
+                        Ok((
+                            row.get::<_, u16>(0)?,
+                            row.get::<_, u32>(1)?,
+                            row.get::<_, u32>(2)?,
+                            row.get::<_, bool>(3)?,
+                            row.get::<_, String>(4)?,
+                            row.get::<_, u64>(5)?,
+                            row.get::<_, Option<u64>>(6)?,
+                            row.get::<_, usize>(7)?,
+                        ))
+                    },
+                )
+                .unwrap()
+        };
+        assert_eq!(team(key(1)), (1_090, 2, 0, true, "Aggressive".into(), 0, None, 5));
+        assert_eq!(team(key(2)), (912, 0, 2, false, "Defensive".into(), 10_000_000, Some(90_000_000), 4));
+
+        let rating_changes = count(&db, "SELECT COUNT(*) FROM rating_history WHERE team = ?1", &key(1));
+        assert_eq!(rating_changes, 2);
+    }
+
+    #[test]
+    fn fixture_follows_both_matches() {
+        let (db, _) = ingest();
+        let state = |match_key: String| {
+            db.conn()
+                .query_row(
+                    "SELECT status, team1_score, team2_score, winner, team1_fee_paid, team2_fee_paid,
+                         team2_consented, fees_settled, penalty
+                     FROM matches WHERE match_key = ?1",
+                    [match_key],
+                    |row| {
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            row.get::<_, u8>(1)?,
+                            row.get::<_, u8>(2)?,
+                            row.get::<_, String>(3)?,
+                            row.get::<_, bool>(4)?,
+                            row.get::<_, bool>(5)?,
+                            row.get::<_, bool>(6)?,
+                            row.get::<_, bool>(7)?,
+                            row.get::<_, Option<u64>>(8)?,
+                        ))
+                    },
+                )
+                .unwrap()
+        };
+
+        // The dispute was rejected, so the simulated result stands
+        assert_eq!(state(key(5)), ("Finished".into(), 2, 1, key(1), true, true, true, true, None));
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM lineups WHERE match_key = ?1", &key(5)), 10);
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM events WHERE match_key = ?1", &key(5)), 13);
+
+        assert_eq!(state(key(6)), ("Forfeited".into(), 1, 0, key(1), true, false, false, true, Some(10_000_000)));
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM lineups WHERE match_key = ?1", &key(6)), 0);
+    }
+
+    #[test]
+    fn fixture_applies_a_slot_in_recorded_order() {
+        let (db, _) = ingest();
+        let events: Vec<String> = db
+            .conn()
+            .prepare("SELECT signature FROM events WHERE slot = 14 ORDER BY rowid")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(events, ["strategy-alpha", "strategy-bravo-first", "strategy-bravo"]);
+
+        // Bravo's second strategy of the slot is the one that holds
+        let strategy: (String, String) = db
+            .conn()
+            .query_row("SELECT strategy, strategy_description FROM teams WHERE address = ?1", [key(2)], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!(strategy, ("Defensive".into(), "Hold and scale".into()));
+    }
+
+    #[test]
+    fn upheld_dispute_takes_back_the_result() {
+        let (mut db, _) = ingest();
+        let played = Pubkey::new_from_array([5; 32]);
+        let tx = db.conn.transaction().unwrap();
+        let resolved = events::DisputeResolved { match_key: played, refund: true };
+        apply(&tx, &Context { slot: 40, block_time: None }, &Event::DisputeResolved(resolved)).unwrap();
+        tx.commit().unwrap();
+
+        // Only the forfeit still counts
+        let team = |address: String| {
+            db.conn()
+                .query_row("SELECT rating, wins, losses FROM teams WHERE address = ?1", [address], |row| {
+                    Ok((row.get::<_, u16>(0)?, row.get::<_, u32>(1)?, row.get::<_, u32>(2)?))
+                })
+                .unwrap()
+        };
+        assert_eq!(team(key(1)), (1_028, 1, 0));
+        assert_eq!(team(key(2)), (974, 0, 1));
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM rating_history WHERE match_key = ?1", &key(5)), 0);
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM matches WHERE match_key = ?1 AND status = 'Voided'", &key(5)), 1);
+    }
+
+    #[test]
+    fn reingesting_is_a_no_op() {
+        let (mut db, _) = ingest();
+        let fixture: Fixture = serde_json::from_str(MATCH_LOGS).unwrap();
+        let summary = fixture.ingest(&mut db).unwrap();
+        assert_eq!((summary.transactions, summary.events), (0, 0));
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM events WHERE signature <> ?1", ""), 34);
+    }
+
+    fn match_account(address: &str, state: &MatchState) -> ProgramAccount {
+        let mut data = Vec::new();
+        state.try_serialize(&mut data).unwrap();
+        ProgramAccount {
+            pubkey: address.to_string(),
+            account: AccountInfo {
+                owner: match_state::ID.to_string(),
+                data: (BASE64_STANDARD.encode(data), "base64".to_string()),
+            },
+        }
+    }
+
+    #[test]
+    fn snapshots_record_locked_lineups_only() {
+        let mut db = Db::open(Path::new(":memory:")).unwrap();
+        let lineup = |first: u8| std::array::from_fn(|i| Pubkey::new_from_array([first + i as u8; 32]));
+
+        // Forfeited before locking: the lineups were never picked
+        let unlocked = MatchState {
+            status: MatchStatus::Forfeited,
+            team1: Pubkey::new_from_array([1; 32]),
+            team2: Pubkey::new_from_array([2; 32]),
+            winner: Pubkey::new_from_array([1; 32]),
+            scheduled_at: 1,
+            finished_at: 2,
+            ..Default::default()
+        };
+        db.index_account(&match_account(&key(6), &unlocked)).unwrap();
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM lineups WHERE match_key = ?1", &key(6)), 0);
+
+        let locked = MatchState {
+            status: MatchStatus::LineupsLocked,
+            team1_lineup: lineup(10),
+            team2_lineup: lineup(20),
+            locked_at: 3,
+            ..unlocked
+        };
+        db.index_account(&match_account(&key(5), &locked)).unwrap();
+        assert_eq!(count(&db, "SELECT COUNT(*) FROM lineups WHERE match_key = ?1", &key(5)), 10);
+        let status: String = db
+            .conn()
+            .query_row("SELECT status FROM matches WHERE match_key = ?1", [key(5)], |row| row.get(0))
+            .unwrap();
+        assert_eq!(status, "LineupsLocked");
+    }
+}
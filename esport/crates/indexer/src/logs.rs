@@ -0,0 +1,144 @@
+// Event extraction from transaction logs.
+//
+// Systems run through the world program, so their events are logged inside nested
+// invocations. The invoke/success lines are tracked as a stack and only "Program data"
+// lines logged while team_system or match_system is running, invoked by the world program,
+// are decoded. Another program logging the same bytes, or calling a system directly to
+// get it to log them, can't forge an event.
+use base64::prelude::{Engine, BASE64_STANDARD};
+use esport_client::WORLD_PROGRAM_ID;
+use esport_types::events::Event;
+
+const DATA_PREFIX: &str = "Program data: ";
+
+// Events in the order they were emitted
+pub fn events(logs: &[String]) -> Vec<Event> {
+    let emitters: [String; 2] = [team_system::ID.to_string(), match_system::ID.to_string()];
+    let world = WORLD_PROGRAM_ID.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        if let Some(data) = line.strip_prefix(DATA_PREFIX) {
+            let running = match stack.as_slice() {
+                [.., parent, program] => *parent == world && emitters.iter().any(|id| id == program),
+                _ => false,
+            };
+            if let (true, Ok(bytes)) = (running, BASE64_STANDARD.decode(data.trim())) {
+                events.extend(Event::decode(&bytes));
+            }
+            continue;
+        }
+
+        let Some(rest) = line.strip_prefix("Program ") else { continue };
+        let mut words = rest.split_whitespace();
+        let (Some(program), Some(status)) = (words.next(), words.next()) else { continue };
+        match status {
+            "invoke" => stack.push(program),
+            "success" | "failed:" if stack.last() == Some(&program) => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::Event as _;
+    use esport_types::events::TeamCreated;
+
+    use super::*;
+
+    fn team_created(name: &str) -> TeamCreated {
+        TeamCreated { team: Pubkey::new_unique(), owner: Pubkey::new_unique(), name: name.into(), stake_amount: 1 }
+    }
+
+    fn data(event: &TeamCreated) -> String {
+        format!("{DATA_PREFIX}{}", BASE64_STANDARD.encode(event.data()))
+    }
+
+    #[test]
+    fn decodes_events_of_world_applied_systems() {
+        let (world, team_system) = (WORLD_PROGRAM_ID, team_system::ID);
+        let first = team_created("Alpha");
+        let second = team_created("Bravo");
+        let logs = [
+            format!("Program {world} invoke [1]"),
+            "Program log: Instruction: Apply".into(),
+            format!("Program {team_system} invoke [2]"),
+            data(&first),
+            format!("Program {team_system} consumed 100 of 200 compute units"),
+            format!("Program {team_system} success"),
+            format!("Program {world} success"),
+            format!("Program {world} invoke [1]"),
+            format!("Program {} invoke [2]", match_system::ID),
+            data(&second),
+            format!("Program {} success", match_system::ID),
+            format!("Program {world} success"),
+        ];
+
+        assert_eq!(events(&logs), [Event::TeamCreated(first), Event::TeamCreated(second)]);
+    }
+
+    #[test]
+    fn ignores_events_outside_a_world_applied_system() {
+        let (world, team_system) = (WORLD_PROGRAM_ID, team_system::ID);
+        let forger = Pubkey::new_unique();
+        let logs = [
+            // Logged by the world program itself, after the system returned
+            format!("Program {world} invoke [1]"),
+            format!("Program {team_system} invoke [2]"),
+            format!("Program {team_system} success"),
+            data(&team_created("After")),
+            format!("Program {world} success"),
+            // The system called directly, not through the world
+            format!("Program {team_system} invoke [1]"),
+            data(&team_created("Direct")),
+            format!("Program {team_system} success"),
+            // The system called by another program
+            format!("Program {forger} invoke [1]"),
+            format!("Program {team_system} invoke [2]"),
+            data(&team_created("Wrapped")),
+            format!("Program {team_system} success"),
+            format!("Program {forger} success"),
+            // A program the system calls
+            format!("Program {world} invoke [1]"),
+            format!("Program {team_system} invoke [2]"),
+            format!("Program {forger} invoke [3]"),
+            data(&team_created("Nested")),
+            format!("Program {forger} success"),
+            format!("Program {team_system} success"),
+            format!("Program {world} success"),
+            // Another program logging event bytes
+            format!("Program {forger} invoke [1]"),
+            data(&team_created("Forged")),
+            format!("Program {forger} success"),
+        ];
+
+        assert_eq!(events(&logs), []);
+    }
+
+    #[test]
+    fn failed_frames_are_popped() {
+        let (world, team_system) = (WORLD_PROGRAM_ID, team_system::ID);
+        let event = team_created("Alpha");
+        let logs = [
+            format!("Program {world} invoke [1]"),
+            format!("Program {team_system} invoke [2]"),
+            format!("Program {team_system} failed: custom program error: 0x1770"),
+            format!("Program {world} failed: custom program error: 0x1770"),
+            data(&event),
+            format!("Program {world} invoke [1]"),
+            format!("Program {team_system} invoke [2]"),
+            "Program data: not base64!".into(),
+            data(&event),
+            format!("Program {team_system} success"),
+            format!("Program {world} success"),
+        ];
+
+        assert_eq!(events(&logs), [Event::TeamCreated(event)]);
+    }
+}
@@ -0,0 +1,90 @@
+// Local indexer for the 5VS5dotGG programs.
+//
+// Reads the events team_system and match_system emit, and the TeamData, PlayerStats and
+// MatchState component accounts, from a local validator or a fixture file of recorded
+// RPC responses, keeps them in a SQLite database of teams, players, matches and rating
+// history, and serves that database as a small JSON API for the frontend.
+mod api;
+mod db;
+mod logs;
+mod source;
+
+use std::error::Error;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+use clap::{Parser, Subcommand};
+
+use db::{Db, Summary};
+use source::{Fixture, Rpc};
+
+#[derive(Parser)]
+#[command(name = "esport-indexer", about = "Index esport events and accounts into SQLite and serve them")]
+struct Cli {
+    #[arg(long, default_value = "esport-index.sqlite", help = "SQLite database, created if missing")]
+    db: PathBuf,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Index a fixture file of recorded getTransaction and getProgramAccounts responses
+    Ingest {
+        #[arg(help = "Fixture file")]
+        fixture: PathBuf,
+    },
+    /// Index new transactions and current accounts from a validator once
+    Sync {
+        #[arg(long, default_value = "http://127.0.0.1:8899", help = "Validator JSON-RPC URL")]
+        rpc: String,
+    },
+    /// Serve the JSON API, optionally syncing from a validator in the background
+    Serve {
+        #[arg(long, default_value = "127.0.0.1:8080", help = "Address to listen on")]
+        listen: String,
+
+        #[arg(long, help = "Validator JSON-RPC URL to keep syncing from")]
+        rpc: Option<String>,
+
+        #[arg(long, default_value_t = 2, help = "Seconds between syncs")]
+        interval: u64,
+    },
+}
+
+fn report(summary: Summary) {
+    println!(
+        "indexed {} transactions, {} events, {} accounts",
+        summary.transactions, summary.events, summary.accounts
+    );
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+    let mut db = Db::open(&cli.db)?;
+
+    match cli.command {
+        Command::Ingest { fixture } => report(Fixture::load(&fixture)?.ingest(&mut db)?),
+        Command::Sync { rpc } => report(Rpc::new(rpc).sync(&mut db)?),
+        Command::Serve { listen, rpc, interval } => {
+            if let Some(rpc) = rpc {
+                // The syncer writes through its own connection
+                let mut writer = Db::open(&cli.db)?;
+                let rpc = Rpc::new(rpc);
+                thread::spawn(move || loop {
+                    match rpc.sync(&mut writer) {
+                        Ok(summary) if summary.transactions > 0 => report(summary),
+                        Ok(_) => {}
+                        Err(error) => eprintln!("sync failed: {error}"),
+                    }
+                    thread::sleep(Duration::from_secs(interval));
+                });
+            }
+            println!("serving on http://{listen}");
+            api::serve(db.conn(), &listen)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,321 @@
+// Where transactions and accounts come from: a local validator's JSON-RPC, or a fixture
+// file of recorded RPC responses.
+//
+// A fixture is one JSON object:
+//   {"transactions": [<getTransaction result>, ...],
+//    "accounts": [<getProgramAccounts entry>, ...]}
+// with transactions in "json" encoding and accounts in "base64" encoding, so responses
+// dumped from a validator can be replayed as they are. Transactions are recorded newest
+// first, the order getSignaturesForAddress returns them in.
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use anchor_lang::prelude::Pubkey;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::db::{Db, Summary};
+
+// Programs whose transactions carry esport events
+const EVENT_PROGRAMS: [Pubkey; 2] = [team_system::ID, match_system::ID];
+
+// Component programs whose accounts are snapshotted
+const COMPONENT_PROGRAMS: [Pubkey; 3] = [team_data::ID, player_stats::ID, match_state::ID];
+
+// getSignaturesForAddress's maximum page size
+const SIGNATURE_PAGE: usize = 1000;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub transaction: TransactionBody,
+    pub meta: Option<TransactionMeta>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TransactionBody {
+    pub signatures: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionMeta {
+    pub err: Option<Value>,
+    pub log_messages: Option<Vec<String>>,
+}
+
+impl Transaction {
+    pub fn signature(&self) -> Option<&str> {
+        self.transaction.signatures.first().map(String::as_str)
+    }
+
+    // Logs of a successful transaction; a failed one's events were rolled back
+    pub fn logs(&self) -> &[String] {
+        match &self.meta {
+            Some(TransactionMeta { err: None, log_messages: Some(logs) }) => logs,
+            _ => &[],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ProgramAccount {
+    pub pubkey: String,
+    pub account: AccountInfo,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct AccountInfo {
+    pub owner: String,
+    // [data, encoding]
+    pub data: (String, String),
+}
+
+impl ProgramAccount {
+    pub fn data(&self) -> Option<Vec<u8>> {
+        let (data, encoding) = &self.account.data;
+        if encoding != "base64" {
+            return None;
+        }
+        BASE64_STANDARD.decode(data).ok()
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Fixture {
+    #[serde(default)]
+    pub transactions: Vec<Transaction>,
+    #[serde(default)]
+    pub accounts: Vec<ProgramAccount>,
+}
+
+impl Fixture {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_reader(BufReader::new(File::open(path)?))?)
+    }
+
+    // Index the transactions oldest first, then the account snapshots. The sort is stable,
+    // so transactions of one slot keep their recorded order.
+    pub fn ingest(&self, db: &mut Db) -> rusqlite::Result<Summary> {
+        let mut transactions: Vec<&Transaction> = self.transactions.iter().rev().collect();
+        transactions.sort_by_key(|transaction| transaction.slot);
+
+        let mut summary = Summary::default();
+        for transaction in transactions {
+            summary.add(db.index_transaction(transaction)?);
+        }
+        for account in &self.accounts {
+            summary.add(db.index_account(account)?);
+        }
+        Ok(summary)
+    }
+}
+
+#[derive(Deserialize)]
+struct Response<T> {
+    result: Option<T>,
+    error: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct SignatureInfo {
+    signature: String,
+    slot: u64,
+}
+
+#[derive(Deserialize)]
+struct BlockSignatures {
+    signatures: Vec<String>,
+}
+
+// Merge the programs' signature lists, each oldest first, into one oldest-first order.
+// Every list keeps its own order; a slot with signatures from more than one list is put
+// in block order with `block`, since the lists alone can't tell how they interleave.
+fn merge(
+    lists: &[Vec<SignatureInfo>],
+    mut block: impl FnMut(u64) -> Result<Vec<String>, Box<dyn Error>>,
+) -> Result<Vec<&SignatureInfo>, Box<dyn Error>> {
+    let mut sources: HashMap<u64, HashSet<usize>> = HashMap::new();
+    for (list, signatures) in lists.iter().enumerate() {
+        for info in signatures {
+            sources.entry(info.slot).or_default().insert(list);
+        }
+    }
+
+    let mut positions = HashMap::new();
+    for (&slot, lists) in &sources {
+        if lists.len() > 1 {
+            for (position, signature) in block(slot)?.into_iter().enumerate() {
+                positions.insert(signature, position);
+            }
+        }
+    }
+
+    // Transactions touching several programs appear in each of their lists
+    let mut seen = HashSet::new();
+    let mut order: Vec<&SignatureInfo> = lists.iter().flatten().collect();
+    order.retain(|info| seen.insert(info.signature.as_str()));
+    order.sort_by_key(|info| (info.slot, positions.get(&info.signature).copied().unwrap_or(usize::MAX)));
+    Ok(order)
+}
+
+// JSON-RPC client for a local validator
+pub struct Rpc {
+    url: String,
+    agent: ureq::Agent,
+}
+
+impl Rpc {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into(), agent: ureq::Agent::new() }
+    }
+
+    fn request<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<Option<T>, Box<dyn Error>> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response: Response<T> = self.agent.post(&self.url).send_json(request)?.into_json()?;
+        match response.error {
+            Some(error) => Err(format!("{method}: {error}").into()),
+            None => Ok(response.result),
+        }
+    }
+
+    fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, Box<dyn Error>> {
+        self.request(method, params)?.ok_or_else(|| format!("{method}: empty response").into())
+    }
+
+    // Signatures for `address` newer than `until`, oldest first
+    fn signatures_since(&self, address: &Pubkey, until: Option<&str>) -> Result<Vec<SignatureInfo>, Box<dyn Error>> {
+        let mut signatures: Vec<SignatureInfo> = Vec::new();
+        loop {
+            let mut config = json!({ "limit": SIGNATURE_PAGE, "commitment": "confirmed" });
+            if let Some(until) = until {
+                config["until"] = json!(until);
+            }
+            if let Some(last) = signatures.last() {
+                config["before"] = json!(last.signature);
+            }
+            let page: Vec<SignatureInfo> = self.call("getSignaturesForAddress", json!([address.to_string(), config]))?;
+            let full = page.len() == SIGNATURE_PAGE;
+            signatures.extend(page);
+            if !full {
+                break;
+            }
+        }
+        signatures.reverse();
+        Ok(signatures)
+    }
+
+    // None until the validator has the transaction at the requested commitment
+    fn transaction(&self, signature: &str) -> Result<Option<Transaction>, Box<dyn Error>> {
+        let config = json!({ "encoding": "json", "commitment": "confirmed", "maxSupportedTransactionVersion": 0 });
+        self.request("getTransaction", json!([signature, config]))
+    }
+
+    // Signatures of a slot's transactions in the order the block executed them
+    fn block_signatures(&self, slot: u64) -> Result<Vec<String>, Box<dyn Error>> {
+        let config = json!({
+            "encoding": "json",
+            "transactionDetails": "signatures",
+            "rewards": false,
+            "commitment": "confirmed",
+            "maxSupportedTransactionVersion": 0,
+        });
+        let block: BlockSignatures = self.call("getBlock", json!([slot, config]))?;
+        Ok(block.signatures)
+    }
+
+    fn program_accounts(&self, program: &Pubkey) -> Result<Vec<ProgramAccount>, Box<dyn Error>> {
+        let config = json!({ "encoding": "base64", "commitment": "confirmed" });
+        self.call("getProgramAccounts", json!([program.to_string(), config]))
+    }
+
+    // Index every new transaction of the event programs, then snapshot the component
+    // accounts. Each program's cursor only moves past transactions that were indexed, so
+    // one the validator can't return yet is fetched again on the next sync.
+    pub fn sync(&self, db: &mut Db) -> Result<Summary, Box<dyn Error>> {
+        let mut pending = Vec::new();
+        for program in EVENT_PROGRAMS {
+            pending.push(self.signatures_since(&program, db.cursor(&program)?.as_deref())?);
+        }
+        let order = merge(&pending, |slot| self.block_signatures(slot))?;
+
+        let mut summary = Summary::default();
+        for info in order {
+            if db.is_indexed(&info.signature)? {
+                continue;
+            }
+            match self.transaction(&info.signature)? {
+                Some(transaction) => summary.add(db.index_transaction(&transaction)?),
+                None => break,
+            }
+        }
+
+        for (program, signatures) in EVENT_PROGRAMS.iter().zip(&pending) {
+            let mut cursor = None;
+            for info in signatures {
+                if !db.is_indexed(&info.signature)? {
+                    break;
+                }
+                cursor = Some(info.signature.as_str());
+            }
+            if let Some(cursor) = cursor {
+                db.set_cursor(program, cursor)?;
+            }
+        }
+
+        for program in COMPONENT_PROGRAMS {
+            for account in self.program_accounts(&program)? {
+                summary.add(db.index_account(&account)?);
+            }
+        }
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(signature: &str, slot: u64) -> SignatureInfo {
+        SignatureInfo { signature: signature.into(), slot }
+    }
+
+    fn signatures(order: &[&SignatureInfo]) -> Vec<String> {
+        order.iter().map(|info| info.signature.clone()).collect()
+    }
+
+    #[test]
+    fn merge_keeps_each_list_in_order_within_a_slot() {
+        let lists = [vec![info("create", 5), info("add", 7), info("strategy", 7)], vec![info("schedule", 9)]];
+        let order = merge(&lists, |slot| panic!("slot {slot} has one program's signatures")).unwrap();
+        assert_eq!(signatures(&order), ["create", "add", "strategy", "schedule"]);
+    }
+
+    #[test]
+    fn merge_orders_shared_slots_by_block() {
+        // The settle touches both programs, so it is listed twice
+        let lists = [
+            vec![info("add", 3), info("disband", 8), info("settle", 8)],
+            vec![info("schedule", 3), info("forfeit", 8), info("settle", 8)],
+        ];
+        let mut fetched = Vec::new();
+        let order = merge(&lists, |slot| {
+            fetched.push(slot);
+            Ok(match slot {
+                3 => vec!["vote".into(), "schedule".into(), "add".into()],
+                _ => vec!["forfeit".into(), "settle".into(), "disband".into()],
+            })
+        })
+        .unwrap();
+        fetched.sort();
+        assert_eq!(fetched, [3, 8]);
+        assert_eq!(signatures(&order), ["schedule", "add", "forfeit", "settle", "disband"]);
+    }
+}
@@ -0,0 +1,213 @@
+// Match engine of 5VS5dotGG.
+//
+// Plays a match between two lineups from a seed, with no account or runtime access:
+// match_system runs it on-chain with the recorded match seed and balance tooling runs it
+// natively, and both get the same result for the same input.
+#![no_std]
+
+extern crate alloc;
+
+pub mod rng;
+pub mod series;
+pub mod strategy;
+pub mod strength;
+
+use esport_types::{Role, StrategyType};
+
+pub use esport_types::MatchType;
+pub use rng::MatchRng;
+pub use series::{play_game, play_series, GameLog, SeriesResult};
+
+// Player attributes, 0-100 each
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Attributes {
+    pub mechanical: u8,
+    pub game_knowledge: u8,
+    pub team_communication: u8,
+    pub adaptability: u8,
+    pub consistency: u8,
+    pub form: u8,
+    pub potential: u8,
+}
+
+impl Attributes {
+    // In the order of strength::AttributeWeights
+    pub fn to_array(&self) -> [u8; 7] {
+        [
+            self.mechanical,
+            self.game_knowledge,
+            self.team_communication,
+            self.adaptability,
+            self.consistency,
+            self.form,
+            self.potential,
+        ]
+    }
+}
+
+// A player as they take part in a match
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Player {
+    pub role: Option<Role>,     // Role the player specialises in; None if unrecognised
+    pub position: Option<Role>, // Position the team owner slotted them in
+    pub attributes: Attributes,
+}
+
+// A team as it takes part in a match: its strategy and five players in lineup order
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Lineup {
+    pub strategy: StrategyType,
+    pub players: [Player; 5],
+}
+
+impl Lineup {
+    fn average_adaptability(&self) -> u32 {
+        self.players.iter().map(|p| p.attributes.adaptability as u32).sum::<u32>() / 5
+    }
+}
+
+// Play a whole match from its seed
+pub fn simulate(format: MatchType, team1: &Lineup, team2: &Lineup, seed: &[u8; 32]) -> SeriesResult {
+    play_series(format, team1, team2, &mut MatchRng::new(seed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two on-role lineups: a strong aggressive one and a weaker split-push one it counters
+    fn lineups() -> (Lineup, Lineup) {
+        let player = |role, attributes: [u8; 7]| Player {
+            role: Some(role),
+            position: Some(role),
+            attributes: Attributes {
+                mechanical: attributes[0],
+                game_knowledge: attributes[1],
+                team_communication: attributes[2],
+                adaptability: attributes[3],
+                consistency: attributes[4],
+                form: attributes[5],
+                potential: attributes[6],
+            },
+        };
+        let team1 = Lineup {
+            strategy: StrategyType::Aggressive,
+            players: [
+                player(Role::Top, [80, 70, 60, 75, 70, 80, 85]),
+                player(Role::Jungle, [70, 85, 75, 70, 65, 75, 80]),
+                player(Role::Mid, [90, 80, 65, 70, 75, 85, 90]),
+                player(Role::Carry, [92, 60, 60, 65, 85, 80, 88]),
+                player(Role::Support, [55, 80, 90, 75, 70, 70, 75]),
+            ],
+        };
+        let team2 = Lineup {
+            strategy: StrategyType::SplitPush,
+            players: [
+                player(Role::Top, [70, 65, 60, 60, 50, 70, 75]),
+                player(Role::Jungle, [65, 70, 65, 60, 55, 65, 70]),
+                player(Role::Mid, [75, 70, 60, 65, 60, 70, 80]),
+                player(Role::Carry, [80, 55, 55, 60, 55, 75, 85]),
+                player(Role::Support, [50, 70, 75, 65, 60, 65, 70]),
+            ],
+        };
+        (team1, team2)
+    }
+
+    fn seed() -> [u8; 32] {
+        core::array::from_fn(|i| i as u8)
+    }
+
+    #[test]
+    fn golden_bo3() {
+        let (team1, team2) = lineups();
+        let result = simulate(MatchType::parse("bo3"), &team1, &team2, &seed());
+
+        assert_eq!((result.team1_wins, result.team2_wins), (2, 0));
+        assert_eq!(
+            result.games,
+            [
+                GameLog {
+                    team1_won: true,
+                    team1_fights: 3,
+                    team2_fights: 2,
+                    team1_objectives: 1,
+                    team2_objectives: 4,
+                    team1_strength: 1702,
+                    team2_strength: 1274,
+                    mvp: 3,
+                    team1_contributions: [311, 297, 304, 327, 294],
+                    team2_contributions: [236, 271, 264, 294, 263],
+                },
+                GameLog {
+                    team1_won: true,
+                    team1_fights: 5,
+                    team2_fights: 0,
+                    team1_objectives: 3,
+                    team2_objectives: 2,
+                    team1_strength: 1730,
+                    team2_strength: 1259,
+                    mvp: 2,
+                    team1_contributions: [291, 316, 324, 314, 300],
+                    team2_contributions: [276, 279, 262, 245, 247],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn golden_mirror_bo5() {
+        // Identical lineups go the distance; the all-zero seed starts the generator at zero
+        let (team1, _) = lineups();
+        let result = simulate(MatchType::parse("ranked-bo5"), &team1, &team1, &[0; 32]);
+
+        assert_eq!((result.team1_wins, result.team2_wins), (2, 3));
+        assert!(!result.team1_won());
+        let games: alloc::vec::Vec<_> = result
+            .games
+            .iter()
+            .map(|g| {
+                (
+                    g.team1_won,
+                    g.team1_strength,
+                    g.team2_strength,
+                    g.mvp,
+                    (g.team1_fights, g.team2_fights),
+                    (g.team1_objectives, g.team2_objectives),
+                )
+            })
+            .collect();
+        assert_eq!(
+            games,
+            [
+                (false, 1549, 1580, 3, (1, 4), (1, 4)),
+                (false, 1576, 1587, 1, (4, 1), (3, 2)),
+                (true, 1582, 1549, 4, (4, 3), (2, 3)),
+                (true, 1602, 1571, 2, (2, 2), (3, 2)),
+                (false, 1565, 1569, 2, (3, 1), (3, 2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn games_are_consistent() {
+        let (team1, team2) = lineups();
+        for byte in 0..32u8 {
+            let mut seed = seed();
+            seed[0] = byte;
+            let result = simulate(MatchType::parse("bo5"), &team2, &team1, &seed);
+
+            // Same seed, same match
+            assert_eq!(simulate(MatchType::parse("bo5"), &team2, &team1, &seed), result);
+            assert_eq!(result.team1_wins.max(result.team2_wins), 3);
+            assert_eq!(result.games.len(), (result.team1_wins + result.team2_wins) as usize);
+
+            for game in &result.games {
+                assert_eq!(game.team1_won, game.team1_strength > game.team2_strength);
+                assert!((3..8).contains(&(game.team1_fights + game.team2_fights)));
+                assert_eq!(game.team1_objectives + game.team2_objectives, 5);
+                let winners = if game.team1_won { game.team1_contributions } else { game.team2_contributions };
+                assert!(winners.iter().all(|&c| c <= winners[game.mvp]));
+            }
+        }
+    }
+}
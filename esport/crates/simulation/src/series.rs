@@ -0,0 +1,119 @@
+use alloc::vec::Vec;
+
+use esport_types::MatchType;
+
+use crate::rng::MatchRng;
+use crate::{strategy, strength, Lineup};
+
+// Random bonus added to each team's strength in every game
+const GAME_RANDOM_FACTOR: u32 = 50;
+
+// Every game has between MIN_FIGHTS and MIN_FIGHTS + FIGHT_SPREAD - 1 teamfights,
+// and OBJECTIVES_PER_GAME neutral objectives
+const MIN_FIGHTS: u32 = 3;
+const FIGHT_SPREAD: u32 = 5;
+const OBJECTIVES_PER_GAME: u32 = 5;
+
+// Play-by-play of one game
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GameLog {
+    pub team1_won: bool,
+    pub team1_fights: u8, // Teamfights won
+    pub team2_fights: u8,
+    pub team1_objectives: u8, // Neutral objectives taken
+    pub team2_objectives: u8,
+    pub team1_strength: u32, // Game strength after strategy and random factor
+    pub team2_strength: u32,
+    pub mvp: usize, // Lineup index of the MVP in the winning team
+    // Each player's strength in the game, in lineup order
+    pub team1_contributions: [u32; 5],
+    pub team2_contributions: [u32; 5],
+}
+
+// Team strength for one game and every player's contribution to it, in lineup order
+fn lineup_strength(lineup: &Lineup, opponent: &Lineup, rng: &mut MatchRng) -> (u32, [u32; 5]) {
+    // Role-weighted attributes for the slotted position, with consistency-driven variance
+    let mut contributions = [0; 5];
+    for (contribution, player) in contributions.iter_mut().zip(lineup.players.iter()) {
+        *contribution = strength::player_strength(player, rng);
+    }
+    let base: u32 = contributions.iter().sum();
+
+    // Strategy matchup, softened for countered teams by lineup adaptability
+    let modifier = strategy::matchup_modifier(lineup.strategy, opponent.strategy, lineup.average_adaptability());
+
+    (
+        strategy::apply_modifier(base, modifier) + rng.next_below(GAME_RANDOM_FACTOR),
+        contributions,
+    )
+}
+
+// Split `rounds` contests between the teams, each one won with a chance proportional
+// to the team's strength; returns the rounds won by (team1, team2)
+fn contest(rounds: u32, team1_strength: u32, team2_strength: u32, rng: &mut MatchRng) -> (u8, u8) {
+    let total = (team1_strength + team2_strength).max(1);
+    let team1_won = (0..rounds).filter(|_| rng.next_below(total) < team1_strength).count() as u32;
+    (team1_won as u8, (rounds - team1_won) as u8)
+}
+
+// Play a single game and log it. The winner is decided by strength alone; fights and
+// objectives are drawn afterwards so the log doesn't change the balance of the match.
+pub fn play_game(team1: &Lineup, team2: &Lineup, rng: &mut MatchRng) -> GameLog {
+    let (team1_strength, team1_contributions) = lineup_strength(team1, team2, rng);
+    let (team2_strength, team2_contributions) = lineup_strength(team2, team1, rng);
+    let team1_won = team1_strength > team2_strength;
+
+    let fights = MIN_FIGHTS + rng.next_below(FIGHT_SPREAD);
+    let (team1_fights, team2_fights) = contest(fights, team1_strength, team2_strength, rng);
+    let (team1_objectives, team2_objectives) =
+        contest(OBJECTIVES_PER_GAME, team1_strength, team2_strength, rng);
+
+    // MVP: the top contributor of the winning team, first in lineup order on ties
+    let winner_contributions = if team1_won { &team1_contributions } else { &team2_contributions };
+    let mvp = (0..5).rev().max_by_key(|&index| winner_contributions[index]).unwrap_or(0);
+
+    GameLog {
+        team1_won,
+        team1_fights,
+        team2_fights,
+        team1_objectives,
+        team2_objectives,
+        team1_strength,
+        team2_strength,
+        mvp,
+        team1_contributions,
+        team2_contributions,
+    }
+}
+
+// Played series: final game counts and the log of every game
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SeriesResult {
+    pub team1_wins: u8,
+    pub team2_wins: u8,
+    pub games: Vec<GameLog>,
+}
+
+impl SeriesResult {
+    pub fn team1_won(&self) -> bool {
+        self.team1_wins > self.team2_wins
+    }
+}
+
+// Play games until one team reaches the required wins
+pub fn play_series(format: MatchType, team1: &Lineup, team2: &Lineup, rng: &mut MatchRng) -> SeriesResult {
+    let wins_needed = format.wins_needed();
+    let mut result = SeriesResult { team1_wins: 0, team2_wins: 0, games: Vec::new() };
+
+    while result.team1_wins < wins_needed && result.team2_wins < wins_needed {
+        let game = play_game(team1, team2, rng);
+        if game.team1_won {
+            result.team1_wins += 1;
+        } else {
+            result.team2_wins += 1;
+        }
+        result.games.push(game);
+    }
+
+    result
+}
@@ -0,0 +1,90 @@
+use esport_types::StrategyType;
+
+// Strength bonus (permille) when a strategy counters the opponent's, and the penalty when it is countered
+pub const COUNTER_BONUS: i32 = 100;
+pub const COUNTER_PENALTY: i32 = -100;
+
+// Every strategy counters two others and is countered by the remaining two:
+//
+//   Aggressive       > SplitPush, ObjectiveControl
+//   SplitPush        > Defensive, Teamfight
+//   Teamfight        > Aggressive, ObjectiveControl
+//   ObjectiveControl > SplitPush, Defensive
+//   Defensive        > Aggressive, Teamfight
+fn counters(strategy: StrategyType, opponent: StrategyType) -> bool {
+    use StrategyType::*;
+
+    matches!(
+        (strategy, opponent),
+        (Aggressive, SplitPush)
+            | (Aggressive, ObjectiveControl)
+            | (SplitPush, Defensive)
+            | (SplitPush, Teamfight)
+            | (Teamfight, Aggressive)
+            | (Teamfight, ObjectiveControl)
+            | (ObjectiveControl, SplitPush)
+            | (ObjectiveControl, Defensive)
+            | (Defensive, Aggressive)
+            | (Defensive, Teamfight)
+    )
+}
+
+// Strength modifier in permille for `strategy` facing `opponent`.
+//
+// A countered team loses strength, but the average adaptability (0-100) of its lineup
+// softens the penalty: a fully adaptable lineup only takes half of it.
+pub fn matchup_modifier(strategy: StrategyType, opponent: StrategyType, average_adaptability: u32) -> i32 {
+    if counters(strategy, opponent) {
+        COUNTER_BONUS
+    } else if counters(opponent, strategy) {
+        COUNTER_PENALTY * (200 - average_adaptability.min(100) as i32) / 200
+    } else {
+        0
+    }
+}
+
+// Apply a permille modifier to a team strength
+pub fn apply_modifier(strength: u32, modifier: i32) -> u32 {
+    (strength as i64 * (1000 + modifier) as i64 / 1000).max(0) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counter_matrix() {
+        for strategy in StrategyType::ALL {
+            let beats = StrategyType::ALL.iter().filter(|&&o| counters(strategy, o)).count();
+            let beaten_by = StrategyType::ALL.iter().filter(|&&o| counters(o, strategy)).count();
+            assert_eq!((beats, beaten_by), (2, 2), "{strategy:?}");
+            assert!(!counters(strategy, strategy));
+            assert!(!counters(strategy, StrategyType::None));
+            assert!(!counters(StrategyType::None, strategy));
+
+            for opponent in StrategyType::ALL {
+                assert!(!(counters(strategy, opponent) && counters(opponent, strategy)));
+            }
+        }
+    }
+
+    #[test]
+    fn modifiers() {
+        use StrategyType::*;
+
+        assert_eq!(matchup_modifier(Aggressive, SplitPush, 0), COUNTER_BONUS);
+        assert_eq!(matchup_modifier(Aggressive, SplitPush, 100), COUNTER_BONUS);
+        // Adaptability halves the penalty at most
+        assert_eq!(matchup_modifier(SplitPush, Aggressive, 0), COUNTER_PENALTY);
+        assert_eq!(matchup_modifier(SplitPush, Aggressive, 50), -75);
+        assert_eq!(matchup_modifier(SplitPush, Aggressive, 100), COUNTER_PENALTY / 2);
+        assert_eq!(matchup_modifier(SplitPush, Aggressive, 250), COUNTER_PENALTY / 2);
+        assert_eq!(matchup_modifier(Aggressive, Aggressive, 0), 0);
+        assert_eq!(matchup_modifier(None, Defensive, 0), 0);
+
+        assert_eq!(apply_modifier(1000, COUNTER_BONUS), 1100);
+        assert_eq!(apply_modifier(1000, COUNTER_PENALTY), 900);
+        assert_eq!(apply_modifier(1999, -75), 1849);
+        assert_eq!(apply_modifier(100, -2000), 0);
+    }
+}
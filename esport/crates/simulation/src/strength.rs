@@ -0,0 +1,145 @@
+use esport_types::Role;
+
+use crate::rng::MatchRng;
+use crate::Player;
+
+// Attribute weights in percent, in the order:
+// mechanical, game knowledge, team communication, adaptability, consistency, form, potential
+pub type AttributeWeights = [u32; 7];
+
+pub const TOP_WEIGHTS: AttributeWeights = [20, 15, 10, 20, 15, 15, 5];
+pub const JUNGLE_WEIGHTS: AttributeWeights = [15, 25, 15, 15, 10, 15, 5];
+pub const MID_WEIGHTS: AttributeWeights = [25, 20, 10, 15, 10, 15, 5];
+pub const CARRY_WEIGHTS: AttributeWeights = [30, 10, 10, 10, 20, 15, 5];
+pub const SUPPORT_WEIGHTS: AttributeWeights = [10, 20, 30, 15, 10, 10, 5];
+// Used for players whose role label isn't recognised
+pub const FLEX_WEIGHTS: AttributeWeights = [20, 15, 15, 15, 15, 15, 5];
+
+// Strength penalty (percent) for a player slotted outside their own role
+pub const OFF_ROLE_PENALTY: u32 = 15;
+
+// Largest performance swing (percent) of a player with zero consistency
+pub const MAX_VARIANCE: u32 = 20;
+
+// Weighted attributes are divided by this so a perfect player is worth 400,
+// the same scale as the original four-attribute sum
+const STRENGTH_DIVISOR: u32 = 25;
+
+pub fn role_weights(role: Option<Role>) -> &'static AttributeWeights {
+    match role {
+        Some(Role::Top) => &TOP_WEIGHTS,
+        Some(Role::Jungle) => &JUNGLE_WEIGHTS,
+        Some(Role::Mid) => &MID_WEIGHTS,
+        Some(Role::Carry) => &CARRY_WEIGHTS,
+        Some(Role::Support) => &SUPPORT_WEIGHTS,
+        None => &FLEX_WEIGHTS,
+    }
+}
+
+// Strength of a player before variance: attributes weighted for the slotted position,
+// minus OFF_ROLE_PENALTY percent for an off-role player
+pub fn base_strength(player: &Player) -> u32 {
+    let weights = role_weights(player.position);
+    let weighted: u32 = player
+        .attributes
+        .to_array()
+        .iter()
+        .zip(weights.iter())
+        .map(|(attribute, weight)| *attribute as u32 * weight)
+        .sum();
+    let strength = weighted / STRENGTH_DIVISOR;
+
+    if player.role.is_none() || player.role != player.position {
+        strength * (100 - OFF_ROLE_PENALTY) / 100
+    } else {
+        strength
+    }
+}
+
+// Strength of one player for one game: the base strength swinging by up to MAX_VARIANCE
+// percent, scaled by how inconsistent the player is
+pub fn player_strength(player: &Player, rng: &mut MatchRng) -> u32 {
+    let strength = base_strength(player);
+
+    // Variance: roll in [-swing, +swing] percent
+    let swing = MAX_VARIANCE * (100 - (player.attributes.consistency as u32).min(100)) / 100;
+    let roll = rng.next_below(2 * swing + 1);
+    strength * (100 + roll - swing) / 100
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Attributes;
+
+    fn player(role: Option<Role>, position: Option<Role>, value: u8) -> Player {
+        Player {
+            role,
+            position,
+            attributes: Attributes {
+                mechanical: value,
+                game_knowledge: value,
+                team_communication: value,
+                adaptability: value,
+                consistency: value,
+                form: value,
+                potential: value,
+            },
+        }
+    }
+
+    #[test]
+    fn weights_sum_to_100() {
+        for role in Role::ALL.map(Some).into_iter().chain([None]) {
+            assert_eq!(role_weights(role).iter().sum::<u32>(), 100, "{role:?}");
+        }
+    }
+
+    #[test]
+    fn on_role_and_off_role_strength() {
+        // A perfect on-role player is worth 400 whatever the role
+        for role in Role::ALL {
+            assert_eq!(base_strength(&player(Some(role), Some(role), 100)), 400);
+        }
+        assert_eq!(base_strength(&player(Some(Role::Mid), Some(Role::Support), 100)), 340);
+        // Unrecognised roles are always off-role, on flex weights
+        assert_eq!(base_strength(&player(None, Some(Role::Mid), 100)), 340);
+        assert_eq!(base_strength(&player(None, None, 100)), 340);
+        assert_eq!(base_strength(&player(Some(Role::Top), Some(Role::Top), 0)), 0);
+    }
+
+    #[test]
+    fn weights_follow_the_position() {
+        // Mechanics count most for carries, communication for supports
+        let mut carry = player(Some(Role::Carry), Some(Role::Carry), 50);
+        carry.attributes.mechanical = 100;
+        let mut support = player(Some(Role::Support), Some(Role::Support), 50);
+        support.attributes.mechanical = 100;
+        assert_eq!(base_strength(&carry), 260);
+        assert_eq!(base_strength(&support), 220);
+    }
+
+    #[test]
+    fn variance_is_bounded_by_consistency() {
+        let mut rng = MatchRng::new(&[7; 32]);
+
+        // Perfectly consistent players always play at their base strength
+        let steady = player(Some(Role::Mid), Some(Role::Mid), 100);
+        for _ in 0..100 {
+            assert_eq!(player_strength(&steady, &mut rng), 400);
+        }
+
+        // Zero consistency swings up to MAX_VARIANCE percent either way
+        let mut erratic = steady;
+        erratic.attributes.consistency = 0;
+        let base = base_strength(&erratic);
+        let (mut low, mut high) = (u32::MAX, 0);
+        for _ in 0..1000 {
+            let strength = player_strength(&erratic, &mut rng);
+            low = low.min(strength);
+            high = high.max(strength);
+        }
+        assert_eq!(low, base * (100 - MAX_VARIANCE) / 100);
+        assert_eq!(high, base * (100 + MAX_VARIANCE) / 100);
+    }
+}
@@ -0,0 +1,215 @@
+// System arguments as tagged enums. Each variant carries only the fields its action needs.
+//
+// The JSON layout is the one clients already send: the action name under "action" next to
+// camelCase fields, e.g. `{"action":"addPlayerToTeam","playerNftMint":"...","position":"Mid"}`.
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey::Pubkey;
+
+use crate::{RandomnessSource, Role, StrategyType};
+
+// team_system actions
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "action", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum TeamAction {
+    // Extra accounts: stake vault, system program, instructions sysvar. The stake defaults to
+    // the configured minimum.
+    CreateTeam {
+        team_name: String,
+        #[serde(default)]
+        stake_amount: Option<u64>,
+    },
+    // Extra accounts: the signer's token account for the mint, the mint's player record
+    AddPlayerToTeam {
+        #[serde(with = "crate::serde_pubkey")]
+        player_nft_mint: Pubkey,
+        position: Role,
+    },
+    RemovePlayerFromTeam {
+        #[serde(with = "crate::serde_pubkey")]
+        player_nft_mint: Pubkey,
+    },
+    SetStrategy {
+        strategy_type: StrategyType,
+        strategy_description: String,
+    },
+    // Extra accounts: stake vault, system program, treasury, instructions sysvar
+    DisbandTeam,
+    // Extra account, when the player is still rostered: the new holder's token account
+    ReleasePlayer,
+}
+
+// match_system actions
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "action", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum MatchAction {
+    ScheduleMatch {
+        pending_match_id: u64,
+        #[serde(default)]
+        randomness: RandomnessSource,
+    },
+    // Extra accounts: match fee vault, system program, instructions sysvar
+    PayEntryFee,
+    // Extra accounts: the 10 lineup token accounts
+    LockLineups,
+    ConsentMatch,
+    CommitSeed {
+        seed_commitment: [u8; 32],
+    },
+    RevealSeed {
+        seed: [u8; 32],
+    },
+    // Extra accounts: the 10 lineup token accounts, then the instructions sysvar for "vrf"
+    // matches
+    SimulateMatch,
+    Forfeit,
+    ClaimForfeit,
+    DisputeMatch,
+    // Admin or match operator only. `refund` voids the result and refunds the entry fees;
+    // otherwise the result stands.
+    ResolveDispute {
+        refund: bool,
+    },
+    // Extra accounts: match fee vault, system program, treasury, instructions sysvar, then the
+    // payees
+    SettleFees,
+}
+
+// player_system actions
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "action", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum PlayerAction {
+    // Extra accounts: the NFT's metadata, the mint's player record, system program,
+    // instructions sysvar, then the signer's token account for the mint unless the signer is
+    // the mint authority. Only the mint authority may choose the attributes.
+    MintPlayer {
+        #[serde(with = "crate::serde_pubkey")]
+        player_nft_mint: Pubkey,
+        #[serde(flatten)]
+        attributes: ChosenAttributes,
+    },
+}
+
+// Role and attributes the mint authority gives a player; left empty, they are rolled
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChosenAttributes {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<Role>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mechanical: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_knowledge: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub team_communication: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adaptability: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consistency: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub form: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub potential: Option<u8>,
+}
+
+impl ChosenAttributes {
+    // Role and every attribute, in PlayerStats order
+    pub fn new(role: Role, attributes: [u8; 7]) -> Self {
+        let [mechanical, game_knowledge, team_communication, adaptability, consistency, form, potential] = attributes;
+        Self {
+            role: Some(role),
+            mechanical: Some(mechanical),
+            game_knowledge: Some(game_knowledge),
+            team_communication: Some(team_communication),
+            adaptability: Some(adaptability),
+            consistency: Some(consistency),
+            form: Some(form),
+            potential: Some(potential),
+        }
+    }
+
+    // Role and attributes in PlayerStats order, if all of them were given
+    pub fn complete(&self) -> Option<(Role, [u8; 7])> {
+        Some((
+            self.role?,
+            [
+                self.mechanical?,
+                self.game_knowledge?,
+                self.team_communication?,
+                self.adaptability?,
+                self.consistency?,
+                self.form?,
+                self.potential?,
+            ],
+        ))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+// queue_system actions. Team 1 is always the challenger and team 2 the opponent.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "action", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum QueueAction {
+    Challenge {
+        match_type: String,
+    },
+    Accept {
+        pending_match_id: u64,
+    },
+    Decline {
+        pending_match_id: u64,
+    },
+    Cancel {
+        pending_match_id: u64,
+    },
+    Expire,
+}
+
+// matchmaking_system actions
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "action", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum MatchmakingAction {
+    Enqueue {
+        match_type: String,
+    },
+    Leave,
+    Matchmake,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mint_player_json() {
+        let mint = Pubkey::new_from_array([7; 32]);
+        let json = format!(
+            r#"{{"action":"mintPlayer","playerNftMint":"{mint}","role":"Midlaner","mechanical":90,"gameKnowledge":85,"teamCommunication":80,"adaptability":75,"consistency":85,"form":90,"potential":95}}"#
+        );
+        let action: PlayerAction = serde_json::from_str(&json).unwrap();
+        let attributes = ChosenAttributes::new(Role::Mid, [90, 85, 80, 75, 85, 90, 95]);
+        assert_eq!(action, PlayerAction::MintPlayer { player_nft_mint: mint, attributes });
+        assert_eq!(attributes.complete(), Some((Role::Mid, [90, 85, 80, 75, 85, 90, 95])));
+
+        // Without attributes they are rolled
+        let rolled: PlayerAction =
+            serde_json::from_str(&format!(r#"{{"action":"mintPlayer","playerNftMint":"{mint}"}}"#)).unwrap();
+        let PlayerAction::MintPlayer { attributes, .. } = rolled;
+        assert!(attributes.is_empty());
+        assert_eq!(serde_json::to_string(&rolled).unwrap(), format!(r#"{{"action":"mintPlayer","playerNftMint":"{mint}"}}"#));
+    }
+
+    #[test]
+    fn queue_and_matchmaking_json() {
+        let accept: QueueAction = serde_json::from_str(r#"{"action":"accept","pendingMatchId":3}"#).unwrap();
+        assert_eq!(accept, QueueAction::Accept { pending_match_id: 3 });
+        let challenge = QueueAction::Challenge { match_type: "bo3".into() };
+        assert_eq!(serde_json::to_string(&challenge).unwrap(), r#"{"action":"challenge","matchType":"bo3"}"#);
+
+        let enqueue: MatchmakingAction = serde_json::from_str(r#"{"action":"enqueue","matchType":"ranked"}"#).unwrap();
+        assert_eq!(enqueue, MatchmakingAction::Enqueue { match_type: "ranked".into() });
+        assert_eq!(serde_json::to_string(&MatchmakingAction::Matchmake).unwrap(), r#"{"action":"matchmake"}"#);
+    }
+}
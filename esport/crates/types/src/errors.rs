@@ -0,0 +1,233 @@
+// Error codes of the esport components.
+//
+// Codes are numbered like Anchor's `#[error_code(offset = N)]`: the enum's offset plus the
+// variant's position, so off-chain tools can decode a failed transaction with `from_code`.
+// Every enum, here and in the programs, gets its own block of ERROR_CODE_BLOCK codes, so
+// a code alone names the error whichever program raised it. With the `anchor` feature every
+// enum converts into an Anchor error for `require!` and `?`.
+
+// First custom error code of a program, as in Anchor
+pub const ERROR_CODE_OFFSET: u32 = 6000;
+
+// Codes reserved for each error enum
+pub const ERROR_CODE_BLOCK: u32 = 100;
+
+// Offsets of the error enums, components first. The systems and the esport program pass
+// theirs to `#[error_code(offset = ...)]`.
+pub mod offsets {
+    pub const TEAM: u32 = 6000;
+    pub const PLAYER: u32 = 6100;
+    pub const QUEUE: u32 = 6200;
+    pub const MATCH: u32 = 6300;
+    pub const TEAM_SYSTEM: u32 = 6400;
+    pub const MATCH_SYSTEM: u32 = 6500;
+    pub const QUEUE_SYSTEM: u32 = 6600;
+    pub const MATCHMAKING_SYSTEM: u32 = 6700;
+    pub const PLAYER_SYSTEM: u32 = 6800;
+    pub const CONFIG: u32 = 6900;
+}
+
+macro_rules! error_codes {
+    (#[error_code(offset = $offset:expr)] $(#[$meta:meta])* pub enum $name:ident { $($variant:ident => $msg:literal,)* }) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        #[repr(u32)]
+        pub enum $name {
+            $($variant,)*
+        }
+
+        impl $name {
+            pub const OFFSET: u32 = $offset;
+
+            const ALL: &'static [$name] = &[$($name::$variant,)*];
+
+            pub fn code(self) -> u32 {
+                Self::OFFSET + self as u32
+            }
+
+            pub fn from_code(code: u32) -> Option<Self> {
+                let index = code.checked_sub(Self::OFFSET)?;
+                Self::ALL.get(index as usize).copied()
+            }
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $($name::$variant => stringify!($variant),)*
+                }
+            }
+
+            pub fn message(self) -> &'static str {
+                match self {
+                    $($name::$variant => $msg,)*
+                }
+            }
+        }
+
+        impl core::fmt::Display for $name {
+            fn fmt(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                formatter.write_str(self.message())
+            }
+        }
+
+        #[cfg(feature = "anchor")]
+        impl From<$name> for anchor_lang::error::Error {
+            fn from(error: $name) -> Self {
+                anchor_lang::error::Error::from(anchor_lang::error::AnchorError {
+                    error_name: error.name().to_string(),
+                    error_code_number: error.code(),
+                    error_msg: error.message().to_string(),
+                    error_origin: None,
+                    compared_values: None,
+                })
+            }
+        }
+    };
+}
+
+error_codes! {
+    // team_data
+    #[error_code(offset = offsets::TEAM)]
+    pub enum TeamError {
+        InvalidName => "Team name must not be empty",
+        RosterFull => "Roster already has maximum number of players",
+        PlayerAlreadyInRoster => "Player already in roster",
+        PlayerNotFound => "Player not found in roster",
+        InvalidStrategy => "A strategy type must be selected",
+        AlreadyInitialized => "Team is already initialized",
+        TeamInactive => "Team is not active",
+        MatchLocked => "Team has a match with locked lineups",
+        OpenMatches => "Team has matches that haven't finished",
+    }
+}
+
+error_codes! {
+    // player_stats
+    #[error_code(offset = offsets::PLAYER)]
+    pub enum PlayerError {
+        InvalidAttribute => "Attribute value must be between 0 and 100",
+        AlreadyInitialized => "Player stats are already initialized",
+        PlayerOnAnotherTeam => "Player is already on another team",
+        PlayerNotOnTeam => "Player is not on this team",
+        InvalidTokenAccount => "Account is not an SPL token account",
+        NftMintMismatch => "Token account is for a different mint",
+        NftNotOwned => "Player NFT is not held by the owner",
+    }
+}
+
+error_codes! {
+    // match_queue
+    #[error_code(offset = offsets::QUEUE)]
+    pub enum QueueError {
+        SameTeam => "A team can't challenge itself",
+        InvalidMatchType => "Match type must not be empty",
+        QueueFull => "Match queue is full",
+        MatchAlreadyPending => "A match between these teams is already pending",
+        PendingMatchNotFound => "Pending match not found",
+        MatchAlreadyAccepted => "Match was already accepted",
+        MatchNotAccepted => "Match has not been accepted",
+        MatchExpired => "Pending match has expired",
+        TeamMismatch => "Teams don't match the pending match",
+        AlreadyQueued => "Team is already queued for matchmaking",
+        NotQueued => "Team is not queued for matchmaking",
+        TooManyChallenges => "Team has too many open challenges",
+    }
+}
+
+error_codes! {
+    // match_state
+    #[error_code(offset = offsets::MATCH)]
+    pub enum MatchError {
+        AlreadyScheduled => "Match is already scheduled",
+        InvalidStatus => "Action not allowed in the current match status",
+        TeamMismatch => "Teams don't match this match",
+        LineupMismatch => "Lineup differs from the locked lineup",
+        InvalidSeedCommitment => "Seed commitment must not be empty",
+        SeedAlreadyCommitted => "A seed is already committed for this match",
+        SeedAlreadyRevealed => "Seed already revealed",
+        SeedNotRevealed => "Seed has not been revealed",
+        SeedMismatch => "Revealed seed does not match the commitment",
+        RevealWindowOpen => "Opponent can still reveal their seed",
+        DisputeWindowClosed => "Dispute window has closed",
+        TooManyGames => "Too many games for one match",
+        FeeAlreadyPaid => "Entry fee already paid",
+        FeesNotPaid => "Both teams must pay the entry fee first",
+        FeesAlreadySettled => "Match fees were already settled",
+        DisputeWindowOpen => "Match can still be disputed",
+        AlreadyConsented => "Team 2 already consented to this match",
+        WrongRandomnessSource => "Match was scheduled with another randomness source",
+        DisputeAlreadyResolved => "Match dispute was already resolved",
+        ReadyWindowOpen => "Owners can still consent and commit their seeds",
+        OpponentNotStalled => "Opponent did their part of the match in time",
+        SimulateWindowOpen => "Team 1 can still simulate the match",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate alloc;
+
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    // (offset, codes) of every component error enum
+    fn enums() -> [(u32, Vec<u32>); 4] {
+        [
+            (TeamError::OFFSET, TeamError::ALL.iter().map(|e| e.code()).collect()),
+            (PlayerError::OFFSET, PlayerError::ALL.iter().map(|e| e.code()).collect()),
+            (QueueError::OFFSET, QueueError::ALL.iter().map(|e| e.code()).collect()),
+            (MatchError::OFFSET, MatchError::ALL.iter().map(|e| e.code()).collect()),
+        ]
+    }
+
+    #[test]
+    fn codes_round_trip() {
+        for error in TeamError::ALL {
+            assert_eq!(TeamError::from_code(error.code()), Some(*error));
+        }
+        for error in PlayerError::ALL {
+            assert_eq!(PlayerError::from_code(error.code()), Some(*error));
+        }
+        for error in QueueError::ALL {
+            assert_eq!(QueueError::from_code(error.code()), Some(*error));
+        }
+        for error in MatchError::ALL {
+            assert_eq!(MatchError::from_code(error.code()), Some(*error));
+        }
+
+        assert_eq!(TeamError::InvalidName.code(), 6000);
+        assert_eq!(TeamError::from_code(6001), Some(TeamError::RosterFull));
+        assert_eq!(MatchError::from_code(MatchError::OFFSET - 1), None);
+    }
+
+    #[test]
+    fn codes_are_disjoint() {
+        let enums = enums();
+        for (offset, codes) in &enums {
+            assert!(*offset >= ERROR_CODE_OFFSET);
+            assert!(codes.len() as u32 <= ERROR_CODE_BLOCK);
+            assert!(codes.iter().all(|code| (*offset..offset + ERROR_CODE_BLOCK).contains(code)));
+        }
+
+        // Codes of one enum don't decode as another
+        assert_eq!(PlayerError::from_code(TeamError::InvalidName.code()), None);
+        assert_eq!(TeamError::from_code(PlayerError::InvalidAttribute.code()), None);
+        assert_eq!(QueueError::from_code(MatchError::AlreadyScheduled.code()), None);
+        assert_eq!(MatchError::from_code(QueueError::SameTeam.code()), None);
+
+        let mut all = [
+            offsets::TEAM,
+            offsets::PLAYER,
+            offsets::QUEUE,
+            offsets::MATCH,
+            offsets::TEAM_SYSTEM,
+            offsets::MATCH_SYSTEM,
+            offsets::QUEUE_SYSTEM,
+            offsets::MATCHMAKING_SYSTEM,
+            offsets::PLAYER_SYSTEM,
+            offsets::CONFIG,
+        ];
+        all.sort_unstable();
+        assert!(all.windows(2).all(|pair| pair[1] - pair[0] >= ERROR_CODE_BLOCK));
+    }
+}
@@ -0,0 +1,169 @@
+// Events emitted by team_system and match_system, one per state change.
+//
+// With `anchor` these are Anchor events: `emit!` logs them as "Program data: <base64>",
+// the 8-byte discriminator sha256("event:<Name>") followed by the Borsh-encoded fields.
+// Without it they are plain Borsh structs that `Event::decode` reads back off-chain.
+#[cfg(feature = "anchor")]
+use anchor_lang::{AnchorDeserialize, AnchorSerialize};
+use solana_program::hash::hash;
+use solana_program::pubkey::Pubkey;
+
+use crate::StrategyType;
+
+macro_rules! events {
+    ($($name:ident { $($field:ident: $ty:ty,)* })*) => {
+        $(
+            #[cfg_attr(feature = "anchor", anchor_lang::event)]
+            #[cfg_attr(not(feature = "anchor"), derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
+            #[derive(Clone, Debug, PartialEq, Eq)]
+            pub struct $name {
+                $(pub $field: $ty,)*
+            }
+        )*
+
+        // Any esport event, as decoded from a log
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub enum Event {
+            $($name($name),)*
+        }
+
+        impl Event {
+            // Decode event data (discriminator and fields); None for other programs' events
+            pub fn decode(data: &[u8]) -> Option<Event> {
+                if data.len() < 8 {
+                    return None;
+                }
+                let (discriminator, mut fields) = data.split_at(8);
+                $(
+                    if discriminator == event_discriminator(stringify!($name)) {
+                        return borsh::BorshDeserialize::deserialize(&mut fields).ok().map(Event::$name);
+                    }
+                )*
+                None
+            }
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Event::$name(_) => stringify!($name),)*
+                }
+            }
+        }
+    };
+}
+
+// Anchor's event discriminator
+pub fn event_discriminator(name: &str) -> [u8; 8] {
+    let mut discriminator = [0; 8];
+    discriminator.copy_from_slice(&hash(format!("event:{name}").as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+// Teams are identified by their TeamData account and matches by their MatchState account
+events! {
+    TeamCreated {
+        team: Pubkey,
+        owner: Pubkey,
+        name: String,
+        stake_amount: u64,
+    }
+    PlayerAdded {
+        team: Pubkey,
+        player_nft_mint: Pubkey,
+        position: String,
+    }
+    PlayerRemoved {
+        team: Pubkey,
+        player_nft_mint: Pubkey,
+    }
+    // A player freed from a disbanded team or from a team that no longer lists it
+    PlayerReleased {
+        team: Pubkey,
+        player_nft_mint: Pubkey,
+    }
+    StrategySet {
+        team: Pubkey,
+        strategy_type: StrategyType,
+        strategy_description: String,
+    }
+    TeamDisbanded {
+        team: Pubkey,
+        owner: Pubkey,
+        refund: u64,
+        penalties: u64,
+    }
+    MatchScheduled {
+        match_key: Pubkey,
+        team1: Pubkey,
+        team2: Pubkey,
+        pending_match_id: u64,
+        match_type: String,
+        entry_fee: u64,
+    }
+    EntryFeePaid {
+        match_key: Pubkey,
+        team: Pubkey,
+        amount: u64,
+    }
+    LineupsLocked {
+        match_key: Pubkey,
+        team1_lineup: [Pubkey; 5],
+        team2_lineup: [Pubkey; 5],
+    }
+    MatchConsented {
+        match_key: Pubkey,
+        team: Pubkey,
+    }
+    SeedCommitted {
+        match_key: Pubkey,
+        team: Pubkey,
+    }
+    SeedRevealed {
+        match_key: Pubkey,
+        team: Pubkey,
+    }
+    // Ratings are the stored values after the match
+    MatchSimulated {
+        match_key: Pubkey,
+        team1: Pubkey,
+        team2: Pubkey,
+        team1_score: u8,
+        team2_score: u8,
+        seed: [u8; 32],
+        team1_rating: u16,
+        team2_rating: u16,
+        team1_rating_delta: i32,
+        team2_rating_delta: i32,
+    }
+    // A forfeit counts as a 1-0 win for `winner`
+    MatchForfeited {
+        match_key: Pubkey,
+        team1: Pubkey,
+        team2: Pubkey,
+        winner: Pubkey,
+        penalty: u64,
+        team1_rating: u16,
+        team2_rating: u16,
+        team1_rating_delta: i32,
+        team2_rating_delta: i32,
+    }
+    // Neither side did its part in time; the entry fees go back to the owners
+    MatchVoided {
+        match_key: Pubkey,
+    }
+    MatchDisputed {
+        match_key: Pubkey,
+        team: Pubkey,
+    }
+    // `refund`: the result was voided and the entry fees go back to the owners
+    DisputeResolved {
+        match_key: Pubkey,
+        refund: bool,
+    }
+    // Refunds go back to the owners when lineups were never locked or the match was voided
+    FeesSettled {
+        match_key: Pubkey,
+        to_creators: u64,
+        to_treasury: u64,
+        refunded: u64,
+    }
+}
@@ -0,0 +1,44 @@
+// Client labels for enums: matching ignores case and punctuation, so "Split-Push",
+// "split push" and "splitpush" are the same label.
+
+// Whether `label` normalizes to `key`, a lowercase alphanumeric string
+pub fn matches(label: &str, key: &str) -> bool {
+    label
+        .bytes()
+        .filter(u8::is_ascii_alphanumeric)
+        .map(|b| b.to_ascii_lowercase())
+        .eq(key.bytes())
+}
+
+// Serialize as the canonical label and deserialize through the enum's `parse`
+macro_rules! label_serde {
+    ($name:ident, $expecting:literal) => {
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.label())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct LabelVisitor;
+
+                impl serde::de::Visitor<'_> for LabelVisitor {
+                    type Value = $name;
+
+                    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+                        formatter.write_str($expecting)
+                    }
+
+                    fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<$name, E> {
+                        $name::parse(value).ok_or_else(|| E::invalid_value(serde::de::Unexpected::Str(value), &self))
+                    }
+                }
+
+                deserializer.deserialize_str(LabelVisitor)
+            }
+        }
+    };
+}
+
+pub(crate) use label_serde;
@@ -0,0 +1,40 @@
+// Types shared by the esport programs and off-chain Rust tools.
+//
+// The domain types and error codes are no_std; the system action arguments and events need
+// the `std` feature (on by default). Components enable `anchor` to store the types in accounts.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+// Borsh layout for types stored in components. With `anchor`, the Anchor derives (same
+// layout) also provide InitSpace and the IDL definition.
+macro_rules! stored {
+    ($item:item) => {
+        #[cfg_attr(
+            feature = "anchor",
+            derive(anchor_lang::AnchorSerialize, anchor_lang::AnchorDeserialize, anchor_lang::InitSpace)
+        )]
+        #[cfg_attr(not(feature = "anchor"), derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
+        $item
+    };
+}
+
+pub mod errors;
+pub mod label;
+pub mod matches;
+pub mod player;
+pub mod rating;
+pub mod team;
+
+#[cfg(feature = "std")]
+pub mod actions;
+#[cfg(feature = "std")]
+pub mod events;
+#[cfg(feature = "std")]
+pub mod serde_pubkey;
+
+pub use matches::{MatchStatus, MatchType, PendingMatchStatus, RandomnessSource, MAX_SERIES_LENGTH};
+pub use player::Role;
+pub use rating::{RatingChange, RatingModel};
+pub use team::StrategyType;
+
+#[cfg(feature = "std")]
+pub use actions::{ChosenAttributes, MatchAction, MatchmakingAction, PlayerAction, QueueAction, TeamAction};
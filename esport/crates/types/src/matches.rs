@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+
+// Longest series supported; keeps a whole series within the compute budget
+pub const MAX_SERIES_LENGTH: u8 = 7;
+
+stored! {
+// Lifecycle of a match:
+//
+//   Scheduled -> LineupsLocked -> InProgress -> Finished <-> Disputed -> Voided
+//        \             \              \
+//         +-------------+--------------+--> Forfeited
+//
+// A dispute is resolved once, by the admin or the match operator: back to Finished when the
+// result stands, or Voided when it is thrown out and the entry fees are refunded. A match
+// whose lineups are locked is also Voided when neither owner did their part in time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MatchStatus {
+    #[default]
+    None,
+    Scheduled,
+    LineupsLocked,
+    InProgress,
+    Finished,
+    Forfeited,
+    Disputed,
+    Voided,
+}
+}
+
+stored! {
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PendingMatchStatus {
+    // Waiting for the opponent to accept or decline
+    #[default]
+    Challenged,
+    // Opponent accepted; the match can be scheduled
+    Accepted,
+}
+}
+
+// Format of a match, read from the free-form match type chosen by the teams: "bo3",
+// "ranked-bo5", ... Match types without an odd "boN" suffix are played as a single game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MatchType {
+    pub best_of: u8,
+}
+
+impl MatchType {
+    pub fn parse(match_type: &str) -> Self {
+        let bytes = match_type.as_bytes();
+        let best_of = (0..bytes.len().saturating_sub(1))
+            .rev()
+            .find(|&at| bytes[at].eq_ignore_ascii_case(&b'b') && bytes[at + 1].eq_ignore_ascii_case(&b'o'))
+            .and_then(|at| match_type[at + 2..].parse::<u8>().ok())
+            .filter(|n| n % 2 == 1 && *n <= MAX_SERIES_LENGTH)
+            .unwrap_or(1);
+
+        Self { best_of }
+    }
+
+    pub fn wins_needed(&self) -> u8 {
+        self.best_of / 2 + 1
+    }
+}
+
+stored! {
+// Where the randomness of a simulated match comes from; fixed when the match is scheduled
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RandomnessSource {
+    // Both team owners commit a seed hash before the match and reveal the seed afterwards
+    #[default]
+    CommitReveal,
+    // An oracle signs the match and its locked lineups; the signature is hashed into the seed
+    Vrf,
+}
+}
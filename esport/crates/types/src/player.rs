@@ -0,0 +1,59 @@
+use crate::label::{self, label_serde};
+
+stored! {
+// Lane/role a player specialises in. Stored by label in PlayerStats.role and in the
+// position of roster entries, and by value in locked lineups.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Role {
+    Top,
+    Jungle,
+    Mid,
+    Carry,
+    Support,
+}
+}
+
+// Labels accepted from clients, e.g. "Midlaner", "jungle", "ADC"
+const ROLE_LABELS: [(&str, Role); 17] = [
+    ("top", Role::Top),
+    ("toplaner", Role::Top),
+    ("toplane", Role::Top),
+    ("jungle", Role::Jungle),
+    ("jungler", Role::Jungle),
+    ("jg", Role::Jungle),
+    ("mid", Role::Mid),
+    ("midlaner", Role::Mid),
+    ("midlane", Role::Mid),
+    ("carry", Role::Carry),
+    ("adc", Role::Carry),
+    ("bot", Role::Carry),
+    ("botlaner", Role::Carry),
+    ("marksman", Role::Carry),
+    ("support", Role::Support),
+    ("supp", Role::Support),
+    ("sup", Role::Support),
+];
+
+impl Role {
+    pub const ALL: [Role; 5] = [Role::Top, Role::Jungle, Role::Mid, Role::Carry, Role::Support];
+
+    pub fn parse(label: &str) -> Option<Self> {
+        ROLE_LABELS
+            .iter()
+            .find(|(key, _)| label::matches(label, key))
+            .map(|(_, role)| *role)
+    }
+
+    // Canonical label stored in PlayerStats.role
+    pub fn label(&self) -> &'static str {
+        match self {
+            Role::Top => "Top",
+            Role::Jungle => "Jungle",
+            Role::Mid => "Mid",
+            Role::Carry => "Carry",
+            Role::Support => "Support",
+        }
+    }
+}
+
+label_serde!(Role, "a player role such as \"Mid\" or \"jungler\"");
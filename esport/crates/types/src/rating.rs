@@ -0,0 +1,47 @@
+// Rating models used to update TeamData after a match.
+//
+// Ratings are stored as integers on the component; the maths is done in f64 and
+// rounded back when the new values are written. The maths needs the `std` feature.
+
+#[cfg(feature = "std")]
+mod update;
+
+#[cfg(feature = "std")]
+pub use update::{elo_expected_score, elo_update, glicko2_rating_period, glicko2_update, rate_match, Rating};
+
+pub const DEFAULT_RATING: u16 = 1000;
+pub const DEFAULT_RATING_DEVIATION: u16 = 350;
+pub const DEFAULT_VOLATILITY: u32 = 60_000; // 0.06, stored in millionths
+
+pub const DEFAULT_K_FACTOR: u16 = 32;
+
+stored! {
+// How far one match moved a team's stored rating values, kept with the match so a voided
+// result can be taken back
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RatingChange {
+    pub rating: i32,
+    pub deviation: i32,
+    pub volatility: i64,
+}
+}
+
+// Rating model applied to both teams after a match
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RatingModel {
+    // Classic Elo; only `rating` changes
+    Elo { k_factor: u16 },
+    // Glicko-2 treating every match as its own rating period
+    #[default]
+    Glicko2,
+}
+
+impl RatingModel {
+    // The game config stores the model as a K-factor, 0 selecting Glicko-2
+    pub fn from_k_factor(k_factor: u16) -> Self {
+        match k_factor {
+            0 => RatingModel::Glicko2,
+            k_factor => RatingModel::Elo { k_factor },
+        }
+    }
+}
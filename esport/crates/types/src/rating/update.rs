@@ -0,0 +1,263 @@
+// Rating updates in f64, rounded back to the stored integers by the `stored_*` methods
+use super::{RatingModel, DEFAULT_RATING};
+
+// Glicko-2 constants
+const GLICKO2_SCALE: f64 = 173.7178;
+const GLICKO2_TAU: f64 = 0.5;
+const GLICKO2_EPSILON: f64 = 0.000_001;
+const GLICKO2_MAX_ITERATIONS: usize = 50;
+const MIN_RATING_DEVIATION: f64 = 30.0;
+const MAX_RATING_DEVIATION: f64 = 350.0;
+
+const VOLATILITY_SCALE: f64 = 1_000_000.0;
+
+#[derive(Clone, Copy)]
+pub struct Rating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+impl Rating {
+    pub fn from_stored(rating: u16, deviation: u16, volatility: u32) -> Self {
+        Self {
+            rating: rating as f64,
+            deviation: deviation as f64,
+            volatility: volatility as f64 / VOLATILITY_SCALE,
+        }
+    }
+
+    pub fn stored_rating(&self) -> u16 {
+        self.rating.round().clamp(0.0, u16::MAX as f64) as u16
+    }
+
+    pub fn stored_deviation(&self) -> u16 {
+        self.deviation
+            .clamp(MIN_RATING_DEVIATION, MAX_RATING_DEVIATION)
+            .round() as u16
+    }
+
+    pub fn stored_volatility(&self) -> u32 {
+        (self.volatility * VOLATILITY_SCALE).round().clamp(0.0, u32::MAX as f64) as u32
+    }
+}
+
+// Expected score of a player rated `rating` against `opponent_rating`
+pub fn elo_expected_score(rating: f64, opponent_rating: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent_rating - rating) / 400.0))
+}
+
+pub fn elo_update(player: Rating, opponent: Rating, score: f64, k_factor: u16) -> Rating {
+    let expected = elo_expected_score(player.rating, opponent.rating);
+    Rating {
+        rating: player.rating + k_factor as f64 * (score - expected),
+        ..player
+    }
+}
+
+fn glicko2_g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi * phi / (core::f64::consts::PI * core::f64::consts::PI)).sqrt()
+}
+
+// Glicko-2 update against a single opponent
+pub fn glicko2_update(player: Rating, opponent: Rating, score: f64) -> Rating {
+    glicko2_rating_period(player, &[(opponent, score)])
+}
+
+// Glicko-2 update over one rating period of (opponent, score) results, following
+// Glickman's "Example of the Glicko-2 system". A period without games only widens the
+// deviation.
+pub fn glicko2_rating_period(player: Rating, results: &[(Rating, f64)]) -> Rating {
+    let mu = (player.rating - DEFAULT_RATING as f64) / GLICKO2_SCALE;
+    let phi = player.deviation / GLICKO2_SCALE;
+
+    if results.is_empty() {
+        return Rating {
+            deviation: (phi * phi + player.volatility * player.volatility).sqrt() * GLICKO2_SCALE,
+            ..player
+        };
+    }
+
+    // Estimated variance from the games, and the sum of the score surprises
+    let mut v_inverse = 0.0;
+    let mut surprise = 0.0;
+    for (opponent, score) in results {
+        let mu_j = (opponent.rating - DEFAULT_RATING as f64) / GLICKO2_SCALE;
+        let g = glicko2_g(opponent.deviation / GLICKO2_SCALE);
+        let expected = 1.0 / (1.0 + (-g * (mu - mu_j)).exp());
+        v_inverse += g * g * expected * (1.0 - expected);
+        surprise += g * (score - expected);
+    }
+    let v = 1.0 / v_inverse;
+    let delta = v * surprise;
+
+    // New volatility via the Illinois algorithm
+    let a = (player.volatility * player.volatility).ln();
+    let f = |x: f64| {
+        let ex = x.exp();
+        let denom = phi * phi + v + ex;
+        ex * (delta * delta - phi * phi - v - ex) / (2.0 * denom * denom)
+            - (x - a) / (GLICKO2_TAU * GLICKO2_TAU)
+    };
+
+    let mut big_a = a;
+    let mut big_b = if delta * delta > phi * phi + v {
+        (delta * delta - phi * phi - v).ln()
+    } else {
+        let mut k = 1.0;
+        while f(a - k * GLICKO2_TAU) < 0.0 && k < GLICKO2_MAX_ITERATIONS as f64 {
+            k += 1.0;
+        }
+        a - k * GLICKO2_TAU
+    };
+
+    let mut f_a = f(big_a);
+    let mut f_b = f(big_b);
+    let mut iterations = 0;
+    while (big_b - big_a).abs() > GLICKO2_EPSILON && iterations < GLICKO2_MAX_ITERATIONS {
+        let big_c = big_a + (big_a - big_b) * f_a / (f_b - f_a);
+        let f_c = f(big_c);
+        if f_c * f_b <= 0.0 {
+            big_a = big_b;
+            f_a = f_b;
+        } else {
+            f_a /= 2.0;
+        }
+        big_b = big_c;
+        f_b = f_c;
+        iterations += 1;
+    }
+
+    let volatility = (big_a / 2.0).exp();
+    let phi_star = (phi * phi + volatility * volatility).sqrt();
+    let new_phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / v).sqrt();
+    let new_mu = mu + new_phi * new_phi * surprise;
+
+    Rating {
+        rating: new_mu * GLICKO2_SCALE + DEFAULT_RATING as f64,
+        deviation: new_phi * GLICKO2_SCALE,
+        volatility,
+    }
+}
+
+// Rate a single match for both sides from their pre-match ratings
+pub fn rate_match(model: RatingModel, team1: Rating, team2: Rating, team1_won: bool) -> (Rating, Rating) {
+    let (score1, score2) = if team1_won { (1.0, 0.0) } else { (0.0, 1.0) };
+    match model {
+        RatingModel::Elo { k_factor } => (
+            elo_update(team1, team2, score1, k_factor),
+            elo_update(team2, team1, score2, k_factor),
+        ),
+        RatingModel::Glicko2 => (
+            glicko2_update(team1, team2, score1),
+            glicko2_update(team2, team1, score2),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rating(rating: f64, deviation: f64) -> Rating {
+        Rating { rating, deviation, volatility: 0.06 }
+    }
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!((actual - expected).abs() <= tolerance, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn glickman_example() {
+        // Glickman's worked example. Only rating differences matter, so the paper's 1500
+        // centre doesn't have to match DEFAULT_RATING.
+        let player = rating(1500.0, 200.0);
+        let results = [
+            (rating(1400.0, 30.0), 1.0),
+            (rating(1550.0, 100.0), 0.0),
+            (rating(1700.0, 300.0), 0.0),
+        ];
+        let rated = glicko2_rating_period(player, &results);
+
+        assert_close(rated.rating, 1464.06, 0.01);
+        assert_close(rated.deviation, 151.52, 0.01);
+        assert_close(rated.volatility, 0.05999, 0.00001);
+
+        // Shifted to the stored scale it rounds to the same integers, less the offset
+        let shifted = glicko2_rating_period(
+            rating(1000.0, 200.0),
+            &results.map(|(opponent, score)| (rating(opponent.rating - 500.0, opponent.deviation), score)),
+        );
+        assert_eq!(shifted.stored_rating(), 964);
+        assert_eq!(shifted.stored_deviation(), 152);
+        assert_eq!(shifted.stored_volatility(), 59_996);
+    }
+
+    #[test]
+    fn idle_period_widens_deviation() {
+        let rated = glicko2_rating_period(rating(1000.0, 200.0), &[]);
+        assert_eq!(rated.rating, 1000.0);
+        assert_close(rated.deviation, 200.2714, 0.0001);
+        assert_eq!(rated.volatility, 0.06);
+    }
+
+    #[test]
+    fn large_upset_takes_the_direct_bracket() {
+        // An upset big enough that delta² > phi² + v: the Illinois solver starts from
+        // ln(delta² - phi² - v) instead of stepping down from ln(sigma²)
+        let player = Rating { rating: 1000.0, deviation: 30.0, volatility: 0.06 };
+        let rated = glicko2_update(player, Rating { rating: 2500.0, deviation: 30.0, volatility: 0.06 }, 1.0);
+
+        assert!(rated.rating.is_finite() && rated.rating > player.rating);
+        assert!(rated.volatility > player.volatility);
+        assert!(rated.deviation > 0.0 && rated.deviation < MAX_RATING_DEVIATION);
+    }
+
+    #[test]
+    fn elo_is_zero_sum() {
+        let team1 = rating(1200.0, 350.0);
+        let team2 = rating(1000.0, 350.0);
+        for team1_won in [true, false] {
+            let (new1, new2) = rate_match(RatingModel::Elo { k_factor: 32 }, team1, team2, team1_won);
+            assert_close(new1.rating - team1.rating, team2.rating - new2.rating, 1e-9);
+            assert_eq!(new1.deviation, team1.deviation);
+        }
+        assert_close(elo_expected_score(1200.0, 1000.0), 0.7597, 0.0001);
+        assert_close(elo_expected_score(1000.0, 1000.0), 0.5, 1e-12);
+    }
+
+    #[test]
+    fn glicko2_is_symmetric() {
+        // Equal teams move by the same amount in opposite directions
+        let team = rating(1000.0, 350.0);
+        let (winner, loser) = rate_match(RatingModel::Glicko2, team, team, true);
+        assert_close(winner.rating - 1000.0, 1000.0 - loser.rating, 1e-9);
+        assert_close(winner.deviation, loser.deviation, 1e-9);
+        assert_close(winner.volatility, loser.volatility, 1e-12);
+
+        // Swapping the sides swaps the results
+        let team1 = rating(1100.0, 120.0);
+        let team2 = rating(950.0, 250.0);
+        let (new1, new2) = rate_match(RatingModel::Glicko2, team1, team2, false);
+        let (swapped2, swapped1) = rate_match(RatingModel::Glicko2, team2, team1, true);
+        assert_eq!(new1.rating, swapped1.rating);
+        assert_eq!(new2.rating, swapped2.rating);
+        assert!(new1.rating < team1.rating && new2.rating > team2.rating);
+    }
+
+    #[test]
+    fn stored_values_round_trip() {
+        for (r, d, v) in [(1000, 350, 60_000), (0, 30, 0), (u16::MAX, 200, 59_996), (1464, 152, 123_456)] {
+            let rating = Rating::from_stored(r, d, v);
+            assert_eq!((rating.stored_rating(), rating.stored_deviation(), rating.stored_volatility()), (r, d, v));
+        }
+
+        // Rounded to the nearest integer, clamped to what can be stored
+        let rating = Rating { rating: 1463.5, deviation: 151.49, volatility: 0.059_999_5 };
+        assert_eq!((rating.stored_rating(), rating.stored_deviation(), rating.stored_volatility()), (1464, 151, 60_000));
+        let rating = Rating { rating: -12.0, deviation: 12.0, volatility: -1.0 };
+        assert_eq!((rating.stored_rating(), rating.stored_deviation(), rating.stored_volatility()), (0, 30, 0));
+        let rating = Rating { rating: 70_000.0, deviation: 900.0, volatility: 1e9 };
+        assert_eq!((rating.stored_rating(), rating.stored_deviation(), rating.stored_volatility()), (u16::MAX, 350, u32::MAX));
+    }
+}
@@ -0,0 +1,16 @@
+// Serialize a Pubkey as its base58 string, the way clients write keys in JSON arguments:
+// `#[serde(with = "esport_types::serde_pubkey")]`
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serializer};
+use solana_program::pubkey::Pubkey;
+
+pub fn serialize<S: Serializer>(key: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(key)
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+    // Owned, since flattened arguments are buffered before they reach this
+    let encoded = String::deserialize(deserializer)?;
+    Pubkey::from_str(&encoded).map_err(|_| de::Error::custom("invalid base58 public key"))
+}
@@ -0,0 +1,58 @@
+use crate::label::{self, label_serde};
+
+stored! {
+// Strategy archetypes; see match_system for how they counter each other
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum StrategyType {
+    #[default]
+    None,
+    Aggressive,
+    SplitPush,
+    Teamfight,
+    ObjectiveControl,
+    Defensive,
+}
+}
+
+// Labels accepted from clients, e.g. "aggressive", "split-push" or "ObjectiveControl"
+const STRATEGY_LABELS: [(&str, StrategyType); 7] = [
+    ("aggressive", StrategyType::Aggressive),
+    ("aggro", StrategyType::Aggressive),
+    ("splitpush", StrategyType::SplitPush),
+    ("teamfight", StrategyType::Teamfight),
+    ("objectivecontrol", StrategyType::ObjectiveControl),
+    ("objective", StrategyType::ObjectiveControl),
+    ("defensive", StrategyType::Defensive),
+];
+
+impl StrategyType {
+    // Strategies a team can select
+    pub const ALL: [StrategyType; 5] = [
+        StrategyType::Aggressive,
+        StrategyType::SplitPush,
+        StrategyType::Teamfight,
+        StrategyType::ObjectiveControl,
+        StrategyType::Defensive,
+    ];
+
+    // `None` is never parsed: it only marks a team that hasn't picked a strategy
+    pub fn parse(label: &str) -> Option<Self> {
+        STRATEGY_LABELS
+            .iter()
+            .find(|(key, _)| label::matches(label, key))
+            .map(|(_, strategy)| *strategy)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            StrategyType::None => "None",
+            StrategyType::Aggressive => "Aggressive",
+            StrategyType::SplitPush => "SplitPush",
+            StrategyType::Teamfight => "Teamfight",
+            StrategyType::ObjectiveControl => "ObjectiveControl",
+            StrategyType::Defensive => "Defensive",
+        }
+    }
+}
+
+label_serde!(StrategyType, "a strategy such as \"aggressive\" or \"split-push\"");
@@ -1,22 +1,300 @@
 // match_queue.rs
 use bolt_lang::*;
+use esport_types::errors::QueueError;
+
+pub use esport_types::PendingMatchStatus;
 
 declare_id!("CHUtz6R1YRSYVRf56i4jefH4EiLMGFTx4TuSXa9SfhAy");
 
+// Must match the max_len of MatchQueue.pending_matches and MatchQueue.tickets
+pub const MAX_PENDING_MATCHES: usize = 20;
+pub const MAX_TICKETS: usize = 20;
+
+// Challenges a team may have waiting for an answer at once, so no single team can fill
+// the queue
+pub const MAX_OPEN_CHALLENGES_PER_TEAM: usize = 3;
+
+// Matchmaking rating window: starts at BASE, widens by WIDEN_PER_MINUTE for every
+// minute a ticket waits, and never exceeds MAX
+pub const BASE_RATING_WINDOW: u16 = 50;
+pub const RATING_WINDOW_WIDEN_PER_MINUTE: u16 = 25;
+pub const MAX_RATING_WINDOW: u16 = 400;
+
 // Pending match struct for storage
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
 pub struct PendingMatch {
-    pub team1: Pubkey,
-    pub team2: Pubkey,
+    pub id: u64,
+    pub team1: Pubkey, // Challenger's TeamData component
+    pub team2: Pubkey, // Opponent's TeamData component
     #[max_len(20)]
     pub match_type: String,
     pub timestamp: i64,
+    pub status: PendingMatchStatus,
+    pub expires_at: i64,
+}
+
+// A team waiting for an automatically matched opponent
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct MatchmakingTicket {
+    pub team: Pubkey, // TeamData component
+    pub rating: u16,  // Team rating when it entered the queue
+    #[max_len(20)]
+    pub match_type: String,
+    pub enqueued_at: i64,
+    pub expires_at: i64, // Anyone can prune the ticket from then on
+}
+
+impl MatchmakingTicket {
+    // Largest rating difference this ticket accepts at `now`
+    pub fn rating_window(&self, now: i64) -> u16 {
+        let minutes_waited = ((now - self.enqueued_at).max(0) / 60).min(u16::MAX as i64) as u16;
+        BASE_RATING_WINDOW
+            .saturating_add(minutes_waited.saturating_mul(RATING_WINDOW_WIDEN_PER_MINUTE))
+            .min(MAX_RATING_WINDOW)
+    }
 }
 
 // Component definition for match queue
 #[component]
 #[derive(Default)]
 pub struct MatchQueue {
+    pub next_match_id: u64,
     #[max_len(20)]
     pub pending_matches: Vec<PendingMatch>,
-}
\ No newline at end of file
+    #[max_len(20)]
+    pub tickets: Vec<MatchmakingTicket>,
+}
+
+impl MatchQueue {
+    pub fn challenge(
+        &mut self,
+        team1: Pubkey,
+        team2: Pubkey,
+        match_type: String,
+        timeout: i64, // Seconds the challenge stays open before anyone can expire it
+    ) -> Result<u64> {
+        require!(team1 != team2, QueueError::SameTeam);
+        require!(!match_type.is_empty(), QueueError::InvalidMatchType);
+
+        // Expired entries make room for new ones
+        let now = Clock::get()?.unix_timestamp;
+        self.pending_matches.retain(|m| m.expires_at > now);
+        require!(self.pending_matches.len() < MAX_PENDING_MATCHES, QueueError::QueueFull);
+        require!(
+            self.open_challenges(team1) < MAX_OPEN_CHALLENGES_PER_TEAM,
+            QueueError::TooManyChallenges
+        );
+
+        // Only one open match between the same two teams
+        require!(!self.has_pending(team1, team2), QueueError::MatchAlreadyPending);
+
+        Ok(self.push_pending(team1, team2, match_type, PendingMatchStatus::Challenged, now, now + timeout))
+    }
+
+    pub fn enqueue(
+        &mut self,
+        team: Pubkey,
+        rating: u16,
+        match_type: String,
+        timeout: i64, // Seconds the ticket waits for an opponent before anyone can prune it
+    ) -> Result<()> {
+        require!(!match_type.is_empty(), QueueError::InvalidMatchType);
+
+        // Stale tickets make room for new ones
+        let now = Clock::get()?.unix_timestamp;
+        self.prune_tickets(now);
+        require!(self.tickets.len() < MAX_TICKETS, QueueError::QueueFull);
+        require!(!self.tickets.iter().any(|t| t.team == team), QueueError::AlreadyQueued);
+
+        self.tickets.push(MatchmakingTicket {
+            team,
+            rating,
+            match_type,
+            enqueued_at: now,
+            expires_at: now + timeout,
+        });
+
+        Ok(())
+    }
+
+    pub fn leave(&mut self, team: Pubkey) -> Result<()> {
+        let index = self
+            .tickets
+            .iter()
+            .position(|t| t.team == team)
+            .ok_or(QueueError::NotQueued)?;
+        self.tickets.remove(index);
+        Ok(())
+    }
+
+    // Pair queued teams, closest ratings first, as long as the difference fits inside
+    // both tickets' rating windows. Paired teams become accepted pending matches that
+    // must be played within `accepted_timeout` seconds.
+    // Returns the ids of the new pending matches.
+    pub fn matchmake(&mut self, accepted_timeout: i64) -> Result<Vec<u64>> {
+        let now = Clock::get()?.unix_timestamp;
+        self.prune_tickets(now);
+
+        // Every compatible pair, sorted by rating difference then by the oldest ticket
+        let mut candidates: Vec<(u16, i64, usize, usize)> = Vec::new();
+        for (i, a) in self.tickets.iter().enumerate() {
+            for (j, b) in self.tickets.iter().enumerate().skip(i + 1) {
+                if a.match_type != b.match_type {
+                    continue;
+                }
+                let difference = a.rating.abs_diff(b.rating);
+                if difference <= a.rating_window(now).min(b.rating_window(now)) {
+                    candidates.push((difference, a.enqueued_at.min(b.enqueued_at), i, j));
+                }
+            }
+        }
+        candidates.sort_unstable();
+
+        let mut paired = vec![false; self.tickets.len()];
+        let mut created = Vec::new();
+        for (_, _, i, j) in candidates {
+            if paired[i] || paired[j] || self.pending_matches.len() >= MAX_PENDING_MATCHES {
+                continue;
+            }
+            let (team1, team2) = (self.tickets[i].team, self.tickets[j].team);
+            if self.has_pending(team1, team2) {
+                continue;
+            }
+
+            let match_type = self.tickets[i].match_type.clone();
+            let expires_at = now + accepted_timeout;
+            created.push(self.push_pending(team1, team2, match_type, PendingMatchStatus::Accepted, now, expires_at));
+            paired[i] = true;
+            paired[j] = true;
+        }
+
+        // Drop the tickets that found a match
+        let mut index = 0;
+        self.tickets.retain(|_| {
+            let keep = !paired[index];
+            index += 1;
+            keep
+        });
+
+        Ok(created)
+    }
+
+    pub fn get(&self, id: u64) -> Result<&PendingMatch> {
+        self.pending_matches
+            .iter()
+            .find(|m| m.id == id)
+            .ok_or_else(|| QueueError::PendingMatchNotFound.into())
+    }
+
+    // Accept a challenge; the match must then be played within `timeout` seconds
+    pub fn accept(&mut self, id: u64, timeout: i64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let pending = self.open_challenge(id, now)?;
+
+        pending.status = PendingMatchStatus::Accepted;
+        pending.expires_at = now + timeout;
+
+        Ok(())
+    }
+
+    pub fn decline(&mut self, id: u64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        self.open_challenge(id, now)?;
+        self.remove(id)
+    }
+
+    pub fn cancel(&mut self, id: u64) -> Result<()> {
+        let pending = self.get(id)?;
+        require!(pending.status == PendingMatchStatus::Challenged, QueueError::MatchAlreadyAccepted);
+        self.remove(id)
+    }
+
+    // Remove every pending match and ticket past its deadline; returns how many of each
+    // were expired
+    pub fn expire(&mut self) -> Result<(usize, usize)> {
+        let now = Clock::get()?.unix_timestamp;
+        let before = self.pending_matches.len();
+        self.pending_matches.retain(|m| m.expires_at > now);
+        Ok((before - self.pending_matches.len(), self.prune_tickets(now)))
+    }
+
+    // Accepted match between team1 and team2 that can be played now
+    pub fn playable(&self, id: u64, team1: Pubkey, team2: Pubkey) -> Result<&PendingMatch> {
+        let pending = self.get(id)?;
+        require!(pending.team1 == team1 && pending.team2 == team2, QueueError::TeamMismatch);
+        require!(pending.status == PendingMatchStatus::Accepted, QueueError::MatchNotAccepted);
+        require!(pending.expires_at > Clock::get()?.unix_timestamp, QueueError::MatchExpired);
+        Ok(pending)
+    }
+
+    // Drop a match from the queue once it has been played
+    pub fn complete(&mut self, id: u64) -> Result<()> {
+        self.remove(id)
+    }
+
+    // Challenges issued by `team` that the opponent hasn't answered yet
+    fn open_challenges(&self, team: Pubkey) -> usize {
+        self.pending_matches
+            .iter()
+            .filter(|m| m.team1 == team && m.status == PendingMatchStatus::Challenged)
+            .count()
+    }
+
+    fn prune_tickets(&mut self, now: i64) -> usize {
+        let before = self.tickets.len();
+        self.tickets.retain(|t| t.expires_at > now);
+        before - self.tickets.len()
+    }
+
+    fn has_pending(&self, team1: Pubkey, team2: Pubkey) -> bool {
+        self.pending_matches.iter().any(|m| {
+            (m.team1 == team1 && m.team2 == team2) || (m.team1 == team2 && m.team2 == team1)
+        })
+    }
+
+    fn push_pending(
+        &mut self,
+        team1: Pubkey,
+        team2: Pubkey,
+        match_type: String,
+        status: PendingMatchStatus,
+        now: i64,
+        expires_at: i64,
+    ) -> u64 {
+        let id = self.next_match_id;
+        self.next_match_id += 1;
+
+        self.pending_matches.push(PendingMatch {
+            id,
+            team1,
+            team2,
+            match_type,
+            timestamp: now,
+            status,
+            expires_at,
+        });
+
+        id
+    }
+
+    fn open_challenge(&mut self, id: u64, now: i64) -> Result<&mut PendingMatch> {
+        let pending = self
+            .pending_matches
+            .iter_mut()
+            .find(|m| m.id == id)
+            .ok_or(QueueError::PendingMatchNotFound)?;
+        require!(pending.status == PendingMatchStatus::Challenged, QueueError::MatchAlreadyAccepted);
+        require!(pending.expires_at > now, QueueError::MatchExpired);
+        Ok(pending)
+    }
+
+    fn remove(&mut self, id: u64) -> Result<()> {
+        let index = self
+            .pending_matches
+            .iter()
+            .position(|m| m.id == id)
+            .ok_or(QueueError::PendingMatchNotFound)?;
+        self.pending_matches.remove(index);
+        Ok(())
+    }
+}
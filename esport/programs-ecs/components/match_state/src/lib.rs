@@ -0,0 +1,427 @@
+use bolt_lang::*;
+use solana_program::pubkey::Pubkey;
+use esport_types::errors::MatchError;
+
+pub use esport_types::{MatchStatus, RandomnessSource, RatingChange, Role, StrategyType};
+
+// You'll need to replace this with an actual program ID when deploying
+declare_id!("BqpxFzvkwhr77HFMuEP95jPEFfTN6DYhD3Vpr1pxzRVg");
+
+// Time after a finished match during which either owner can dispute it
+pub const DISPUTE_WINDOW: i64 = 24 * 60 * 60;
+
+// Must match the max_len of MatchState.games; the longest series is a best of 7
+pub const MAX_GAMES: usize = 7;
+
+// Which side of the match a team plays on
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Team1,
+    Team2,
+}
+
+impl Side {
+    pub fn opponent(self) -> Side {
+        match self {
+            Side::Team1 => Side::Team2,
+            Side::Team2 => Side::Team1,
+        }
+    }
+}
+
+// A team's seed for the commit-reveal randomness flow
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct SeedCommitment {
+    pub commitment: [u8; 32], // sha256(seed || owner)
+    pub seed: [u8; 32],
+    pub revealed: bool,
+}
+
+impl SeedCommitment {
+    pub fn committed(&self) -> bool {
+        self.commitment != [0; 32]
+    }
+}
+
+// A lineup player as they were when lineups were locked
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct LockedPlayer {
+    pub role: Option<Role>,     // Role the player specialises in
+    pub position: Option<Role>, // Position the team owner slotted them in
+    // Mechanical, game knowledge, team communication, adaptability, consistency, form, potential
+    pub attributes: [u8; 7],
+}
+
+// A team as it was when lineups were locked. The match is simulated from this snapshot,
+// so neither owner can change the odds once the lineups are known.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, InitSpace)]
+pub struct LockedLineup {
+    pub strategy: StrategyType,
+    pub players: [LockedPlayer; 5], // In lineup order
+}
+
+// Play-by-play summary of one game of a series, replayable from the match seed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct GameLog {
+    pub team1_won: bool,
+    pub team1_fights: u8, // Teamfights won
+    pub team2_fights: u8,
+    pub team1_objectives: u8, // Neutral objectives taken
+    pub team2_objectives: u8,
+    pub mvp: Pubkey, // Player NFT mint
+    // Per-player contribution scores, in lineup order
+    pub team1_contributions: [u16; 5],
+    pub team2_contributions: [u16; 5],
+}
+
+// On-chain record of one match, one per match entity
+#[component]
+#[derive(Default)]
+pub struct MatchState {
+    pub status: MatchStatus,
+    pub pending_match_id: u64, // MatchQueue entry this match was scheduled from
+    pub team1: Pubkey,         // TeamData components
+    pub team2: Pubkey,
+    #[max_len(20)]
+    pub match_type: String,
+    pub team1_lineup: [Pubkey; 5], // Player NFT mints
+    pub team2_lineup: [Pubkey; 5],
+    pub team1_seed: SeedCommitment,
+    pub team2_seed: SeedCommitment,
+    pub seed: [u8; 32], // Seed the match was simulated with, for replays
+    pub team1_score: u8,
+    pub team2_score: u8,
+    #[max_len(7)]
+    pub games: Vec<GameLog>, // One entry per game played, empty for forfeits
+    pub winner: Pubkey, // Default until the match is finished or forfeited
+    pub scheduled_at: i64,
+    pub locked_at: i64,
+    pub started_at: i64,
+    pub finished_at: i64,
+    pub entry_fee: u64, // Lamports each team pays into the match fee vault
+    pub team1_fee_paid: bool,
+    pub team2_fee_paid: bool,
+    pub fees_settled: bool,
+    pub team2_consented: bool, // Team 2's owner agreed to simulate the locked lineups
+    pub team1_locked: LockedLineup,
+    pub team2_locked: LockedLineup,
+    pub randomness: RandomnessSource, // Chosen when the match is scheduled
+    pub dispute_resolved: bool,
+    pub ready_deadline: i64, // Team 2's consent and both seed commitments are due by then
+    pub simulate_deadline: i64, // Team 1 must simulate by then; set once the seed is known and team 2 consented
+    // What the result did to each team's rating, taken back if a dispute voids it
+    pub team1_rating_change: RatingChange,
+    pub team2_rating_change: RatingChange,
+}
+
+impl MatchState {
+    pub fn schedule(
+        &mut self,
+        pending_match_id: u64,
+        team1: Pubkey,
+        team2: Pubkey,
+        match_type: String,
+        entry_fee: u64,
+        randomness: RandomnessSource,
+    ) -> Result<()> {
+        require!(self.status == MatchStatus::None, MatchError::AlreadyScheduled);
+
+        self.status = MatchStatus::Scheduled;
+        self.pending_match_id = pending_match_id;
+        self.team1 = team1;
+        self.team2 = team2;
+        self.match_type = match_type;
+        self.entry_fee = entry_fee;
+        self.randomness = randomness;
+        self.scheduled_at = Clock::get()?.unix_timestamp;
+
+        Ok(())
+    }
+
+    // Check that the match is being played by these two TeamData components
+    pub fn require_teams(&self, team1: Pubkey, team2: Pubkey) -> Result<()> {
+        require!(self.team1 == team1 && self.team2 == team2, MatchError::TeamMismatch);
+        Ok(())
+    }
+
+    // Mark a side's entry fee as paid; returns the amount it owes
+    pub fn pay_entry_fee(&mut self, side: Side) -> Result<u64> {
+        self.require_status(MatchStatus::Scheduled)?;
+
+        let paid = match side {
+            Side::Team1 => &mut self.team1_fee_paid,
+            Side::Team2 => &mut self.team2_fee_paid,
+        };
+        require!(!*paid, MatchError::FeeAlreadyPaid);
+        *paid = true;
+
+        Ok(self.entry_fee)
+    }
+
+    pub fn fees_paid(&self) -> bool {
+        self.entry_fee == 0 || (self.team1_fee_paid && self.team2_fee_paid)
+    }
+
+    // Freeze both lineups; the owners then have `timeout` seconds to consent and commit
+    pub fn lock_lineups(
+        &mut self,
+        team1_lineup: [Pubkey; 5],
+        team2_lineup: [Pubkey; 5],
+        team1_locked: LockedLineup,
+        team2_locked: LockedLineup,
+        timeout: i64,
+    ) -> Result<()> {
+        self.require_status(MatchStatus::Scheduled)?;
+        require!(self.fees_paid(), MatchError::FeesNotPaid);
+
+        self.team1_lineup = team1_lineup;
+        self.team2_lineup = team2_lineup;
+        self.team1_locked = team1_locked;
+        self.team2_locked = team2_locked;
+        self.status = MatchStatus::LineupsLocked;
+        self.locked_at = Clock::get()?.unix_timestamp;
+        self.ready_deadline = self.locked_at + timeout;
+
+        Ok(())
+    }
+
+    // Lineups must be the ones that were locked
+    pub fn require_lineups(&self, team1_lineup: &[Pubkey; 5], team2_lineup: &[Pubkey; 5]) -> Result<()> {
+        require!(
+            self.team1_lineup == *team1_lineup && self.team2_lineup == *team2_lineup,
+            MatchError::LineupMismatch
+        );
+        Ok(())
+    }
+
+    // Commit a seed hash; the match starts once both sides have committed
+    pub fn commit_seed(&mut self, side: Side, commitment: [u8; 32]) -> Result<()> {
+        require!(
+            self.status == MatchStatus::LineupsLocked,
+            MatchError::InvalidStatus
+        );
+        require!(self.randomness == RandomnessSource::CommitReveal, MatchError::WrongRandomnessSource);
+        require!(commitment != [0; 32], MatchError::InvalidSeedCommitment);
+
+        let entry = self.seed_entry_mut(side);
+        require!(!entry.committed(), MatchError::SeedAlreadyCommitted);
+        entry.commitment = commitment;
+
+        if self.team1_seed.committed() && self.team2_seed.committed() {
+            self.status = MatchStatus::InProgress;
+            self.started_at = Clock::get()?.unix_timestamp;
+        }
+
+        Ok(())
+    }
+
+    pub fn reveal_seed(&mut self, side: Side, seed: [u8; 32], owner: &Pubkey, timeout: i64) -> Result<()> {
+        self.require_status(MatchStatus::InProgress)?;
+
+        let entry = self.seed_entry_mut(side);
+        require!(!entry.revealed, MatchError::SeedAlreadyRevealed);
+
+        // Commitments are bound to the owner so one team can't replay the other's hash
+        require!(seed_commitment(&seed, owner) == entry.commitment, MatchError::SeedMismatch);
+
+        entry.seed = seed;
+        entry.revealed = true;
+
+        self.start_simulate_deadline(timeout)
+    }
+
+    // Both revealed seeds, once available
+    pub fn revealed_seeds(&self) -> Option<([u8; 32], [u8; 32])> {
+        if self.team1_seed.revealed && self.team2_seed.revealed {
+            Some((self.team1_seed.seed, self.team2_seed.seed))
+        } else {
+            None
+        }
+    }
+
+    // Team 2's sign-off on the locked lineups; team 1 gives theirs by running the simulation
+    pub fn consent(&mut self, timeout: i64) -> Result<()> {
+        require!(self.ready_to_simulate(), MatchError::InvalidStatus);
+        require!(!self.team2_consented, MatchError::AlreadyConsented);
+        self.team2_consented = true;
+        self.start_simulate_deadline(timeout)
+    }
+
+    // Once team 2 consented and the seed can be known (an oracle match needs no reveals),
+    // team 1 could work out the result off-chain, so it gets `timeout` seconds to play it
+    fn start_simulate_deadline(&mut self, timeout: i64) -> Result<()> {
+        let seed_known = self.randomness == RandomnessSource::Vrf || self.revealed_seeds().is_some();
+        if self.team2_consented && seed_known {
+            self.simulate_deadline = Clock::get()?.unix_timestamp + timeout;
+        }
+        Ok(())
+    }
+
+    // Whether the match can be simulated now
+    pub fn ready_to_simulate(&self) -> bool {
+        matches!(self.status, MatchStatus::LineupsLocked | MatchStatus::InProgress)
+    }
+
+    pub fn finish(
+        &mut self,
+        seed: [u8; 32],
+        team1_score: u8,
+        team2_score: u8,
+        games: Vec<GameLog>,
+    ) -> Result<()> {
+        require!(self.ready_to_simulate(), MatchError::InvalidStatus);
+        require!(games.len() <= MAX_GAMES, MatchError::TooManyGames);
+
+        self.seed = seed;
+        self.team1_score = team1_score;
+        self.team2_score = team2_score;
+        self.games = games;
+        self.winner = if team1_score > team2_score { self.team1 } else { self.team2 };
+        self.status = MatchStatus::Finished;
+        self.finished_at = Clock::get()?.unix_timestamp;
+
+        Ok(())
+    }
+
+    // A side gives up a match that hasn't finished
+    pub fn forfeit(&mut self, side: Side) -> Result<()> {
+        require!(
+            matches!(
+                self.status,
+                MatchStatus::Scheduled | MatchStatus::LineupsLocked | MatchStatus::InProgress
+            ),
+            MatchError::InvalidStatus
+        );
+
+        self.winner = match side.opponent() {
+            Side::Team1 => self.team1,
+            Side::Team2 => self.team2,
+        };
+        self.status = MatchStatus::Forfeited;
+        self.finished_at = Clock::get()?.unix_timestamp;
+
+        Ok(())
+    }
+
+    // Once the current step's deadline has passed, a side that did its part claims the win
+    // from one that didn't; if neither did, the match is voided and the fees refunded. The
+    // deadline is `ready_deadline` for consent and commitments, `reveal_timeout` seconds
+    // after the match started for reveals, then `simulate_deadline` for team 1 to play it.
+    // Stops an owner from stalling forever.
+    pub fn claim_forfeit(&mut self, side: Side, reveal_timeout: i64) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        if self.simulate_deadline != 0 {
+            require!(self.ready_to_simulate(), MatchError::InvalidStatus);
+            require!(now > self.simulate_deadline, MatchError::SimulateWindowOpen);
+            require!(side == Side::Team2, MatchError::OpponentNotStalled);
+            return self.forfeit(Side::Team1);
+        }
+
+        match self.status {
+            MatchStatus::LineupsLocked => require!(now > self.ready_deadline, MatchError::ReadyWindowOpen),
+            MatchStatus::InProgress => require!(now > self.started_at + reveal_timeout, MatchError::RevealWindowOpen),
+            _ => return Err(MatchError::InvalidStatus.into()),
+        }
+        require!(!self.step_done(side.opponent()), MatchError::OpponentNotStalled);
+
+        if self.step_done(side) {
+            self.forfeit(side.opponent())
+        } else {
+            self.void(now);
+            Ok(())
+        }
+    }
+
+    // Whether a side did what the current step asks of it: team 2 consents, and each side
+    // of a commit-reveal match commits, then reveals, its seed
+    fn step_done(&self, side: Side) -> bool {
+        let entry = self.seed_entry(side);
+        let seed_done = match self.status {
+            MatchStatus::InProgress => entry.revealed,
+            _ => self.randomness != RandomnessSource::CommitReveal || entry.committed(),
+        };
+        seed_done && (side == Side::Team1 || self.team2_consented)
+    }
+
+    // Call off a match nobody played, so settling refunds the entry fees
+    fn void(&mut self, now: i64) {
+        self.status = MatchStatus::Voided;
+        self.finished_at = now;
+    }
+
+    pub fn dispute(&mut self) -> Result<()> {
+        self.require_status(MatchStatus::Finished)?;
+        require!(!self.dispute_resolved, MatchError::DisputeAlreadyResolved);
+        require!(
+            Clock::get()?.unix_timestamp <= self.finished_at + DISPUTE_WINDOW,
+            MatchError::DisputeWindowClosed
+        );
+
+        self.status = MatchStatus::Disputed;
+
+        Ok(())
+    }
+
+    // Settle a dispute: the result stands, or it is voided and the entry fees refunded
+    pub fn resolve_dispute(&mut self, refund: bool) -> Result<()> {
+        self.require_status(MatchStatus::Disputed)?;
+
+        self.status = if refund { MatchStatus::Voided } else { MatchStatus::Finished };
+        self.dispute_resolved = true;
+
+        Ok(())
+    }
+
+    // Whether the match got as far as locked lineups, i.e. the NFTs actually played
+    pub fn lineups_locked(&self) -> bool {
+        self.locked_at != 0
+    }
+
+    // Whether settling pays the entry fees back to the owners instead of out to creators
+    // and the treasury
+    pub fn refunds_fees(&self) -> bool {
+        !self.lineups_locked() || self.status == MatchStatus::Voided
+    }
+
+    // Fees are settled once per match: after the dispute window of a finished match or once
+    // its dispute was resolved, and right away for a forfeit
+    pub fn settle_fees(&mut self) -> Result<()> {
+        match self.status {
+            MatchStatus::Finished => require!(
+                self.dispute_resolved || Clock::get()?.unix_timestamp > self.finished_at + DISPUTE_WINDOW,
+                MatchError::DisputeWindowOpen
+            ),
+            MatchStatus::Forfeited | MatchStatus::Voided => {}
+            _ => return Err(MatchError::InvalidStatus.into()),
+        }
+        require!(!self.fees_settled, MatchError::FeesAlreadySettled);
+
+        self.fees_settled = true;
+
+        Ok(())
+    }
+
+    fn require_status(&self, status: MatchStatus) -> Result<()> {
+        require!(self.status == status, MatchError::InvalidStatus);
+        Ok(())
+    }
+
+    fn seed_entry(&self, side: Side) -> &SeedCommitment {
+        match side {
+            Side::Team1 => &self.team1_seed,
+            Side::Team2 => &self.team2_seed,
+        }
+    }
+
+    fn seed_entry_mut(&mut self, side: Side) -> &mut SeedCommitment {
+        match side {
+            Side::Team1 => &mut self.team1_seed,
+            Side::Team2 => &mut self.team2_seed,
+        }
+    }
+}
+
+// Commitment a team owner submits before a match: sha256(seed || owner)
+pub fn seed_commitment(seed: &[u8; 32], owner: &Pubkey) -> [u8; 32] {
+    solana_program::hash::hashv(&[seed, owner.as_ref()]).to_bytes()
+}
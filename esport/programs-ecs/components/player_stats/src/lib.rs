@@ -1,5 +1,10 @@
 use bolt_lang::*;
 use solana_program::pubkey::Pubkey;
+use esport_types::errors::PlayerError;
+
+pub use esport_types::Role;
+
+pub mod ownership;
 
 // You'll need to replace this with an actual program ID when deploying
 declare_id!("5VLAaXmJsdUeV66WQJKvwGd3cLfsM5ETnN3PXC9ny1jh");
@@ -20,6 +25,7 @@ pub struct PlayerStats {
     pub matches_played: u32,
     pub wins: u32,
     pub losses: u32,
+    pub current_team: Pubkey, // TeamData component the player is rostered on, default when free
 }
 
 // Just use a standard impl without any special attribute
@@ -36,14 +42,17 @@ impl PlayerStats {
         form: u8,
         potential: u8,
     ) -> Result<()> {
+        // Stats are bound to one NFT for good
+        require!(self.nft_mint == Pubkey::default(), PlayerError::AlreadyInitialized);
+        
         // Validate attribute ranges (0-100)
-        require!(mechanical <= 100, ComponentError::InvalidAttribute);
-        require!(game_knowledge <= 100, ComponentError::InvalidAttribute);
-        require!(team_communication <= 100, ComponentError::InvalidAttribute);
-        require!(adaptability <= 100, ComponentError::InvalidAttribute);
-        require!(consistency <= 100, ComponentError::InvalidAttribute);
-        require!(form <= 100, ComponentError::InvalidAttribute);
-        require!(potential <= 100, ComponentError::InvalidAttribute);
+        require!(mechanical <= 100, PlayerError::InvalidAttribute);
+        require!(game_knowledge <= 100, PlayerError::InvalidAttribute);
+        require!(team_communication <= 100, PlayerError::InvalidAttribute);
+        require!(adaptability <= 100, PlayerError::InvalidAttribute);
+        require!(consistency <= 100, PlayerError::InvalidAttribute);
+        require!(form <= 100, PlayerError::InvalidAttribute);
+        require!(potential <= 100, PlayerError::InvalidAttribute);
         
         self.nft_mint = nft_mint;
         self.role = role;
@@ -61,9 +70,27 @@ impl PlayerStats {
         Ok(())
     }
     
+    // Role parsed from the stored label, None for unrecognised labels
+    pub fn role(&self) -> Option<Role> {
+        Role::parse(&self.role)
+    }
+    
+    // Lock the player to a team's roster; a player can only be on one team at a time
+    pub fn join_team(&mut self, team: Pubkey) -> Result<()> {
+        require!(self.current_team == Pubkey::default(), PlayerError::PlayerOnAnotherTeam);
+        self.current_team = team;
+        Ok(())
+    }
+    
+    pub fn leave_team(&mut self, team: Pubkey) -> Result<()> {
+        require!(self.current_team == team, PlayerError::PlayerNotOnTeam);
+        self.current_team = Pubkey::default();
+        Ok(())
+    }
+    
     pub fn update_form(&mut self, new_form: u8) -> Result<()> {
         // Only owner or authorized systems should update form
-        require!(new_form <= 100, ComponentError::InvalidAttribute);
+        require!(new_form <= 100, PlayerError::InvalidAttribute);
         self.form = new_form;
         Ok(())
     }
@@ -77,11 +104,14 @@ impl PlayerStats {
         }
         Ok(())
     }
+    
+    // Take back the result of a voided match
+    pub fn revert_match_result(&mut self, win: bool) {
+        self.matches_played = self.matches_played.saturating_sub(1);
+        if win {
+            self.wins = self.wins.saturating_sub(1);
+        } else {
+            self.losses = self.losses.saturating_sub(1);
+        }
+    }
 }
-
-// Custom errors for the component
-#[error_code]
-pub enum ComponentError {
-    #[msg("Attribute value must be between 0 and 100")]
-    InvalidAttribute,
-}
\ No newline at end of file
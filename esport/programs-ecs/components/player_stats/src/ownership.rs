@@ -0,0 +1,24 @@
+use anchor_spl::token::{self, TokenAccount};
+use bolt_lang::*;
+use solana_program::pubkey::Pubkey;
+
+use esport_types::errors::PlayerError;
+
+use crate::PlayerStats;
+
+impl PlayerStats {
+    // Check that `owner` holds this player's NFT in `token_account`, an SPL token account
+    // for the player's mint with a balance of exactly one
+    pub fn verify_owner(&self, token_account: &AccountInfo, owner: &Pubkey) -> Result<()> {
+        require_keys_eq!(*token_account.owner, token::ID, PlayerError::InvalidTokenAccount);
+
+        let data = token_account.try_borrow_data()?;
+        let account = TokenAccount::try_deserialize(&mut &data[..])
+            .map_err(|_| PlayerError::InvalidTokenAccount)?;
+
+        require_keys_eq!(account.mint, self.nft_mint, PlayerError::NftMintMismatch);
+        require!(account.owner == *owner && account.amount == 1, PlayerError::NftNotOwned);
+
+        Ok(())
+    }
+}
@@ -1,9 +1,19 @@
 use bolt_lang::*;
 use solana_program::pubkey::Pubkey;
+use esport_types::errors::TeamError;
+
+pub use esport_types::StrategyType;
+
+pub mod rating;
+
+use rating::{Rating, RatingChange, RatingModel};
 
 // You'll need to replace this with an actual program ID when deploying
 declare_id!("D4xnj8Qd5vh7zZ2t5oVT78csuJUL1H5UbTxrC1bUutrN");
 
+// Must match the max_len of TeamData.match_history
+pub const MAX_MATCH_HISTORY: usize = 20;
+
 // Player in the team roster
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
 pub struct Player {
@@ -16,8 +26,7 @@ pub struct Player {
 // Strategy definition
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
 pub struct Strategy {
-    #[max_len(20)]
-    pub strategy_type: String,
+    pub strategy_type: StrategyType,
     #[max_len(100)]
     pub description: String,
 }
@@ -25,13 +34,13 @@ pub struct Strategy {
 // Match history entry
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
 pub struct MatchResult {
-    #[max_len(50)]
-    pub match_id: String,
+    pub match_key: Pubkey, // MatchState component of the match
     pub opponent: Pubkey,
     pub win: bool,
     pub timestamp: i64,
     pub team_score: u8,
     pub opponent_score: u8,
+    pub seed: [u8; 32], // Randomness seed the match was simulated with, for replays
 }
 
 #[component]
@@ -47,7 +56,13 @@ pub struct TeamData {
     #[max_len(20)]
     pub match_history: Vec<MatchResult>,
     pub rating: u16,
+    pub rating_deviation: u16, // Glicko-2 rating deviation
+    pub volatility: u32,       // Glicko-2 volatility in millionths
     pub active: bool, // New field to track if team is active
+    pub stake_amount: u64, // Lamports held in the team's stake vault
+    pub penalties: u64,    // Lamports of the stake forfeited to the treasury on disband
+    pub locked_matches: u8, // Matches with locked lineups that haven't finished
+    pub open_matches: u8,   // Scheduled matches that haven't finished
 }
 
 // Use regular impl without component_methods
@@ -56,13 +71,20 @@ impl TeamData {
         &mut self,
         name: String,
         owner: Pubkey,
+        stake_amount: u64,
     ) -> Result<()> {
-        require!(name.len() > 0, ComponentError::InvalidName);
+        require!(name.len() > 0, TeamError::InvalidName);
+        // A team holding a stake can't be taken over by initializing it again
+        require!(self.owner == Pubkey::default(), TeamError::AlreadyInitialized);
         
         self.name = name;
         self.owner = owner;
+        self.stake_amount = stake_amount;
+        self.penalties = 0;
         self.created_at = Clock::get()?.unix_timestamp;
-        self.rating = 1000; // Starting ELO rating
+        self.rating = rating::DEFAULT_RATING;
+        self.rating_deviation = rating::DEFAULT_RATING_DEVIATION;
+        self.volatility = rating::DEFAULT_VOLATILITY;
         self.active = true; // Team starts as active
         
         Ok(())
@@ -72,13 +94,14 @@ impl TeamData {
         &mut self,
         nft_mint: Pubkey,
         position: String,
+        max_roster_size: usize,
     ) -> Result<()> {
         // Validate roster size
-        require!(self.roster.len() < 5, ComponentError::RosterFull);
+        require!(self.roster.len() < max_roster_size.min(5), TeamError::RosterFull);
         
         // Check if player is already in the roster
         for player in self.roster.iter() {
-            require!(player.nft_mint != nft_mint, ComponentError::PlayerAlreadyInRoster);
+            require!(player.nft_mint != nft_mint, TeamError::PlayerAlreadyInRoster);
         }
         
         // Add player to roster
@@ -95,9 +118,12 @@ impl TeamData {
         &mut self,
         nft_mint: Pubkey,
     ) -> Result<()> {
+        // Locked lineups are played as locked, roster included
+        require!(self.locked_matches == 0, TeamError::MatchLocked);
+        
         // Find player index
         let player_index = self.roster.iter().position(|p| p.nft_mint == nft_mint);
-        require!(player_index.is_some(), ComponentError::PlayerNotFound);
+        require!(player_index.is_some(), TeamError::PlayerNotFound);
         
         // Remove player
         self.roster.remove(player_index.unwrap());
@@ -107,10 +133,11 @@ impl TeamData {
     
     pub fn set_strategy(
         &mut self,
-        strategy_type: String,
+        strategy_type: StrategyType,
         description: String,
     ) -> Result<()> {
-        require!(!strategy_type.is_empty(), ComponentError::InvalidStrategy);
+        require!(strategy_type != StrategyType::None, TeamError::InvalidStrategy);
+        require!(self.locked_matches == 0, TeamError::MatchLocked);
         
         self.strategy = Strategy {
             strategy_type,
@@ -122,56 +149,119 @@ impl TeamData {
     
     pub fn record_match_result(
         &mut self,
-        match_id: String,
+        match_key: Pubkey,
         opponent: Pubkey,
         win: bool,
         team_score: u8,
         opponent_score: u8,
+        seed: [u8; 32],
     ) -> Result<()> {
+        // Keep only the most recent matches so the history fits the account
+        if self.match_history.len() >= MAX_MATCH_HISTORY {
+            self.match_history.remove(0);
+        }
+        
         // Add to match history
         self.match_history.push(MatchResult {
-            match_id,
+            match_key,
             opponent,
             win,
             timestamp: Clock::get()?.unix_timestamp,
             team_score,
             opponent_score,
+            seed,
         });
         
-        // Update rating based on result
-        if win {
-            self.rating = self.rating.saturating_add(25);
-        } else {
-            self.rating = self.rating.saturating_sub(20);
-        }
-        
         Ok(())
     }
     
-    pub fn disband(&mut self) -> Result<()> {
+    // Update the ratings of both teams from one match. Both sides are rated against
+    // the other's pre-match values; returns the rating changes of (self, opponent).
+    pub fn update_ratings(
+        &mut self,
+        opponent: &mut TeamData,
+        win: bool,
+        model: RatingModel,
+    ) -> (RatingChange, RatingChange) {
+        let (new_self, new_opponent) = rating::rate_match(model, self.current_rating(), opponent.current_rating(), win);
+        
+        let self_delta = self.apply_rating(new_self);
+        let opponent_delta = opponent.apply_rating(new_opponent);
+        
+        (self_delta, opponent_delta)
+    }
+    
+    fn current_rating(&self) -> Rating {
+        Rating::from_stored(self.rating, self.rating_deviation, self.volatility)
+    }
+    
+    fn apply_rating(&mut self, new_rating: Rating) -> RatingChange {
+        let change = RatingChange {
+            rating: new_rating.stored_rating() as i32 - self.rating as i32,
+            deviation: new_rating.stored_deviation() as i32 - self.rating_deviation as i32,
+            volatility: new_rating.stored_volatility() as i64 - self.volatility as i64,
+        };
+        
+        self.rating = new_rating.stored_rating();
+        self.rating_deviation = new_rating.stored_deviation();
+        self.volatility = new_rating.stored_volatility();
+        
+        change
+    }
+    
+    // Take back a voided match: its history entry and the rating change it made. The change
+    // is subtracted rather than the old values restored, so matches played since still count.
+    pub fn revert_match(&mut self, match_key: &Pubkey, change: RatingChange) {
+        self.match_history.retain(|result| result.match_key != *match_key);
+        
+        self.rating = (self.rating as i32 - change.rating).clamp(0, u16::MAX as i32) as u16;
+        self.rating_deviation = (self.rating_deviation as i32 - change.deviation).clamp(0, u16::MAX as i32) as u16;
+        self.volatility = (self.volatility as i64 - change.volatility).clamp(0, u32::MAX as i64) as u32;
+    }
+    
+    // A match of this team was scheduled; the team can't disband until it ends
+    pub fn open_match(&mut self) {
+        self.open_matches += 1;
+    }
+    
+    // A scheduled match finished or was forfeited
+    pub fn close_match(&mut self) {
+        self.open_matches = self.open_matches.saturating_sub(1);
+    }
+    
+    // A match of this team locked its lineups; roster and strategy are frozen until it ends
+    pub fn lock_match(&mut self) {
+        self.locked_matches += 1;
+    }
+    
+    // A match with locked lineups finished or was forfeited
+    pub fn release_match(&mut self) {
+        self.locked_matches = self.locked_matches.saturating_sub(1);
+    }
+    
+    // Take a penalty out of the stake, capped at what is left of it
+    pub fn penalize(&mut self, amount: u64) -> u64 {
+        let penalty = amount.min(self.stake_amount - self.penalties);
+        self.penalties += penalty;
+        penalty
+    }
+    
+    // Returns the stake to refund to the owner and the penalties owed to the treasury
+    pub fn disband(&mut self) -> Result<(u64, u64)> {
+        require!(self.active, TeamError::TeamInactive);
+        // Disbanding must not get a team out of a match it could lose, or its penalty
+        require!(self.open_matches == 0, TeamError::OpenMatches);
+        
         // Clear the roster
         self.roster.clear();
         // Mark team as inactive
         self.active = false;
         
-        Ok(())
+        let refund = self.stake_amount - self.penalties;
+        let penalties = self.penalties;
+        self.stake_amount = 0;
+        self.penalties = 0;
+        
+        Ok((refund, penalties))
     }
 }
-
-#[error_code]
-pub enum ComponentError {
-    #[msg("Team name must not be empty")]
-    InvalidName,
-    
-    #[msg("Roster already has maximum number of players (5)")]
-    RosterFull,
-    
-    #[msg("Player already in roster")]
-    PlayerAlreadyInRoster,
-    
-    #[msg("Player not found in roster")]
-    PlayerNotFound,
-    
-    #[msg("Strategy type must not be empty")]
-    InvalidStrategy,
-}
\ No newline at end of file
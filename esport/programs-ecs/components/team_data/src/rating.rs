@@ -0,0 +1,4 @@
+// Rating models used to update TeamData after a match; shared with off-chain tools
+// through esport-types so simulated seasons rate teams exactly like the program.
+
+pub use esport_types::rating::*;
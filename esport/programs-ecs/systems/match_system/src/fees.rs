@@ -0,0 +1,97 @@
+use bolt_lang::*;
+use mpl_token_metadata::accounts::Metadata;
+use solana_program::program::{invoke, invoke_signed};
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+
+use crate::SystemError;
+
+pub const FEE_VAULT_SEED: &[u8] = b"fee_vault";
+
+// Lamport vault holding a match's entry fees: a system account at a match_system PDA
+pub fn fee_vault_address(match_key: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FEE_VAULT_SEED, match_key.as_ref()], &crate::ID)
+}
+
+fn check_accounts(vault: &AccountInfo, system_program: &AccountInfo, match_key: &Pubkey) -> Result<u8> {
+    let (address, bump) = fee_vault_address(match_key);
+    require_keys_eq!(vault.key(), address, SystemError::InvalidFeeVault);
+    require_keys_eq!(system_program.key(), solana_program::system_program::ID, SystemError::InvalidSystemProgram);
+    Ok(bump)
+}
+
+// Move `amount` lamports from a team owner into the match's fee vault
+pub fn deposit<'info>(
+    payer: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    match_key: &Pubkey,
+    amount: u64,
+) -> Result<()> {
+    check_accounts(vault, system_program, match_key)?;
+    if amount == 0 {
+        return Ok(());
+    }
+
+    invoke(
+        &system_instruction::transfer(payer.key, vault.key, amount),
+        &[payer.clone(), vault.clone(), system_program.clone()],
+    )?;
+
+    Ok(())
+}
+
+// Pay `amount` lamports out of the match's fee vault, signed with the vault seeds
+pub fn withdraw<'info>(
+    vault: &AccountInfo<'info>,
+    recipient: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    match_key: &Pubkey,
+    amount: u64,
+) -> Result<()> {
+    let bump = check_accounts(vault, system_program, match_key)?;
+    if amount == 0 {
+        return Ok(());
+    }
+
+    invoke_signed(
+        &system_instruction::transfer(vault.key, recipient.key, amount),
+        &[vault.clone(), recipient.clone(), system_program.clone()],
+        &[&[FEE_VAULT_SEED, match_key.as_ref(), &[bump]]],
+    )?;
+
+    Ok(())
+}
+
+// Whether `recipient` can take `amount`: a transfer must not leave it holding less than
+// the rent-exempt minimum for its data
+pub fn payable(recipient: &AccountInfo, amount: u64) -> Result<bool> {
+    Ok(recipient.lamports() + amount >= Rent::get()?.minimum_balance(recipient.data_len()))
+}
+
+// Split the creators' share of the fees over the lineup NFTs: every NFT gets an equal
+// part, divided between its metadata creators by their shares. Returns (creator, lamports)
+// in NFT order, then metadata creator order; rounding leftovers stay with the caller.
+pub fn creator_payouts(
+    metadata_accounts: &[AccountInfo],
+    lineup: &[Pubkey],
+    creator_pot: u64,
+) -> Result<Vec<(Pubkey, u64)>> {
+    require!(metadata_accounts.len() >= lineup.len(), SystemError::FeeAccountsNotProvided);
+    let per_nft = creator_pot / lineup.len() as u64;
+
+    let mut payouts = Vec::new();
+    for (metadata_account, mint) in metadata_accounts.iter().zip(lineup) {
+        require_keys_eq!(*metadata_account.owner, mpl_token_metadata::ID, SystemError::InvalidMetadata);
+        require_keys_eq!(metadata_account.key(), Metadata::find_pda(mint).0, SystemError::InvalidMetadata);
+
+        let data = metadata_account.try_borrow_data()?;
+        let metadata = Metadata::safe_deserialize(&data).map_err(|_| SystemError::InvalidMetadata)?;
+
+        for creator in metadata.creators.iter().flatten().filter(|c| c.share > 0) {
+            payouts.push((creator.address, per_nft * creator.share as u64 / 100));
+        }
+    }
+
+    Ok(payouts)
+}
@@ -1,166 +1,714 @@
 use bolt_lang::*;
 use anchor_lang::AnchorSerialize;
 use solana_program::pubkey::Pubkey;
+use esport::pause;
+use esport_types::{MatchAction, RatingChange, RatingModel};
+
+pub mod fees;
+pub mod randomness;
+pub mod series;
 
 declare_id!("ENQjWYERif38dvoXjqEJpzM2cYXgEq2VBzDeH1Rn2RAz");
 
 // Use the #[arguments] attribute instead of manual Serialize/Deserialize
 #[arguments]
 pub struct MatchSystemArgs {
-    pub action: String,
-    pub match_type: Option<String>,
-    pub match_id: Option<String>,
+    #[serde(flatten)]
+    pub action: MatchAction,
 }
 
-// Error codes
-#[error_code]
+// Error codes, from esport_types::errors::offsets::MATCH_SYSTEM
+#[error_code(offset = 6500)]
 pub enum SystemError {
-    #[msg("Invalid arguments format")]
-    InvalidArgs,
-    
-    #[msg("Unknown action")]
-    UnknownAction,
-    
     #[msg("Team doesn't have enough players")]
     InsufficientRoster,
     
     #[msg("Team hasn't selected a strategy")]
     NoStrategy,
     
-    #[msg("Match type not provided")]
-    MatchTypeNotProvided,
+    #[msg("Signer owns neither team")]
+    NotTeamOwner,
+    
+    #[msg("Both teams must reveal their seeds before the match")]
+    SeedsNotRevealed,
+    
+    #[msg("Instructions sysvar account missing or invalid")]
+    InvalidInstructionsSysvar,
+    
+    #[msg("Oracle signature instruction missing")]
+    MissingOracleSignature,
+    
+    #[msg("Oracle signature does not match this match")]
+    InvalidOracleSignature,
+    
+    #[msg("Player is not in the team's roster")]
+    PlayerNotInRoster,
+    
+    #[msg("Player appears twice in the lineup")]
+    DuplicatePlayer,
+    
+    #[msg("Match is not ready to be simulated")]
+    MatchNotReady,
+    
+    #[msg("Lineup token accounts not provided")]
+    TokenAccountsNotProvided,
+    
+    #[msg("Game config not provided")]
+    GameConfigNotProvided,
+    
+    #[msg("Fee accounts not provided")]
+    FeeAccountsNotProvided,
+    
+    #[msg("Fee vault does not belong to this match")]
+    InvalidFeeVault,
+    
+    #[msg("Invalid system program")]
+    InvalidSystemProgram,
     
-    #[msg("Match ID not provided")]
-    MatchIdNotProvided,
+    #[msg("Treasury account does not match the game config")]
+    InvalidTreasury,
+    
+    #[msg("Account is not the NFT's Metaplex metadata")]
+    InvalidMetadata,
+    
+    #[msg("Creator account does not match the NFT metadata")]
+    InvalidCreatorAccount,
+    
+    #[msg("Account is not the team owner")]
+    InvalidOwnerAccount,
+    
+    #[msg("Signer is neither team 1's owner nor the match operator")]
+    NotTeamOwnerOrOperator,
+    
+    #[msg("Only team 2's owner can consent to the match")]
+    NotOpponentOwner,
+    
+    #[msg("Team 2 has not consented to the match")]
+    ConsentNotGiven,
+    
+    #[msg("Team is not active")]
+    TeamInactive,
+    
+    #[msg("Signer is neither the config admin nor the match operator")]
+    NotAdminOrOperator,
+}
+
+// Check that a lineup is made of distinct, active players from the team's roster
+pub fn validate_lineup(team: &team_data::TeamData, lineup: &[Pubkey]) -> Result<()> {
+    for (index, nft_mint) in lineup.iter().enumerate() {
+        require!(!lineup[..index].contains(nft_mint), SystemError::DuplicatePlayer);
+        require!(
+            team.roster.iter().any(|player| player.active && player.nft_mint == *nft_mint),
+            SystemError::PlayerNotInRoster
+        );
+    }
+    
+    Ok(())
+}
+
+// Token accounts for both lineups come first after the game config, team 1 first
+pub const LINEUP_TOKEN_ACCOUNTS: usize = 10;
+
+// Check that each team owner still holds every NFT in their lineup, so sold players
+// can't keep playing. Token accounts are in lineup order, team 1 then team 2.
+pub fn verify_lineup_ownership(
+    team1: &team_data::TeamData,
+    team2: &team_data::TeamData,
+    team1_players: [&player_stats::PlayerStats; 5],
+    team2_players: [&player_stats::PlayerStats; 5],
+    token_accounts: &[AccountInfo],
+) -> Result<()> {
+    require!(token_accounts.len() >= LINEUP_TOKEN_ACCOUNTS, SystemError::TokenAccountsNotProvided);
+    
+    let players = team1_players.iter().map(|player| (player, &team1.owner))
+        .chain(team2_players.iter().map(|player| (player, &team2.owner)));
+    for ((player, owner), token_account) in players.zip(token_accounts) {
+        player.verify_owner(token_account, owner)?;
+    }
+    
+    Ok(())
+}
+
+// Disbanded teams can't take part in new matches or play the ones they had
+pub fn require_active(team1: &team_data::TeamData, team2: &team_data::TeamData) -> Result<()> {
+    require!(team1.active && team2.active, SystemError::TeamInactive);
+    Ok(())
+}
+
+// Side of the match owned by the signer
+pub fn owner_side(authority: &Pubkey, team1: &team_data::TeamData, team2: &team_data::TeamData) -> Result<match_state::Side> {
+    if team1.owner == *authority {
+        Ok(match_state::Side::Team1)
+    } else if team2.owner == *authority {
+        Ok(match_state::Side::Team2)
+    } else {
+        Err(SystemError::NotTeamOwner.into())
+    }
+}
+
+// Record a finished or forfeited match in both histories under the same match key and
+// rate both teams from their pre-match ratings. Returns the rating changes of (team1, team2).
+pub fn record_team_results(
+    team1: &mut team_data::TeamData,
+    team2: &mut team_data::TeamData,
+    match_key: Pubkey,
+    team1_key: Pubkey,
+    team2_key: Pubkey,
+    team1_score: u8,
+    team2_score: u8,
+    seed: [u8; 32],
+    model: RatingModel,
+) -> Result<(RatingChange, RatingChange)> {
+    let team1_won = team1_score > team2_score;
+    
+    team1.record_match_result(match_key, team2_key, team1_won, team1_score, team2_score, seed)?;
+    team2.record_match_result(match_key, team1_key, !team1_won, team2_score, team1_score, seed)?;
+    
+    Ok(team1.update_ratings(team2, team1_won, model))
+}
+
+// GameConfig pause bit of a match action
+fn pause_bit(action: &MatchAction) -> u64 {
+    match action {
+        MatchAction::ScheduleMatch { .. } => pause::SCHEDULE_MATCH,
+        MatchAction::PayEntryFee
+        | MatchAction::LockLineups
+        | MatchAction::ConsentMatch
+        | MatchAction::CommitSeed { .. }
+        | MatchAction::RevealSeed { .. } => pause::PREPARE_MATCH,
+        MatchAction::SimulateMatch => pause::SIMULATE_MATCH,
+        MatchAction::Forfeit
+        | MatchAction::ClaimForfeit
+        | MatchAction::DisputeMatch
+        | MatchAction::ResolveDispute { .. } => pause::FORFEIT_MATCH,
+        MatchAction::SettleFees => pause::SETTLE_FEES,
+    }
 }
 
 /*
  * System implementation
+ *
+ * A match is followed through its MatchState component:
+ *   scheduleMatch - either owner turns an accepted queue entry into a scheduled match and picks
+ *                   where its randomness comes from: commit-reveal seeds or the oracle
+ *   payEntryFee   - each owner pays the entry fee into the match fee vault
+ *   lockLineups   - either owner locks both five-player lineups once both fees are paid; the
+ *                   strategies, positions and attributes are snapshotted and both teams'
+ *                   rosters and strategies stay frozen until the match ends
+ *   consentMatch  - team 2's owner agrees to play the locked lineups
+ *   commitSeed    - each owner of a commit-reveal match commits sha256(seed || owner); the
+ *                   match starts once both did
+ *   revealSeed    - each owner reveals their seed
+ *   simulateMatch - team 1's owner or the config's match operator plays the locked snapshot
+ *                   from the commit-reveal or oracle seed, once team 2 consented
+ *   forfeit       - an owner concedes a match that hasn't finished
+ *   claimForfeit  - once a step's deadline passed, an owner who consented, committed or
+ *                   revealed in time wins against one who didn't; if neither did, the match
+ *                   is voided and its fees refunded. Team 2 also wins a match team 1 didn't
+ *                   simulate in time once the seed was known.
+ *   disputeMatch  - an owner flags a finished match for an off-chain replay from its seed
+ *   resolveDispute - the admin or match operator lets the result stand, or voids the match
+ *                    so its entry fees are refunded and its ratings and records taken back
+ *   settleFees    - anyone pays out the fees of a finished or forfeited match: the creator share
+ *                   to the creators of the ten NFTs, the rest of the vault to the treasury. Fees of a voided
+ *                   match, or one forfeited before lineups were locked, go back to the owners
+ *                   who paid them.
  */
 #[system]
 pub mod match_system {
-    use anchor_lang::prelude::msg;
+    use anchor_lang::prelude::emit;
     use solana_program::pubkey::Pubkey;
     
     // Import components explicitly
-    use team_data::TeamData;
+    use team_data::{StrategyType, TeamData};
     use player_stats::PlayerStats;
+    use match_queue::MatchQueue;
+    use match_state::{MatchState, Side};
+    use esport::GameConfig;
+    use esport_types::{MatchAction, MatchStatus, MatchType, RandomnessSource, RatingModel};
+    use esport_types::events::{
+        DisputeResolved, EntryFeePaid, FeesSettled, LineupsLocked, MatchConsented, MatchDisputed, MatchForfeited,
+        MatchScheduled, MatchSimulated, MatchVoided, SeedCommitted, SeedRevealed,
+    };
     
-    use crate::{SystemError, MatchSystemArgs};
+    use crate::{SystemError, MatchSystemArgs, validate_lineup, verify_lineup_ownership, owner_side, record_team_results};
+    use crate::require_active;
+    use crate::{pause_bit, LINEUP_TOKEN_ACCOUNTS};
+    use crate::fees;
+    use crate::randomness;
+    use crate::series;
     
     pub fn execute(ctx: Context<Components>, args: MatchSystemArgs) -> Result<Components> {
+        let authority = ctx.accounts.authority.key();
+        let match_key = ctx.accounts.match_state.key();
+        let team1_key = ctx.accounts.team1_data.key();
+        let team2_key = ctx.accounts.team2_data.key();
+        let side_key = |side: Side| match side {
+            Side::Team1 => team1_key,
+            Side::Team2 => team2_key,
+        };
+        
+        // The game config is always the first extra account; action-specific accounts follow it
+        let (config, extra_accounts) = GameConfig::from_remaining(ctx.remaining_accounts)?;
+        config.require_unpaused(pause_bit(&args.action))?;
+        
         // Process instructions based on action
-        match args.action.as_str() {
-            "scheduleMatch" => {
-                let match_type = args.match_type.ok_or(SystemError::MatchTypeNotProvided)?;
+        match args.action {
+            MatchAction::ScheduleMatch { pending_match_id, randomness: source } => {
+                owner_side(&authority, &ctx.accounts.team1_data, &ctx.accounts.team2_data)?;
+                require_active(&ctx.accounts.team1_data, &ctx.accounts.team2_data)?;
                 
-                // Get team data directly
-                let team1_data = &ctx.accounts.team1_data;
-                let team2_data = &ctx.accounts.team2_data;
+                // Only matches the opponent accepted through the queue can be played;
+                // the queue entry is consumed so it can't be scheduled twice
+                let pending = ctx.accounts.match_queue.playable(pending_match_id, team1_key, team2_key)?;
+                let match_type = pending.match_type.clone();
+                ctx.accounts.match_queue.complete(pending_match_id)?;
                 
-                // Validate teams have enough players
-                require!(team1_data.roster.len() > 0, SystemError::InsufficientRoster);
-                require!(team2_data.roster.len() > 0, SystemError::InsufficientRoster);
+                // The entry fee and the randomness source are fixed when the match is scheduled
+                ctx.accounts.match_state.schedule(
+                    pending_match_id,
+                    team1_key,
+                    team2_key,
+                    match_type.clone(),
+                    config.entry_fee,
+                    source,
+                )?;
                 
-                // Validate team has selected a strategy
-                require!(!team1_data.strategy.strategy_type.is_empty(), SystemError::NoStrategy);
+                // Neither team can disband until the match is played or forfeited
+                ctx.accounts.team1_data.open_match();
+                ctx.accounts.team2_data.open_match();
                 
-                // Log match scheduling
-                msg!("Match scheduled: {} vs {} ({})", team1_data.name, team2_data.name, match_type);
+                emit!(MatchScheduled {
+                    match_key,
+                    team1: team1_key,
+                    team2: team2_key,
+                    pending_match_id,
+                    match_type,
+                    entry_fee: config.entry_fee,
+                });
             },
-            "simulateMatch" => {
-                let match_id = args.match_id.ok_or(SystemError::MatchIdNotProvided)?;
+            MatchAction::PayEntryFee => {
+                let side = owner_side(&authority, &ctx.accounts.team1_data, &ctx.accounts.team2_data)?;
+                ctx.accounts.match_state.require_teams(team1_key, team2_key)?;
                 
-                // Get team data for modification
-                let team_data = &mut ctx.accounts.team1_data;
+                let amount = ctx.accounts.match_state.pay_entry_fee(side)?;
                 
-                // Access player stats directly - no need for Option handling now
-                let team1_players = [
-                    &ctx.accounts.team1_player1, 
-                    &ctx.accounts.team1_player2, 
-                    &ctx.accounts.team1_player3, 
-                    &ctx.accounts.team1_player4, 
-                    &ctx.accounts.team1_player5
+                // Extra accounts: the match fee vault, the system program and the instructions sysvar
+                let [fee_vault, system_program, instructions_sysvar, ..] = extra_accounts else {
+                    return Err(SystemError::FeeAccountsNotProvided.into());
+                };
+                esport::require_world_apply(instructions_sysvar)?;
+                fees::deposit(&ctx.accounts.authority.to_account_info(), fee_vault, system_program, &match_key, amount)?;
+                
+                emit!(EntryFeePaid { match_key, team: side_key(side), amount });
+            },
+            MatchAction::LockLineups => {
+                owner_side(&authority, &ctx.accounts.team1_data, &ctx.accounts.team2_data)?;
+                ctx.accounts.match_state.require_teams(team1_key, team2_key)?;
+                require_active(&ctx.accounts.team1_data, &ctx.accounts.team2_data)?;
+                
+                // Both lineups must be five distinct players from their own team's roster
+                let team1_lineup = [
+                    ctx.accounts.team1_player1.nft_mint,
+                    ctx.accounts.team1_player2.nft_mint,
+                    ctx.accounts.team1_player3.nft_mint,
+                    ctx.accounts.team1_player4.nft_mint,
+                    ctx.accounts.team1_player5.nft_mint,
                 ];
+                let team2_lineup = [
+                    ctx.accounts.team2_player1.nft_mint,
+                    ctx.accounts.team2_player2.nft_mint,
+                    ctx.accounts.team2_player3.nft_mint,
+                    ctx.accounts.team2_player4.nft_mint,
+                    ctx.accounts.team2_player5.nft_mint,
+                ];
+                validate_lineup(&ctx.accounts.team1_data, &team1_lineup)?;
+                validate_lineup(&ctx.accounts.team2_data, &team2_lineup)?;
+                require!(ctx.accounts.team1_data.strategy.strategy_type != StrategyType::None, SystemError::NoStrategy);
+                require!(ctx.accounts.team2_data.strategy.strategy_type != StrategyType::None, SystemError::NoStrategy);
                 
+                let team1_players = [
+                    &*ctx.accounts.team1_player1,
+                    &*ctx.accounts.team1_player2,
+                    &*ctx.accounts.team1_player3,
+                    &*ctx.accounts.team1_player4,
+                    &*ctx.accounts.team1_player5,
+                ];
                 let team2_players = [
-                    &ctx.accounts.team2_player1, 
-                    &ctx.accounts.team2_player2, 
-                    &ctx.accounts.team2_player3, 
-                    &ctx.accounts.team2_player4, 
-                    &ctx.accounts.team2_player5
+                    &*ctx.accounts.team2_player1,
+                    &*ctx.accounts.team2_player2,
+                    &*ctx.accounts.team2_player3,
+                    &*ctx.accounts.team2_player4,
+                    &*ctx.accounts.team2_player5,
                 ];
+                verify_lineup_ownership(
+                    &ctx.accounts.team1_data,
+                    &ctx.accounts.team2_data,
+                    team1_players,
+                    team2_players,
+                    extra_accounts,
+                )?;
                 
-                // Simulation logic
-                let mut team1_strength = 0;
-                let mut team2_strength = 0;
-                
-                // Calculate team1 strength
-                for player in team1_players.iter() {
-                    // Use player attributes to calculate match contribution
-                    let player_contribution = 
-                        player.mechanical as u32 +
-                        player.game_knowledge as u32 +
-                        player.team_communication as u32 + 
-                        player.form as u32;
-                    
-                    team1_strength += player_contribution;
-                    
-                    // Log for debug purposes
-                    msg!("Team 1 player {} contributing strength: {}", player.role, player_contribution);
-                }
+                // The match is played from this snapshot; both teams' rosters and
+                // strategies stay frozen until it ends
+                let team1_locked = series::lock_lineup(&ctx.accounts.team1_data, team1_players);
+                let team2_locked = series::lock_lineup(&ctx.accounts.team2_data, team2_players);
                 
-                // Calculate team2 strength
-                for player in team2_players.iter() {
-                    // Use player attributes to calculate match contribution
-                    let player_contribution = 
-                        player.mechanical as u32 +
-                        player.game_knowledge as u32 +
-                        player.team_communication as u32 + 
-                        player.form as u32;
-                    
-                    team2_strength += player_contribution;
-                    
-                    // Log for debug purposes
-                    msg!("Team 2 player {} contributing strength: {}", player.role, player_contribution);
-                }
+                let accounts = &mut ctx.accounts;
+                accounts.match_state.lock_lineups(
+                    team1_lineup,
+                    team2_lineup,
+                    team1_locked,
+                    team2_locked,
+                    config.reveal_timeout,
+                )?;
+                accounts.team1_data.lock_match();
+                accounts.team2_data.lock_match();
+                
+                emit!(LineupsLocked { match_key, team1_lineup, team2_lineup });
+            },
+            MatchAction::ConsentMatch => {
+                let side = owner_side(&authority, &ctx.accounts.team1_data, &ctx.accounts.team2_data)?;
+                require!(side == Side::Team2, SystemError::NotOpponentOwner);
+                ctx.accounts.match_state.require_teams(team1_key, team2_key)?;
+                
+                ctx.accounts.match_state.consent(config.reveal_timeout)?;
+                
+                emit!(MatchConsented { match_key, team: team2_key });
+            },
+            MatchAction::CommitSeed { seed_commitment: commitment } => {
+                let side = owner_side(&authority, &ctx.accounts.team1_data, &ctx.accounts.team2_data)?;
+                ctx.accounts.match_state.require_teams(team1_key, team2_key)?;
+                
+                ctx.accounts.match_state.commit_seed(side, commitment)?;
+                
+                emit!(SeedCommitted { match_key, team: side_key(side) });
+            },
+            MatchAction::RevealSeed { seed } => {
+                let side = owner_side(&authority, &ctx.accounts.team1_data, &ctx.accounts.team2_data)?;
+                ctx.accounts.match_state.require_teams(team1_key, team2_key)?;
                 
-                // Add randomness factor (still influenced by team strength)
-                let clock = Clock::get()?;
-                let random_factor = (clock.unix_timestamp % 100) as u32;
+                ctx.accounts.match_state.reveal_seed(side, seed, &authority, config.reveal_timeout)?;
                 
-                // Determine match winner with weighted randomness
-                let team1_final = team1_strength + (random_factor % 50);
-                let team2_final = team2_strength + ((100 - random_factor) % 50);
+                emit!(SeedRevealed { match_key, team: side_key(side) });
+            },
+            MatchAction::SimulateMatch => {
+                
+                // The simulation writes both teams' histories and ratings, so it needs team 1's
+                // owner (or the operator's crank) to run it and team 2's recorded consent
+                require!(
+                    authority == ctx.accounts.team1_data.owner || config.is_match_operator(&authority),
+                    SystemError::NotTeamOwnerOrOperator
+                );
+                
+                let match_state = &ctx.accounts.match_state;
+                match_state.require_teams(team1_key, team2_key)?;
+                require_active(&ctx.accounts.team1_data, &ctx.accounts.team2_data)?;
+                require!(match_state.ready_to_simulate(), SystemError::MatchNotReady);
+                require!(match_state.team2_consented, SystemError::ConsentNotGiven);
+                
+                // Lineups must be the ones that were locked
+                let team1_lineup = [
+                    ctx.accounts.team1_player1.nft_mint,
+                    ctx.accounts.team1_player2.nft_mint,
+                    ctx.accounts.team1_player3.nft_mint,
+                    ctx.accounts.team1_player4.nft_mint,
+                    ctx.accounts.team1_player5.nft_mint,
+                ];
+                let team2_lineup = [
+                    ctx.accounts.team2_player1.nft_mint,
+                    ctx.accounts.team2_player2.nft_mint,
+                    ctx.accounts.team2_player3.nft_mint,
+                    ctx.accounts.team2_player4.nft_mint,
+                    ctx.accounts.team2_player5.nft_mint,
+                ];
+                match_state.require_lineups(&team1_lineup, &team2_lineup)?;
                 
-                let win = team1_final > team2_final;
+                // Derive the match seed; it is recorded with the result so the match can be replayed
+                let seed = match match_state.randomness {
+                    RandomnessSource::CommitReveal => {
+                        let (team1_seed, team2_seed) = match_state.revealed_seeds()
+                            .ok_or(SystemError::SeedsNotRevealed)?;
+                        randomness::commit_reveal_seed(&match_key, &team1_seed, &team2_seed)
+                    },
+                    RandomnessSource::Vrf => {
+                        let instructions_sysvar = extra_accounts.get(LINEUP_TOKEN_ACCOUNTS)
+                            .ok_or(SystemError::InvalidInstructionsSysvar)?;
+                        randomness::vrf_seed(instructions_sysvar, &config.randomness_oracle, &match_key, match_state)?
+                    },
+                };
                 
-                // Record match result
-                team_data.record_match_result(
-                    match_id,
-                    ctx.accounts.team2_data.key(),
-                    win,
-                    if win { 3 } else { 1 },
-                    if win { 1 } else { 3 },
+                // Owners may have sold players since the lineups were locked
+                verify_lineup_ownership(
+                    &ctx.accounts.team1_data,
+                    &ctx.accounts.team2_data,
+                    [
+                        &ctx.accounts.team1_player1,
+                        &ctx.accounts.team1_player2,
+                        &ctx.accounts.team1_player3,
+                        &ctx.accounts.team1_player4,
+                        &ctx.accounts.team1_player5,
+                    ],
+                    [
+                        &ctx.accounts.team2_player1,
+                        &ctx.accounts.team2_player2,
+                        &ctx.accounts.team2_player3,
+                        &ctx.accounts.team2_player4,
+                        &ctx.accounts.team2_player5,
+                    ],
+                    extra_accounts,
                 )?;
                 
-                // Update player stats based on match result
-                // Note: In a real implementation, you would need to make these mutable
-                // and update them. For now, we're just logging what would happen.
-                for player in team1_players.iter() {
-                    msg!("Player {} would record match result: {}", player.role, win);
-                    // In a real implementation with mutable access:
-                    // player.record_match_result(win)?;
+                // The engine plays the whole series from the seed and the lineups as they
+                // were locked, so it replays off-chain from the match state alone
+                let format = MatchType::parse(&match_state.match_type);
+                let team1 = series::to_simulation(&match_state.team1_locked);
+                let team2 = series::to_simulation(&match_state.team2_locked);
+                let result = esport_simulation::simulate(format, &team1, &team2, &seed);
+                let games = series::game_logs(&result, &team1_lineup, &team2_lineup);
+                let (team1_score, team2_score) = (result.team1_wins, result.team2_wins);
+                let win = result.team1_won();
+                
+                let accounts = &mut ctx.accounts;
+                
+                accounts.match_state.finish(seed, team1_score, team2_score, games)?;
+                accounts.team1_data.release_match();
+                accounts.team2_data.release_match();
+                accounts.team1_data.close_match();
+                accounts.team2_data.close_match();
+                let (team1_change, team2_change) = record_team_results(
+                    &mut accounts.team1_data,
+                    &mut accounts.team2_data,
+                    match_key,
+                    team1_key,
+                    team2_key,
+                    team1_score,
+                    team2_score,
+                    seed,
+                    RatingModel::from_k_factor(config.elo_k_factor),
+                )?;
+                accounts.match_state.team1_rating_change = team1_change;
+                accounts.match_state.team2_rating_change = team2_change;
+                
+                // Update every participating player
+                for player in [
+                    &mut accounts.team1_player1,
+                    &mut accounts.team1_player2,
+                    &mut accounts.team1_player3,
+                    &mut accounts.team1_player4,
+                    &mut accounts.team1_player5,
+                ] {
+                    player.record_match_result(win)?;
+                }
+                for player in [
+                    &mut accounts.team2_player1,
+                    &mut accounts.team2_player2,
+                    &mut accounts.team2_player3,
+                    &mut accounts.team2_player4,
+                    &mut accounts.team2_player5,
+                ] {
+                    player.record_match_result(!win)?;
+                }
+                
+                emit!(MatchSimulated {
+                    match_key,
+                    team1: team1_key,
+                    team2: team2_key,
+                    team1_score,
+                    team2_score,
+                    seed,
+                    team1_rating: accounts.team1_data.rating,
+                    team2_rating: accounts.team2_data.rating,
+                    team1_rating_delta: team1_change.rating,
+                    team2_rating_delta: team2_change.rating,
+                });
+            },
+            action @ (MatchAction::Forfeit | MatchAction::ClaimForfeit) => {
+                let side = owner_side(&authority, &ctx.accounts.team1_data, &ctx.accounts.team2_data)?;
+                ctx.accounts.match_state.require_teams(team1_key, team2_key)?;
+                
+                if action == MatchAction::Forfeit {
+                    ctx.accounts.match_state.forfeit(side)?;
+                } else {
+                    ctx.accounts.match_state.claim_forfeit(side, config.reveal_timeout)?;
+                }
+                
+                let accounts = &mut ctx.accounts;
+                if accounts.match_state.lineups_locked() {
+                    accounts.team1_data.release_match();
+                    accounts.team2_data.release_match();
                 }
+                accounts.team1_data.close_match();
+                accounts.team2_data.close_match();
+                
+                // Neither side did its part in time: nobody wins and the fees are refunded
+                if accounts.match_state.status == MatchStatus::Voided {
+                    emit!(MatchVoided { match_key });
+                    return Ok(ctx.accounts);
+                }
+                
+                // A forfeit counts as a 1-0 win for the other side
+                let team1_won = accounts.match_state.winner == team1_key;
+                let (team1_score, team2_score) = if team1_won { (1, 0) } else { (0, 1) };
+                accounts.match_state.team1_score = team1_score;
+                accounts.match_state.team2_score = team2_score;
+                let (team1_change, team2_change) = record_team_results(
+                    &mut accounts.team1_data,
+                    &mut accounts.team2_data,
+                    match_key,
+                    team1_key,
+                    team2_key,
+                    team1_score,
+                    team2_score,
+                    [0; 32],
+                    RatingModel::from_k_factor(config.elo_k_factor),
+                )?;
+                
+                // The forfeiting team loses part of its stake, paid to the treasury on disband
+                let loser = if team1_won { &mut accounts.team2_data } else { &mut accounts.team1_data };
+                let penalty = loser.penalize(config.forfeit_penalty(loser.stake_amount));
+                
+                emit!(MatchForfeited {
+                    match_key,
+                    team1: team1_key,
+                    team2: team2_key,
+                    winner: accounts.match_state.winner,
+                    penalty,
+                    team1_rating: accounts.team1_data.rating,
+                    team2_rating: accounts.team2_data.rating,
+                    team1_rating_delta: team1_change.rating,
+                    team2_rating_delta: team2_change.rating,
+                });
+            },
+            MatchAction::DisputeMatch => {
+                let side = owner_side(&authority, &ctx.accounts.team1_data, &ctx.accounts.team2_data)?;
+                ctx.accounts.match_state.require_teams(team1_key, team2_key)?;
                 
-                msg!("Match simulated: {} {} with score {}-{}", 
-                    team_data.name, 
-                    if win { "won" } else { "lost" },
-                    if win { 3 } else { 1 },
-                    if win { 1 } else { 3 }
+                ctx.accounts.match_state.dispute()?;
+                
+                emit!(MatchDisputed { match_key, team: side_key(side) });
+            },
+            MatchAction::ResolveDispute { refund } => {
+                require!(
+                    authority == config.admin || config.is_match_operator(&authority),
+                    SystemError::NotAdminOrOperator
                 );
+                ctx.accounts.match_state.require_teams(team1_key, team2_key)?;
+                
+                ctx.accounts.match_state.resolve_dispute(refund)?;
+                
+                // A voided result never counted: take back both teams' rating changes and
+                // history entries and the lineup players' records
+                if refund {
+                    let accounts = &mut ctx.accounts;
+                    let team1_lineup = [
+                        accounts.team1_player1.nft_mint,
+                        accounts.team1_player2.nft_mint,
+                        accounts.team1_player3.nft_mint,
+                        accounts.team1_player4.nft_mint,
+                        accounts.team1_player5.nft_mint,
+                    ];
+                    let team2_lineup = [
+                        accounts.team2_player1.nft_mint,
+                        accounts.team2_player2.nft_mint,
+                        accounts.team2_player3.nft_mint,
+                        accounts.team2_player4.nft_mint,
+                        accounts.team2_player5.nft_mint,
+                    ];
+                    accounts.match_state.require_lineups(&team1_lineup, &team2_lineup)?;
+                    
+                    let match_state = &accounts.match_state;
+                    let win = match_state.team1_score > match_state.team2_score;
+                    accounts.team1_data.revert_match(&match_key, match_state.team1_rating_change);
+                    accounts.team2_data.revert_match(&match_key, match_state.team2_rating_change);
+                    for player in [
+                        &mut accounts.team1_player1,
+                        &mut accounts.team1_player2,
+                        &mut accounts.team1_player3,
+                        &mut accounts.team1_player4,
+                        &mut accounts.team1_player5,
+                    ] {
+                        player.revert_match_result(win);
+                    }
+                    for player in [
+                        &mut accounts.team2_player1,
+                        &mut accounts.team2_player2,
+                        &mut accounts.team2_player3,
+                        &mut accounts.team2_player4,
+                        &mut accounts.team2_player5,
+                    ] {
+                        player.revert_match_result(!win);
+                    }
+                }
+                
+                emit!(DisputeResolved { match_key, refund });
+            },
+            MatchAction::SettleFees => {
+                ctx.accounts.match_state.require_teams(team1_key, team2_key)?;
+                ctx.accounts.match_state.settle_fees()?;
+                
+                // Extra accounts: the match fee vault, the system program, the treasury and the
+                // instructions sysvar, followed by the payees
+                let [fee_vault, system_program, treasury, instructions_sysvar, payees @ ..] = extra_accounts else {
+                    return Err(SystemError::FeeAccountsNotProvided.into());
+                };
+                esport::require_world_apply(instructions_sysvar)?;
+                require_keys_eq!(treasury.key(), config.treasury, SystemError::InvalidTreasury);
+                
+                let match_state = &ctx.accounts.match_state;
+                if match_state.refunds_fees() {
+                    // Payees: both team owners, who get back what they paid
+                    let [team1_owner, team2_owner, ..] = payees else {
+                        return Err(SystemError::FeeAccountsNotProvided.into());
+                    };
+                    require_keys_eq!(team1_owner.key(), ctx.accounts.team1_data.owner, SystemError::InvalidOwnerAccount);
+                    require_keys_eq!(team2_owner.key(), ctx.accounts.team2_data.owner, SystemError::InvalidOwnerAccount);
+                    
+                    let mut refunded = 0;
+                    for (side, owner) in [(Side::Team1, team1_owner), (Side::Team2, team2_owner)] {
+                        let paid = match side {
+                            Side::Team1 => match_state.team1_fee_paid,
+                            Side::Team2 => match_state.team2_fee_paid,
+                        };
+                        if paid {
+                            fees::withdraw(fee_vault, owner, system_program, &match_key, match_state.entry_fee)?;
+                            refunded += match_state.entry_fee;
+                        }
+                    }
+                    
+                    emit!(FeesSettled { match_key, to_creators: 0, to_treasury: 0, refunded });
+                } else {
+                    // Payees: the metadata accounts of the ten lineup NFTs, team 1 first, then
+                    // every creator listed in those metadata accounts, in order
+                    let pot = match_state.entry_fee * 2;
+                    let lineup: Vec<Pubkey> = match_state.team1_lineup.iter()
+                        .chain(match_state.team2_lineup.iter())
+                        .copied()
+                        .collect();
+                    let (metadata_accounts, creator_accounts) = payees.split_at(payees.len().min(lineup.len()));
+                    
+                    let payouts = fees::creator_payouts(metadata_accounts, &lineup, config.creator_share(pot))?;
+                    require!(creator_accounts.len() >= payouts.len(), SystemError::FeeAccountsNotProvided);
+                    
+                    // A cut too small to leave an empty creator account rent exempt could never be
+                    // paid and would block the settlement, so it stays in the vault instead
+                    let mut paid_to_creators = 0;
+                    for ((creator, amount), creator_account) in payouts.iter().zip(creator_accounts) {
+                        require_keys_eq!(creator_account.key(), *creator, SystemError::InvalidCreatorAccount);
+                        if fees::payable(creator_account, *amount)? {
+                            fees::withdraw(fee_vault, creator_account, system_program, &match_key, *amount)?;
+                            paid_to_creators += amount;
+                        }
+                    }
+                    
+                    // The treasury sweeps the vault: the platform share, plus rounding, NFTs
+                    // without creators and unpayable creator cuts
+                    let to_treasury = fee_vault.lamports();
+                    fees::withdraw(fee_vault, treasury, system_program, &match_key, to_treasury)?;
+                    
+                    emit!(FeesSettled { match_key, to_creators: paid_to_creators, to_treasury, refunded: 0 });
+                }
             },
-            _ => return Err(SystemError::UnknownAction.into())
         }
         
         Ok(ctx.accounts)
@@ -169,6 +717,8 @@ pub mod match_system {
     // Define Components struct with non-optional PlayerStats fields
     #[system_input]
     pub struct Components {
+        pub match_queue: MatchQueue,
+        pub match_state: MatchState,
         pub team1_data: TeamData,
         pub team2_data: TeamData,
         // Team 1 players - all required now
@@ -0,0 +1,103 @@
+use bolt_lang::*;
+use solana_program::ed25519_program;
+use solana_program::hash::hashv;
+use solana_program::pubkey::Pubkey;
+use solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use anchor_lang::AnchorSerialize;
+use match_state::MatchState;
+
+use crate::SystemError;
+
+// Domain separators so a seed or signature can never be reused for another purpose
+const MATCH_SEED_DOMAIN: &[u8] = b"5vs5dotgg:match-seed";
+const VRF_MESSAGE_DOMAIN: &[u8] = b"5vs5dotgg:vrf";
+
+// Layout of the Ed25519 precompile instruction data
+const ED25519_HEADER_LEN: usize = 2;
+const ED25519_OFFSETS_LEN: usize = 14;
+const ED25519_PUBKEY_LEN: usize = 32;
+const ED25519_SIGNATURE_LEN: usize = 64;
+
+// Combine both revealed team seeds into the match seed
+pub fn commit_reveal_seed(match_key: &Pubkey, team1_seed: &[u8; 32], team2_seed: &[u8; 32]) -> [u8; 32] {
+    hashv(&[MATCH_SEED_DOMAIN, match_key.as_ref(), team1_seed, team2_seed]).to_bytes()
+}
+
+// Message the oracle has to sign for a match: the match and its teams, when the lineups
+// were locked and a hash of the locked lineups, so a signature only fits the lineups it
+// was made for. Only defined once the lineups are locked.
+pub fn vrf_message(match_key: &Pubkey, state: &MatchState) -> Vec<u8> {
+    let lineups = (state.team1_lineup, state.team2_lineup, state.team1_locked, state.team2_locked)
+        .try_to_vec()
+        .expect("lineups serialize");
+    [
+        VRF_MESSAGE_DOMAIN,
+        match_key.as_ref(),
+        state.team1.as_ref(),
+        state.team2.as_ref(),
+        &state.locked_at.to_le_bytes(),
+        &hashv(&[&lineups]).to_bytes(),
+    ]
+    .concat()
+}
+
+// Derive the match seed from an oracle signature verified by the Ed25519 precompile.
+//
+// The precompile instruction must directly precede the world `apply` instruction and carry
+// exactly one signature by `oracle` (the game config's randomness oracle) over `vrf_message`. Ed25519 signatures are
+// deterministic, so the oracle cannot grind for a favourable outcome.
+pub fn vrf_seed(
+    instructions_sysvar: &AccountInfo,
+    oracle: &Pubkey,
+    match_key: &Pubkey,
+    state: &MatchState,
+) -> Result<[u8; 32]> {
+    require!(state.lineups_locked(), SystemError::MatchNotReady);
+    require_keys_eq!(
+        *instructions_sysvar.key,
+        solana_program::sysvar::instructions::ID,
+        SystemError::InvalidInstructionsSysvar
+    );
+
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, SystemError::MissingOracleSignature);
+    let ed25519_ix = load_instruction_at_checked((current_index - 1) as usize, instructions_sysvar)?;
+    require_keys_eq!(ed25519_ix.program_id, ed25519_program::ID, SystemError::MissingOracleSignature);
+
+    let data = &ed25519_ix.data;
+    require!(
+        data.len() >= ED25519_HEADER_LEN + ED25519_OFFSETS_LEN && data[0] == 1,
+        SystemError::InvalidOracleSignature
+    );
+
+    // Offsets struct: signature, signature ix, pubkey, pubkey ix, message, message size, message ix
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let offsets = ED25519_HEADER_LEN;
+    let signature_offset = read_u16(offsets) as usize;
+    let public_key_offset = read_u16(offsets + 4) as usize;
+    let message_offset = read_u16(offsets + 8) as usize;
+    let message_size = read_u16(offsets + 10) as usize;
+
+    // All parts must live in the precompile instruction itself
+    for index_at in [offsets + 2, offsets + 6, offsets + 12] {
+        require!(read_u16(index_at) == u16::MAX, SystemError::InvalidOracleSignature);
+    }
+
+    let public_key = data
+        .get(public_key_offset..public_key_offset + ED25519_PUBKEY_LEN)
+        .ok_or(SystemError::InvalidOracleSignature)?;
+    let signature = data
+        .get(signature_offset..signature_offset + ED25519_SIGNATURE_LEN)
+        .ok_or(SystemError::InvalidOracleSignature)?;
+    let message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(SystemError::InvalidOracleSignature)?;
+
+    require!(public_key == oracle.as_ref(), SystemError::InvalidOracleSignature);
+    require!(
+        message == vrf_message(match_key, state).as_slice(),
+        SystemError::InvalidOracleSignature
+    );
+
+    Ok(hashv(&[MATCH_SEED_DOMAIN, match_key.as_ref(), signature]).to_bytes())
+}
@@ -0,0 +1,80 @@
+// Bridge between the components and the esport-simulation match engine
+use esport_simulation::{self as simulation, SeriesResult};
+use match_state::{GameLog, LockedLineup, LockedPlayer};
+use player_stats::{PlayerStats, Role};
+use solana_program::pubkey::Pubkey;
+use team_data::TeamData;
+
+// Snapshot of a team taken when lineups are locked: the team's strategy and, per player,
+// their own role, the position the team owner slotted them in and their attributes
+pub fn lock_lineup(team: &TeamData, players: [&PlayerStats; 5]) -> LockedLineup {
+    LockedLineup {
+        strategy: team.strategy.strategy_type,
+        players: players.map(|player| LockedPlayer {
+            role: player.role(),
+            position: roster_position(team, player),
+            attributes: [
+                player.mechanical,
+                player.game_knowledge,
+                player.team_communication,
+                player.adaptability,
+                player.consistency,
+                player.form,
+                player.potential,
+            ],
+        }),
+    }
+}
+
+// Engine input for a locked lineup
+pub fn to_simulation(lineup: &LockedLineup) -> simulation::Lineup {
+    simulation::Lineup {
+        strategy: lineup.strategy,
+        players: lineup.players.map(|player| {
+            let [mechanical, game_knowledge, team_communication, adaptability, consistency, form, potential] =
+                player.attributes;
+            simulation::Player {
+                role: player.role,
+                position: player.position,
+                attributes: simulation::Attributes {
+                    mechanical,
+                    game_knowledge,
+                    team_communication,
+                    adaptability,
+                    consistency,
+                    form,
+                    potential,
+                },
+            }
+        }),
+    }
+}
+
+// Position the team owner slotted this player in
+fn roster_position(team: &TeamData, player: &PlayerStats) -> Option<Role> {
+    team.roster
+        .iter()
+        .find(|p| p.nft_mint == player.nft_mint)
+        .and_then(|p| Role::parse(&p.position))
+}
+
+// Game logs as stored in MatchState, with the MVP named by NFT mint
+pub fn game_logs(result: &SeriesResult, team1_lineup: &[Pubkey; 5], team2_lineup: &[Pubkey; 5]) -> Vec<GameLog> {
+    result
+        .games
+        .iter()
+        .map(|game| {
+            let winner = if game.team1_won { team1_lineup } else { team2_lineup };
+            GameLog {
+                team1_won: game.team1_won,
+                team1_fights: game.team1_fights,
+                team2_fights: game.team2_fights,
+                team1_objectives: game.team1_objectives,
+                team2_objectives: game.team2_objectives,
+                mvp: winner[game.mvp],
+                team1_contributions: game.team1_contributions.map(|c| c.min(u16::MAX as u32) as u16),
+                team2_contributions: game.team2_contributions.map(|c| c.min(u16::MAX as u32) as u16),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,115 @@
+use bolt_lang::*;
+use esport_types::MatchmakingAction;
+
+// You'll need to replace this with an actual program ID when deploying
+declare_id!("62fPgcVy5i4HRjwZxffgQf8kGCgDPYLXi3reamzV39sV");
+
+// Serializable arguments for matchmaking system: `{"action": "enqueue", ...}`
+#[arguments]
+pub struct MatchmakingSystemArgs {
+    #[serde(flatten)]
+    pub action: MatchmakingAction,
+}
+
+// Error codes, from esport_types::errors::offsets::MATCHMAKING_SYSTEM
+#[error_code(offset = 6700)]
+pub enum SystemError {
+    #[msg("Not the team owner")]
+    NotTeamOwner,
+
+    #[msg("Team is not active")]
+    TeamInactive,
+
+    #[msg("Team doesn't have enough players")]
+    InsufficientRoster,
+
+    #[msg("Team hasn't selected a strategy")]
+    NoStrategy,
+}
+
+/*
+ * Matchmaking system: "find me a game".
+ *
+ *   enqueue   - team owner joins the queue with the team's current rating; the ticket
+ *               expires after the config's ticket timeout
+ *   leave     - team owner leaves the queue; anyone can drop the ticket of a disbanded
+ *               team
+ *   matchmake - crank, callable by anyone: prunes expired tickets, then pairs the
+ *               closest-rated tickets whose rating windows overlap into accepted pending
+ *               matches that match_system can simulate. team_data is not read by this
+ *               action.
+ */
+#[system]
+pub mod matchmaking_system {
+    use anchor_lang::prelude::msg;
+
+    use esport::{pause, GameConfig};
+    use esport_types::MatchmakingAction;
+    use match_queue::MatchQueue;
+    use team_data::{StrategyType, TeamData};
+
+    use crate::{MatchmakingSystemArgs, SystemError};
+
+    pub fn execute(ctx: Context<Components>, args: MatchmakingSystemArgs) -> Result<Components> {
+        let authority = ctx.accounts.authority.key();
+        let team_key = ctx.accounts.team_data.key();
+
+        // The game config is the first extra account
+        let (config, _) = GameConfig::from_remaining(ctx.remaining_accounts)?;
+        config.require_unpaused(pause::MATCHMAKING)?;
+
+        match args.action {
+            MatchmakingAction::Enqueue { match_type } => {
+                let team_data = &ctx.accounts.team_data;
+                require!(team_data.owner == authority, SystemError::NotTeamOwner);
+                require!(team_data.active, SystemError::TeamInactive);
+                require!(team_data.roster.len() > 0, SystemError::InsufficientRoster);
+                require!(
+                    team_data.strategy.strategy_type != StrategyType::None,
+                    SystemError::NoStrategy
+                );
+
+                let rating = team_data.rating;
+                ctx.accounts.match_queue.enqueue(
+                    team_key,
+                    rating,
+                    match_type.clone(),
+                    config.ticket_timeout,
+                )?;
+
+                msg!(
+                    "{} queued for {} at rating {}",
+                    ctx.accounts.team_data.name,
+                    match_type,
+                    rating
+                );
+            }
+            MatchmakingAction::Leave => {
+                let team_data = &ctx.accounts.team_data;
+                require!(
+                    team_data.owner == authority || !team_data.active,
+                    SystemError::NotTeamOwner
+                );
+                ctx.accounts.match_queue.leave(team_key)?;
+
+                msg!("{} left the queue", ctx.accounts.team_data.name);
+            }
+            MatchmakingAction::Matchmake => {
+                let created = ctx
+                    .accounts
+                    .match_queue
+                    .matchmake(config.accepted_match_timeout)?;
+
+                msg!("Matchmaking paired {} matches", created.len());
+            }
+        }
+
+        Ok(ctx.accounts)
+    }
+
+    #[system_input]
+    pub struct Components {
+        pub match_queue: MatchQueue,
+        pub team_data: TeamData,
+    }
+}
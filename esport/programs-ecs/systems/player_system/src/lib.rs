@@ -0,0 +1,160 @@
+use bolt_lang::*;
+use esport_types::PlayerAction;
+
+pub mod metadata;
+pub mod registry;
+
+// You'll need to replace this with an actual program ID when deploying
+declare_id!("GbAYBAeutMWjBCDfmcRaY2gTsKyLvkLVr2fVBfikVWez");
+
+// Serializable arguments for player system: `{"action": "mintPlayer", ...}`
+#[arguments]
+pub struct PlayerSystemArgs {
+    #[serde(flatten)]
+    pub action: PlayerAction,
+}
+
+// Error codes, from esport_types::errors::offsets::PLAYER_SYSTEM
+#[error_code(offset = 6800)]
+pub enum SystemError {
+    #[msg("Metadata account not provided")]
+    MetadataNotProvided,
+
+    #[msg("Account is not the NFT's Metaplex metadata")]
+    InvalidMetadata,
+
+    #[msg("NFT is not part of the verified player collection")]
+    NotInPlayerCollection,
+
+    #[msg("Only the mint authority can choose player attributes")]
+    NotMintAuthority,
+
+    #[msg("Role and all attributes must be provided")]
+    AttributesNotProvided,
+
+    #[msg("Player record, system program or instructions sysvar not provided")]
+    RecordAccountsNotProvided,
+
+    #[msg("Account is not the mint's player record")]
+    InvalidPlayerRecord,
+
+    #[msg("Invalid system program")]
+    InvalidSystemProgram,
+
+    #[msg("A player has already been minted for this NFT")]
+    PlayerAlreadyMinted,
+
+    #[msg("Player record names a different PlayerStats")]
+    NotRecordedPlayer,
+
+    #[msg("Token account for the player NFT not provided")]
+    TokenAccountNotProvided,
+}
+
+/*
+ * Player system: creates the PlayerStats component of a player NFT.
+ *
+ *   mintPlayer - binds an empty PlayerStats to an NFT of the config's verified player
+ *                collection. The config's mint authority sets role and attributes; anyone
+ *                else must hold the NFT and gets them rolled from the metadata. The mint's
+ *                player record (["player", mint] under this program) is created to point at
+ *                the PlayerStats, so each NFT is minted once; addPlayerToTeam checks it.
+ *                Extra accounts after the game config: the NFT's metadata, the player
+ *                record, the system program, the instructions sysvar and, unless the
+ *                signer is the mint authority, the signer's token account for the mint.
+ */
+#[system]
+pub mod player_system {
+    use anchor_lang::prelude::msg;
+    use player_stats::PlayerStats;
+
+    use esport::{pause, GameConfig};
+    use esport_types::PlayerAction;
+
+    use crate::{metadata, registry};
+    use crate::{PlayerSystemArgs, SystemError};
+
+    pub fn execute(ctx: Context<Components>, args: PlayerSystemArgs) -> Result<Components> {
+        let authority = ctx.accounts.authority.key();
+
+        // The game config is always the first extra account; action-specific accounts follow it
+        let (config, extra_accounts) = GameConfig::from_remaining(ctx.remaining_accounts)?;
+        config.require_unpaused(pause::MINT_PLAYER)?;
+
+        match args.action {
+            PlayerAction::MintPlayer {
+                player_nft_mint,
+                attributes: chosen,
+            } => {
+                let metadata_account = extra_accounts
+                    .first()
+                    .ok_or(SystemError::MetadataNotProvided)?;
+                let metadata = metadata::player_metadata(
+                    metadata_account,
+                    &player_nft_mint,
+                    &config.player_collection,
+                )?;
+
+                let [_, record, system_program, instructions_sysvar, ..] = extra_accounts else {
+                    return Err(SystemError::RecordAccountsNotProvided.into());
+                };
+
+                // The record is only written next to the PlayerStats it names
+                esport::require_world_apply(instructions_sysvar)?;
+                registry::create(
+                    record,
+                    &ctx.accounts.authority.to_account_info(),
+                    system_program,
+                    &player_nft_mint,
+                    &ctx.accounts.player_stats.key(),
+                )?;
+
+                let (role, attributes) = if authority == config.mint_authority {
+                    chosen
+                        .complete()
+                        .ok_or(SystemError::AttributesNotProvided)?
+                } else {
+                    require!(chosen.is_empty(), SystemError::NotMintAuthority);
+                    metadata::rolled_player(&player_nft_mint, &metadata)
+                };
+
+                let [mechanical, game_knowledge, team_communication, adaptability, consistency, form, potential] =
+                    attributes;
+                ctx.accounts.player_stats.initialize_stats(
+                    player_nft_mint,
+                    role.label().to_string(),
+                    mechanical,
+                    game_knowledge,
+                    team_communication,
+                    adaptability,
+                    consistency,
+                    form,
+                    potential,
+                )?;
+
+                // Anyone but the mint authority must hold the NFT
+                if authority != config.mint_authority {
+                    let token_account = extra_accounts
+                        .get(4)
+                        .ok_or(SystemError::TokenAccountNotProvided)?;
+                    ctx.accounts
+                        .player_stats
+                        .verify_owner(token_account, &authority)?;
+                }
+
+                msg!(
+                    "Player minted: {} ({})",
+                    metadata.name.trim_end_matches('\0'),
+                    role.label()
+                );
+            }
+        }
+
+        Ok(ctx.accounts)
+    }
+
+    #[system_input]
+    pub struct Components {
+        pub player_stats: PlayerStats,
+    }
+}
@@ -0,0 +1,62 @@
+use bolt_lang::*;
+use mpl_token_metadata::accounts::Metadata;
+use player_stats::Role;
+use solana_program::hash::hashv;
+use solana_program::pubkey::Pubkey;
+
+use crate::SystemError;
+
+// Domain separator for attributes rolled from metadata
+const ATTRIBUTE_DOMAIN: &[u8] = b"5vs5dotgg:player-attributes";
+
+// Rolled attributes fall in ATTRIBUTE_MIN..=ATTRIBUTE_MIN + ATTRIBUTE_SPREAD - 1, so only the
+// mint authority can hand out the very top values
+const ATTRIBUTE_MIN: u8 = 40;
+const ATTRIBUTE_SPREAD: u8 = 51;
+
+// Metadata of a player NFT, checked to be the Metaplex metadata PDA of `mint` and to
+// belong to the verified player `collection`
+pub fn player_metadata(
+    metadata_account: &AccountInfo,
+    mint: &Pubkey,
+    collection_mint: &Pubkey,
+) -> Result<Metadata> {
+    require_keys_eq!(
+        *metadata_account.owner,
+        mpl_token_metadata::ID,
+        SystemError::InvalidMetadata
+    );
+    require_keys_eq!(
+        metadata_account.key(),
+        Metadata::find_pda(mint).0,
+        SystemError::InvalidMetadata
+    );
+
+    let data = metadata_account.try_borrow_data()?;
+    let metadata = Metadata::safe_deserialize(&data).map_err(|_| SystemError::InvalidMetadata)?;
+    require_keys_eq!(metadata.mint, *mint, SystemError::InvalidMetadata);
+
+    match &metadata.collection {
+        Some(collection) if collection.verified && collection.key == *collection_mint => {
+            Ok(metadata)
+        }
+        _ => Err(SystemError::NotInPlayerCollection.into()),
+    }
+}
+
+// Role and attributes (mechanical, game knowledge, team communication, adaptability,
+// consistency, form, potential) rolled from the mint and its metadata name. The same NFT
+// always rolls the same player.
+pub fn rolled_player(mint: &Pubkey, metadata: &Metadata) -> (Role, [u8; 7]) {
+    let name = metadata.name.trim_end_matches('\0');
+    let hash = hashv(&[ATTRIBUTE_DOMAIN, mint.as_ref(), name.as_bytes()]).to_bytes();
+
+    let mut attributes = [0; 7];
+    for (attribute, byte) in attributes.iter_mut().zip(hash.iter()) {
+        *attribute = ATTRIBUTE_MIN + byte % ATTRIBUTE_SPREAD;
+    }
+
+    let role = Role::ALL[hash[7] as usize % Role::ALL.len()];
+
+    (role, attributes)
+}
@@ -0,0 +1,94 @@
+use bolt_lang::*;
+use solana_program::program::{invoke, invoke_signed};
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+
+use crate::SystemError;
+
+pub const PLAYER_RECORD_SEED: &[u8] = b"player";
+
+// A player record holds the key of the one PlayerStats bound to its mint
+const PLAYER_RECORD_LEN: usize = 32;
+
+// Player record of an NFT mint: a player_system PDA created by mintPlayer
+pub fn player_record_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PLAYER_RECORD_SEED, mint.as_ref()], &crate::ID)
+}
+
+// Create the player record of `mint` pointing at `player_stats`. Fails once the mint has a
+// record, so each NFT gets a single PlayerStats.
+pub fn create<'info>(
+    record: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    mint: &Pubkey,
+    player_stats: &Pubkey,
+) -> Result<()> {
+    let (address, bump) = player_record_address(mint);
+    require_keys_eq!(record.key(), address, SystemError::InvalidPlayerRecord);
+    require_keys_eq!(
+        system_program.key(),
+        solana_program::system_program::ID,
+        SystemError::InvalidSystemProgram
+    );
+    require_keys_neq!(*record.owner, crate::ID, SystemError::PlayerAlreadyMinted);
+
+    let seeds: &[&[u8]] = &[PLAYER_RECORD_SEED, mint.as_ref(), &[bump]];
+    let rent = Rent::get()?.minimum_balance(PLAYER_RECORD_LEN);
+    let lamports = record.lamports();
+
+    if lamports == 0 {
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                record.key,
+                rent,
+                PLAYER_RECORD_LEN as u64,
+                &crate::ID,
+            ),
+            &[payer.clone(), record.clone(), system_program.clone()],
+            &[seeds],
+        )?;
+    } else {
+        // Someone already sent lamports to the address; top it up and take it over
+        if lamports < rent {
+            invoke(
+                &system_instruction::transfer(payer.key, record.key, rent - lamports),
+                &[payer.clone(), record.clone(), system_program.clone()],
+            )?;
+        }
+        invoke_signed(
+            &system_instruction::allocate(record.key, PLAYER_RECORD_LEN as u64),
+            &[record.clone(), system_program.clone()],
+            &[seeds],
+        )?;
+        invoke_signed(
+            &system_instruction::assign(record.key, &crate::ID),
+            &[record.clone(), system_program.clone()],
+            &[seeds],
+        )?;
+    }
+
+    record
+        .try_borrow_mut_data()?
+        .copy_from_slice(player_stats.as_ref());
+    Ok(())
+}
+
+// Check that `record` is the player record of `mint` and names `player_stats`
+pub fn verify(record: &AccountInfo, mint: &Pubkey, player_stats: &Pubkey) -> Result<()> {
+    require_keys_eq!(
+        record.key(),
+        player_record_address(mint).0,
+        SystemError::InvalidPlayerRecord
+    );
+    require_keys_eq!(*record.owner, crate::ID, SystemError::InvalidPlayerRecord);
+
+    let data = record.try_borrow_data()?;
+    require!(
+        data.len() == PLAYER_RECORD_LEN && data[..] == player_stats.as_ref()[..],
+        SystemError::NotRecordedPlayer
+    );
+
+    Ok(())
+}
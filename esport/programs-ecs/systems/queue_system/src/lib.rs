@@ -0,0 +1,165 @@
+use bolt_lang::*;
+use esport_types::QueueAction;
+
+// You'll need to replace this with an actual program ID when deploying
+declare_id!("3wwBeeURrFMPBqKArcat8F4rQYUuagUMzNVU6SQ86Wwa");
+
+// Serializable arguments for queue system: `{"action": "challenge", ...}`
+#[arguments]
+pub struct QueueSystemArgs {
+    #[serde(flatten)]
+    pub action: QueueAction,
+}
+
+// Error codes, from esport_types::errors::offsets::QUEUE_SYSTEM
+#[error_code(offset = 6600)]
+pub enum SystemError {
+    #[msg("Not the team owner")]
+    NotTeamOwner,
+
+    #[msg("Team is not active")]
+    TeamInactive,
+
+    #[msg("Team doesn't have enough players")]
+    InsufficientRoster,
+
+    #[msg("Team hasn't selected a strategy")]
+    NoStrategy,
+
+    #[msg("Teams don't match the pending match")]
+    TeamMismatch,
+}
+
+/*
+ * Queue system: direct challenges between two teams.
+ *
+ * team1_data is always the challenger and team2_data the opponent:
+ *   challenge - team 1 owner pushes a PendingMatch
+ *   accept    - team 2 owner accepts, the match can then be simulated
+ *   decline   - team 2 owner refuses, the entry is removed
+ *   cancel    - team 1 owner withdraws a challenge that wasn't accepted yet
+ *   expire    - anyone removes entries past their deadline, matchmaking tickets included
+ */
+#[system]
+pub mod queue_system {
+    use anchor_lang::prelude::msg;
+
+    use esport::{pause, GameConfig};
+    use esport_types::QueueAction;
+    use match_queue::MatchQueue;
+    use team_data::{StrategyType, TeamData};
+
+    use crate::{QueueSystemArgs, SystemError};
+
+    pub fn execute(ctx: Context<Components>, args: QueueSystemArgs) -> Result<Components> {
+        let authority = ctx.accounts.authority.key();
+        let team1_key = ctx.accounts.team1_data.key();
+        let team2_key = ctx.accounts.team2_data.key();
+
+        // The game config is the first extra account
+        let (config, _) = GameConfig::from_remaining(ctx.remaining_accounts)?;
+        config.require_unpaused(pause::CHALLENGES)?;
+
+        let accept = matches!(args.action, QueueAction::Accept { .. });
+        match args.action {
+            QueueAction::Challenge { match_type } => {
+                let team1_data = &ctx.accounts.team1_data;
+                let team2_data = &ctx.accounts.team2_data;
+                require!(team1_data.owner == authority, SystemError::NotTeamOwner);
+
+                // Both teams must be able to play
+                require!(
+                    team1_data.active && team2_data.active,
+                    SystemError::TeamInactive
+                );
+                require!(team1_data.roster.len() > 0, SystemError::InsufficientRoster);
+                require!(team2_data.roster.len() > 0, SystemError::InsufficientRoster);
+                require!(
+                    team1_data.strategy.strategy_type != StrategyType::None,
+                    SystemError::NoStrategy
+                );
+                require!(
+                    team2_data.strategy.strategy_type != StrategyType::None,
+                    SystemError::NoStrategy
+                );
+
+                let id = ctx.accounts.match_queue.challenge(
+                    team1_key,
+                    team2_key,
+                    match_type.clone(),
+                    config.challenge_timeout,
+                )?;
+
+                msg!(
+                    "Match {} challenged: {} vs {} ({})",
+                    id,
+                    team1_data.name,
+                    team2_data.name,
+                    match_type
+                );
+            }
+            QueueAction::Accept {
+                pending_match_id: id,
+            }
+            | QueueAction::Decline {
+                pending_match_id: id,
+            } => {
+                require!(
+                    ctx.accounts.team2_data.owner == authority,
+                    SystemError::NotTeamOwner
+                );
+
+                let queue = &mut ctx.accounts.match_queue;
+                let pending = queue.get(id)?;
+                require!(
+                    pending.team1 == team1_key && pending.team2 == team2_key,
+                    SystemError::TeamMismatch
+                );
+
+                if accept {
+                    queue.accept(id, config.accepted_match_timeout)?;
+                    msg!("Match {} accepted", id);
+                } else {
+                    queue.decline(id)?;
+                    msg!("Match {} declined", id);
+                }
+            }
+            QueueAction::Cancel {
+                pending_match_id: id,
+            } => {
+                require!(
+                    ctx.accounts.team1_data.owner == authority,
+                    SystemError::NotTeamOwner
+                );
+
+                let queue = &mut ctx.accounts.match_queue;
+                let pending = queue.get(id)?;
+                require!(
+                    pending.team1 == team1_key && pending.team2 == team2_key,
+                    SystemError::TeamMismatch
+                );
+                queue.cancel(id)?;
+
+                msg!("Match {} cancelled", id);
+            }
+            QueueAction::Expire => {
+                let (matches, tickets) = ctx.accounts.match_queue.expire()?;
+
+                msg!(
+                    "Expired {} pending matches and {} matchmaking tickets",
+                    matches,
+                    tickets
+                );
+            }
+        }
+
+        Ok(ctx.accounts)
+    }
+
+    #[system_input]
+    pub struct Components {
+        pub match_queue: MatchQueue,
+        pub team1_data: TeamData,
+        pub team2_data: TeamData,
+    }
+}
@@ -1,5 +1,8 @@
 use bolt_lang::*;
-use solana_program::pubkey::Pubkey;
+use esport::pause;
+use esport_types::TeamAction;
+
+pub mod stake;
 
 // Explicitly import the component types
 // use team_data::TeamData;
@@ -8,129 +11,199 @@ use solana_program::pubkey::Pubkey;
 // You'll need to replace this with an actual program ID when deploying
 declare_id!("EyqXcKHQ6dAEcKpCzrsMsoC87XtMjatsSNSKNNHC5C8R");
 
-// Serializable arguments for team system
+// Serializable arguments for team system: `{"action": "createTeam", ...}`
 #[arguments]
 pub struct TeamSystemArgs {
-    pub action: String,
-    pub team_name: Option<String>,
-    pub player_nft_mint: Option<String>,
-    pub position: Option<String>,
-    pub strategy_type: Option<String>,
-    pub strategy_description: Option<String>,
+    #[serde(flatten)]
+    pub action: TeamAction,
 }
 
-// Error codes
-#[error_code]
+// Error codes, from esport_types::errors::offsets::TEAM_SYSTEM
+#[error_code(offset = 6400)]
 pub enum SystemError {
-    #[msg("Invalid arguments format")]
-    InvalidArgs,
-    
-    #[msg("Unknown action")]
-    UnknownAction,
-    
     #[msg("Not the team owner")]
     NotTeamOwner,
     
     #[msg("Invalid NFT ownership")]
     InvalidNftOwnership,
     
-    #[msg("Team name not provided")]
-    TeamNameNotProvided,
+    #[msg("Player NFT token account or player record not provided")]
+    TokenAccountNotProvided,
+    
+    #[msg("Player stats are for a different NFT")]
+    PlayerMismatch,
     
-    #[msg("Player NFT mint not provided")]
-    PlayerNftMintNotProvided,
+    #[msg("Player is still on the roster of an active team")]
+    PlayerStillRostered,
     
-    #[msg("Position not provided")]
-    PositionNotProvided,
+    #[msg("Stake is below the minimum")]
+    StakeTooLow,
     
-    #[msg("Strategy type not provided")]
-    StrategyTypeNotProvided,
+    #[msg("Stake accounts not provided")]
+    StakeAccountsNotProvided,
     
-    #[msg("Strategy description not provided")]
-    StrategyDescriptionNotProvided,
+    #[msg("Stake vault does not belong to this team")]
+    InvalidStakeVault,
+    
+    #[msg("Invalid system program")]
+    InvalidSystemProgram,
+    
+    #[msg("Treasury account does not match the game config")]
+    InvalidTreasury,
+}
+
+// GameConfig pause bit of a team action
+fn pause_bit(action: &TeamAction) -> u64 {
+    match action {
+        TeamAction::CreateTeam { .. } => pause::CREATE_TEAM,
+        TeamAction::AddPlayerToTeam { .. }
+        | TeamAction::RemovePlayerFromTeam { .. }
+        | TeamAction::ReleasePlayer => pause::MANAGE_ROSTER,
+        TeamAction::SetStrategy { .. } => pause::SET_STRATEGY,
+        TeamAction::DisbandTeam => pause::DISBAND_TEAM,
+    }
 }
 
 #[system]
 pub mod team_system {
     // Import everything we need in the system module scope
     // use bolt_lang::*;
-    use anchor_lang::prelude::msg;
-    use solana_program::pubkey::Pubkey;
-    use std::str::FromStr;
+    use anchor_lang::prelude::emit;
     
     // Import these components explicitly
     use team_data::TeamData;
     use player_stats::PlayerStats;
     
-    use crate::{SystemError, TeamSystemArgs};
+    use esport::GameConfig;
+    use esport_types::TeamAction;
+    use esport_types::events::{PlayerAdded, PlayerReleased, PlayerRemoved, StrategySet, TeamCreated, TeamDisbanded};
+    
+    use crate::{pause_bit, SystemError, TeamSystemArgs};
+    use crate::stake;
     
     pub fn execute(ctx: Context<Components>, args: TeamSystemArgs) -> Result<Components> {
         // Get the authority key
         let authority = ctx.accounts.authority.key();
+        let team_key = ctx.accounts.team_data.key();
+        
+        // The game config is always the first extra account; action-specific accounts follow it
+        let (config, extra_accounts) = GameConfig::from_remaining(ctx.remaining_accounts)?;
+        config.require_unpaused(pause_bit(&args.action))?;
         
         // Dispatch to appropriate handler based on action
-        match args.action.as_str() {
-            "createTeam" => {
-                let team_name = args.team_name.ok_or(SystemError::TeamNameNotProvided)?;
+        match args.action {
+            TeamAction::CreateTeam { team_name, stake_amount } => {
+                let stake_amount = stake_amount.unwrap_or(config.min_team_stake);
+                require!(stake_amount >= config.min_team_stake, SystemError::StakeTooLow);
+                
+                // Extra accounts: the team's stake vault, the system program and the
+                // instructions sysvar
+                let [stake_vault, system_program, instructions_sysvar, ..] = extra_accounts else {
+                    return Err(SystemError::StakeAccountsNotProvided.into());
+                };
+                esport::require_world_apply(instructions_sysvar)?;
+                stake::deposit(
+                    &ctx.accounts.authority.to_account_info(),
+                    stake_vault,
+                    system_program,
+                    &team_key,
+                    stake_amount,
+                )?;
                 
                 let team_data = &mut ctx.accounts.team_data;
-                team_data.initialize(team_name.clone(), authority)?;
+                team_data.initialize(team_name.clone(), authority, stake_amount)?;
                 
-                msg!("Team created: {}", team_name);
+                emit!(TeamCreated { team: team_key, owner: authority, name: team_name, stake_amount });
             },
-            "addPlayerToTeam" => {
-                let player_nft_mint_str = args.player_nft_mint.ok_or(SystemError::PlayerNftMintNotProvided)?;
-                let position = args.position.ok_or(SystemError::PositionNotProvided)?;
-                
-                // Convert string to Pubkey
-                let player_nft_mint = Pubkey::from_str(&player_nft_mint_str)
-                    .map_err(|_| SystemError::InvalidArgs)?;
-                
+            TeamAction::AddPlayerToTeam { player_nft_mint, position } => {
                 // Verify ownership matches
                 let player_stats = &ctx.accounts.player_stats;
                 require!(player_stats.nft_mint == player_nft_mint, SystemError::InvalidNftOwnership);
                 
+                // The signer must hold the NFT, and the stats must be the ones mintPlayer
+                // recorded for it. Extra accounts: the signer's token account for the mint and
+                // the mint's player record.
+                let [token_account, player_record, ..] = extra_accounts else {
+                    return Err(SystemError::TokenAccountNotProvided.into());
+                };
+                player_stats.verify_owner(token_account, &authority)?;
+                player_system::registry::verify(player_record, &player_nft_mint, &player_stats.key())?;
+                
                 // Add player to team
                 let team_data = &mut ctx.accounts.team_data;
                 require!(team_data.owner == authority, SystemError::NotTeamOwner);
-                team_data.add_player(player_nft_mint, position.clone())?;
-                
-                msg!("Player added to team: {}", position);
-            },
-            "removePlayerFromTeam" => {
-                let player_nft_mint_str = args.player_nft_mint.ok_or(SystemError::PlayerNftMintNotProvided)?;
+                team_data.add_player(player_nft_mint, position.label().to_string(), config.max_roster_size as usize)?;
                 
-                // Convert string to Pubkey
-                let player_nft_mint = Pubkey::from_str(&player_nft_mint_str)
-                    .map_err(|_| SystemError::InvalidArgs)?;
+                // Rejects players that are still on another team
+                ctx.accounts.player_stats.join_team(team_key)?;
                 
+                emit!(PlayerAdded { team: team_key, player_nft_mint, position: position.label().to_string() });
+            },
+            TeamAction::RemovePlayerFromTeam { player_nft_mint } => {
                 // Remove player from team
                 let team_data = &mut ctx.accounts.team_data;
                 require!(team_data.owner == authority, SystemError::NotTeamOwner);
                 team_data.remove_player(player_nft_mint)?;
                 
-                msg!("Player removed from team");
-            },
-            "setStrategy" => {
-                let strategy_type = args.strategy_type.ok_or(SystemError::StrategyTypeNotProvided)?;
-                let strategy_description = args.strategy_description.ok_or(SystemError::StrategyDescriptionNotProvided)?;
+                let player_stats = &mut ctx.accounts.player_stats;
+                require!(player_stats.nft_mint == player_nft_mint, SystemError::PlayerMismatch);
+                player_stats.leave_team(team_key)?;
                 
+                emit!(PlayerRemoved { team: team_key, player_nft_mint });
+            },
+            TeamAction::SetStrategy { strategy_type, strategy_description } => {
                 // Set team strategy
                 let team_data = &mut ctx.accounts.team_data;
                 require!(team_data.owner == authority, SystemError::NotTeamOwner);
-                team_data.set_strategy(strategy_type.clone(), strategy_description)?;
+                team_data.set_strategy(strategy_type, strategy_description.clone())?;
                 
-                msg!("Team strategy set: {}", strategy_type);
+                emit!(StrategySet { team: team_key, strategy_type, strategy_description });
             },
-            "disbandTeam" => {
+            TeamAction::DisbandTeam => {
                 // Disband team
                 let team_data = &mut ctx.accounts.team_data;
                 require!(team_data.owner == authority, SystemError::NotTeamOwner);
-                team_data.disband()?;
+                let (refund, penalties) = team_data.disband()?;
+                
+                // Extra accounts: the team's stake vault, the system program, the treasury and
+                // the instructions sysvar. The owner gets the stake back minus penalties, which
+                // go to the treasury.
+                let [stake_vault, system_program, treasury, instructions_sysvar, ..] = extra_accounts else {
+                    return Err(SystemError::StakeAccountsNotProvided.into());
+                };
+                esport::require_world_apply(instructions_sysvar)?;
+                require_keys_eq!(treasury.key(), config.treasury, SystemError::InvalidTreasury);
+                stake::withdraw(stake_vault, &ctx.accounts.authority.to_account_info(), system_program, &team_key, refund)?;
+                stake::withdraw(stake_vault, treasury, system_program, &team_key, penalties)?;
+                
+                emit!(TeamDisbanded { team: team_key, owner: authority, refund, penalties });
+                
+                // The passed player is released right away, the rest through releasePlayer
+                let player_stats = &mut ctx.accounts.player_stats;
+                if player_stats.current_team == team_key {
+                    player_stats.leave_team(team_key)?;
+                    emit!(PlayerReleased { team: team_key, player_nft_mint: player_stats.nft_mint });
+                }
+            },
+            TeamAction::ReleasePlayer => {
+                // Anyone can free a player whose team disbanded or no longer lists it
+                let nft_mint = ctx.accounts.player_stats.nft_mint;
+                let team_data = &mut ctx.accounts.team_data;
+                let rostered = team_data.active && team_data.roster.iter().any(|p| p.nft_mint == nft_mint);
+                
+                // A player sold while rostered can be released by its new holder;
+                // extra account: their token account for the mint
+                if rostered {
+                    require!(team_data.owner != authority, SystemError::PlayerStillRostered);
+                    let token_account = extra_accounts.first().ok_or(SystemError::PlayerStillRostered)?;
+                    ctx.accounts.player_stats.verify_owner(token_account, &authority)?;
+                    team_data.remove_player(nft_mint)?;
+                }
+                
+                ctx.accounts.player_stats.leave_team(team_key)?;
                 
-                msg!("Team disbanded");
+                emit!(PlayerReleased { team: team_key, player_nft_mint: nft_mint });
             },
-            _ => return Err(SystemError::UnknownAction.into())
         }
         
         Ok(ctx.accounts)
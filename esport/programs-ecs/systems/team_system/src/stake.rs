@@ -0,0 +1,60 @@
+use bolt_lang::*;
+use solana_program::program::{invoke, invoke_signed};
+use solana_program::pubkey::Pubkey;
+use solana_program::system_instruction;
+
+use crate::SystemError;
+
+pub const STAKE_VAULT_SEED: &[u8] = b"stake_vault";
+
+// Lamport vault holding a team's stake: a system account at a team_system PDA
+pub fn stake_vault_address(team: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_VAULT_SEED, team.as_ref()], &crate::ID)
+}
+
+fn check_accounts(vault: &AccountInfo, system_program: &AccountInfo, team: &Pubkey) -> Result<u8> {
+    let (address, bump) = stake_vault_address(team);
+    require_keys_eq!(vault.key(), address, SystemError::InvalidStakeVault);
+    require_keys_eq!(system_program.key(), solana_program::system_program::ID, SystemError::InvalidSystemProgram);
+    Ok(bump)
+}
+
+// Move `amount` lamports from the team owner into the team's stake vault
+pub fn deposit<'info>(
+    owner: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    team: &Pubkey,
+    amount: u64,
+) -> Result<()> {
+    check_accounts(vault, system_program, team)?;
+
+    invoke(
+        &system_instruction::transfer(owner.key, vault.key, amount),
+        &[owner.clone(), vault.clone(), system_program.clone()],
+    )?;
+
+    Ok(())
+}
+
+// Pay `amount` lamports out of the team's stake vault, signed with the vault seeds
+pub fn withdraw<'info>(
+    vault: &AccountInfo<'info>,
+    recipient: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    team: &Pubkey,
+    amount: u64,
+) -> Result<()> {
+    let bump = check_accounts(vault, system_program, team)?;
+    if amount == 0 {
+        return Ok(());
+    }
+
+    invoke_signed(
+        &system_instruction::transfer(vault.key, recipient.key, amount),
+        &[vault.clone(), recipient.clone(), system_program.clone()],
+        &[&[STAKE_VAULT_SEED, team.as_ref(), &[bump]]],
+    )?;
+
+    Ok(())
+}
@@ -1,15 +1,379 @@
 use bolt_lang::prelude::*;
+use bolt_lang::solana_program;
+use solana_program::instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT};
+use solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
 
 declare_id!("3h171djVn6LDR1JGHfMvg3HtZM96Vmjg95MJ4Zs5AFYg");
 
+pub const GAME_CONFIG_SEED: &[u8] = b"game_config";
+
+// Fee shares are in basis points of the collected entry fees
+pub const BPS_DENOMINATOR: u16 = 10_000;
+pub const DEFAULT_CREATOR_SHARE_BPS: u16 = 6_000;
+
+// Defaults a new config starts with
+pub const DEFAULT_MIN_TEAM_STAKE: u64 = 100_000_000;
+pub const DEFAULT_FORFEIT_PENALTY_BPS: u16 = 1_000;
+pub const DEFAULT_MAX_ROSTER_SIZE: u8 = 5;
+pub const DEFAULT_CHALLENGE_TIMEOUT: i64 = 24 * 60 * 60;
+pub const DEFAULT_ACCEPTED_MATCH_TIMEOUT: i64 = 24 * 60 * 60;
+pub const DEFAULT_REVEAL_TIMEOUT: i64 = 60 * 60;
+pub const DEFAULT_TICKET_TIMEOUT: i64 = 60 * 60;
+
+// Bolt's world program, which applies systems and writes their components back
+pub const WORLD_PROGRAM_ID: Pubkey = solana_program::pubkey!("WorLD15A7CrDwLcLy4fRqtaTb9fbd8o8iqiEMUDse2n");
+
+// Bits of GameConfig.paused_actions; each pauses a group of system actions
+pub mod pause {
+    pub const CREATE_TEAM: u64 = 1 << 0;
+    pub const MANAGE_ROSTER: u64 = 1 << 1; // addPlayerToTeam, removePlayerFromTeam, releasePlayer
+    pub const SET_STRATEGY: u64 = 1 << 2;
+    pub const DISBAND_TEAM: u64 = 1 << 3;
+    pub const MINT_PLAYER: u64 = 1 << 4;
+    pub const CHALLENGES: u64 = 1 << 5; // Every queue_system action
+    pub const MATCHMAKING: u64 = 1 << 6;
+    pub const SCHEDULE_MATCH: u64 = 1 << 7;
+    pub const PREPARE_MATCH: u64 = 1 << 8; // payEntryFee, lockLineups, consentMatch, commitSeed, revealSeed
+    pub const SIMULATE_MATCH: u64 = 1 << 9;
+    pub const FORFEIT_MATCH: u64 = 1 << 10; // forfeit, claimForfeit, disputeMatch, resolveDispute
+    pub const SETTLE_FEES: u64 = 1 << 11;
+}
+
+// Largest roster a TeamData component has room for
+pub const MAX_ROSTER_SIZE: u8 = 5;
+
+// Game-wide settings, a single PDA every system takes as its first extra account
+#[account]
+#[derive(InitSpace)]
+pub struct GameConfig {
+    pub admin: Pubkey,
+    pub treasury: Pubkey,          // Receives the platform share of match fees and stake penalties
+    pub entry_fee: u64,            // Lamports each team pays to play a match
+    pub creator_share_bps: u16,    // Share of match fees paid to the creators of the playing NFTs
+    pub platform_share_bps: u16,   // Share of match fees paid to the treasury
+    pub min_team_stake: u64,       // Lamports a team must stake when it is created
+    pub forfeit_penalty_bps: u16,  // Share of its stake a team loses for forfeiting
+    pub max_roster_size: u8,
+    pub elo_k_factor: u16,         // Elo K-factor for team ratings; 0 rates with Glicko-2
+    pub challenge_timeout: i64,    // Seconds a challenge stays open
+    pub accepted_match_timeout: i64, // Seconds an accepted match may wait to be scheduled
+    pub reveal_timeout: i64,       // Seconds for each step once lineups lock: consent and commits, reveals, simulation
+    pub ticket_timeout: i64,       // Seconds a matchmaking ticket waits for an opponent
+    pub randomness_oracle: Pubkey, // Signs VRF match seeds
+    pub player_collection: Pubkey, // Verified collection of player NFTs
+    pub mint_authority: Pubkey,    // May choose player attributes
+    pub match_operator: Pubkey,    // Crank allowed to simulate any match; default for none
+    pub paused: bool,              // Stops every system action
+    pub paused_actions: u64,       // Stops single action groups, see `pause`
+    pub bump: u8,
+}
+
+impl GameConfig {
+    // Read the config passed to a system as an extra account
+    pub fn load(account: &AccountInfo) -> Result<Self> {
+        require_keys_eq!(*account.owner, ID, ConfigError::InvalidGameConfig);
+
+        let data = account.try_borrow_data()?;
+        let config = Self::try_deserialize(&mut &data[..])?;
+
+        let address = Pubkey::create_program_address(&[GAME_CONFIG_SEED, &[config.bump]], &ID)
+            .map_err(|_| ConfigError::InvalidGameConfig)?;
+        require_keys_eq!(account.key(), address, ConfigError::InvalidGameConfig);
+
+        Ok(config)
+    }
+
+    // Systems take the config as their first extra account; returns it along with the
+    // extra accounts that follow it
+    pub fn from_remaining<'a, 'info>(
+        accounts: &'a [AccountInfo<'info>],
+    ) -> Result<(Self, &'a [AccountInfo<'info>])> {
+        let (config_account, rest) = accounts.split_first().ok_or(ConfigError::GameConfigNotProvided)?;
+        Ok((Self::load(config_account)?, rest))
+    }
+
+    // Systems call this first thing, with the pause bit of the requested action
+    pub fn require_unpaused(&self, action: u64) -> Result<()> {
+        require!(!self.paused, ConfigError::Paused);
+        require!(self.paused_actions & action == 0, ConfigError::ActionPaused);
+        Ok(())
+    }
+
+    pub fn is_match_operator(&self, key: &Pubkey) -> bool {
+        self.match_operator != Pubkey::default() && *key == self.match_operator
+    }
+
+    // Creators' part of `amount` in fees; the rest goes to the treasury
+    pub fn creator_share(&self, amount: u64) -> u64 {
+        amount * self.creator_share_bps as u64 / BPS_DENOMINATOR as u64
+    }
+
+    // Part of a team's stake it loses for forfeiting
+    pub fn forfeit_penalty(&self, stake: u64) -> u64 {
+        stake * self.forfeit_penalty_bps as u64 / BPS_DENOMINATOR as u64
+    }
+
+    // A fee vault is a plain system account, so a non-zero fee must keep it rent exempt
+    fn set_entry_fee(&mut self, entry_fee: u64) -> Result<()> {
+        require!(
+            entry_fee == 0 || entry_fee >= Rent::get()?.minimum_balance(0),
+            ConfigError::EntryFeeTooLow
+        );
+        self.entry_fee = entry_fee;
+        Ok(())
+    }
+
+    fn set_creator_share(&mut self, creator_share_bps: u16) -> Result<()> {
+        require!(creator_share_bps <= BPS_DENOMINATOR, ConfigError::InvalidShare);
+        self.creator_share_bps = creator_share_bps;
+        self.platform_share_bps = BPS_DENOMINATOR - creator_share_bps;
+        Ok(())
+    }
+
+    fn set_team_rules(&mut self, min_team_stake: u64, max_roster_size: u8, forfeit_penalty_bps: u16) -> Result<()> {
+        // Stake vaults are plain system accounts too
+        require!(min_team_stake >= Rent::get()?.minimum_balance(0), ConfigError::StakeTooLow);
+        require!(
+            max_roster_size > 0 && max_roster_size <= MAX_ROSTER_SIZE,
+            ConfigError::InvalidRosterSize
+        );
+        require!(forfeit_penalty_bps <= BPS_DENOMINATOR, ConfigError::InvalidShare);
+
+        self.min_team_stake = min_team_stake;
+        self.max_roster_size = max_roster_size;
+        self.forfeit_penalty_bps = forfeit_penalty_bps;
+        Ok(())
+    }
+
+    fn set_timeouts(
+        &mut self,
+        challenge_timeout: i64,
+        accepted_match_timeout: i64,
+        reveal_timeout: i64,
+        ticket_timeout: i64,
+    ) -> Result<()> {
+        require!(
+            challenge_timeout > 0 && accepted_match_timeout > 0 && reveal_timeout > 0 && ticket_timeout > 0,
+            ConfigError::InvalidTimeout
+        );
+        self.challenge_timeout = challenge_timeout;
+        self.accepted_match_timeout = accepted_match_timeout;
+        self.reveal_timeout = reveal_timeout;
+        self.ticket_timeout = ticket_timeout;
+        Ok(())
+    }
+}
+
+// Systems call this before moving lamports out of a vault. Invoked directly, or by any
+// program other than the world, a system still makes its transfers but its components are
+// never written back, so the same withdrawal could be replayed until the vault is empty.
+// The current top-level instruction must be the world's, and it must have invoked the
+// system itself.
+pub fn require_world_apply(instructions_sysvar: &AccountInfo) -> Result<()> {
+    require_keys_eq!(
+        *instructions_sysvar.key,
+        solana_program::sysvar::instructions::ID,
+        ConfigError::InvalidInstructionsSysvar
+    );
+
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    let instruction = load_instruction_at_checked(current_index as usize, instructions_sysvar)?;
+    require_keys_eq!(instruction.program_id, WORLD_PROGRAM_ID, ConfigError::NotAppliedByWorld);
+    require!(
+        get_stack_height() == TRANSACTION_LEVEL_STACK_HEIGHT + 1,
+        ConfigError::NotAppliedByWorld
+    );
+
+    Ok(())
+}
+
 #[program]
 pub mod esport {
     use super::*;
 
-    pub fn initialize(_ctx: Context<Initialize>) -> Result<()> {
+    // Create the game config with default rules. Only the program's upgrade authority can
+    // call it, and it becomes the config's admin; the keys trusted with randomness and
+    // player stats have no defaults.
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        treasury: Pubkey,
+        entry_fee: u64,
+        randomness_oracle: Pubkey,
+        player_collection: Pubkey,
+        mint_authority: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.game_config;
+        config.admin = ctx.accounts.admin.key();
+        config.treasury = treasury;
+        config.set_entry_fee(entry_fee)?;
+        config.set_creator_share(DEFAULT_CREATOR_SHARE_BPS)?;
+        config.set_team_rules(DEFAULT_MIN_TEAM_STAKE, DEFAULT_MAX_ROSTER_SIZE, DEFAULT_FORFEIT_PENALTY_BPS)?;
+        config.elo_k_factor = 0;
+        config.set_timeouts(
+            DEFAULT_CHALLENGE_TIMEOUT,
+            DEFAULT_ACCEPTED_MATCH_TIMEOUT,
+            DEFAULT_REVEAL_TIMEOUT,
+            DEFAULT_TICKET_TIMEOUT,
+        )?;
+        config.randomness_oracle = randomness_oracle;
+        config.player_collection = player_collection;
+        config.mint_authority = mint_authority;
+        config.match_operator = Pubkey::default();
+        config.paused = false;
+        config.paused_actions = 0;
+        config.bump = ctx.bumps.game_config;
+
+        Ok(())
+    }
+
+    pub fn set_fees(ctx: Context<UpdateConfig>, entry_fee: u64, creator_share_bps: u16) -> Result<()> {
+        let config = &mut ctx.accounts.game_config;
+        config.set_entry_fee(entry_fee)?;
+        config.set_creator_share(creator_share_bps)
+    }
+
+    pub fn set_team_rules(
+        ctx: Context<UpdateConfig>,
+        min_team_stake: u64,
+        max_roster_size: u8,
+        forfeit_penalty_bps: u16,
+    ) -> Result<()> {
+        ctx.accounts.game_config.set_team_rules(min_team_stake, max_roster_size, forfeit_penalty_bps)
+    }
+
+    // A K-factor of 0 switches team ratings to Glicko-2
+    pub fn set_rating_model(ctx: Context<UpdateConfig>, elo_k_factor: u16) -> Result<()> {
+        ctx.accounts.game_config.elo_k_factor = elo_k_factor;
+        Ok(())
+    }
+
+    pub fn set_timeouts(
+        ctx: Context<UpdateConfig>,
+        challenge_timeout: i64,
+        accepted_match_timeout: i64,
+        reveal_timeout: i64,
+        ticket_timeout: i64,
+    ) -> Result<()> {
+        ctx.accounts
+            .game_config
+            .set_timeouts(challenge_timeout, accepted_match_timeout, reveal_timeout, ticket_timeout)
+    }
+
+    pub fn set_authorities(
+        ctx: Context<UpdateConfig>,
+        randomness_oracle: Pubkey,
+        player_collection: Pubkey,
+        mint_authority: Pubkey,
+    ) -> Result<()> {
+        let config = &mut ctx.accounts.game_config;
+        config.randomness_oracle = randomness_oracle;
+        config.player_collection = player_collection;
+        config.mint_authority = mint_authority;
+        Ok(())
+    }
+
+    // Emergency stop for the whole game
+    pub fn set_paused(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
+        ctx.accounts.game_config.paused = paused;
+        Ok(())
+    }
+
+    // Pause only some actions, e.g. `pause::SIMULATE_MATCH`; replaces the current bits
+    pub fn set_paused_actions(ctx: Context<UpdateConfig>, paused_actions: u64) -> Result<()> {
+        ctx.accounts.game_config.paused_actions = paused_actions;
+        Ok(())
+    }
+
+    // Pubkey::default() removes the operator
+    pub fn set_match_operator(ctx: Context<UpdateConfig>, match_operator: Pubkey) -> Result<()> {
+        ctx.accounts.game_config.match_operator = match_operator;
+        Ok(())
+    }
+
+    pub fn set_treasury(ctx: Context<UpdateConfig>, treasury: Pubkey) -> Result<()> {
+        ctx.accounts.game_config.treasury = treasury;
         Ok(())
     }
+
+    pub fn set_admin(ctx: Context<UpdateConfig>, admin: Pubkey) -> Result<()> {
+        ctx.accounts.game_config.admin = admin;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + GameConfig::INIT_SPACE,
+        seeds = [GAME_CONFIG_SEED],
+        bump
+    )]
+    pub game_config: Account<'info, GameConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ ConfigError::InvalidProgramData)]
+    pub program: Program<'info, crate::program::Esport>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ConfigError::NotUpgradeAuthority)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct Initialize {}
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [GAME_CONFIG_SEED],
+        bump = game_config.bump,
+        has_one = admin @ ConfigError::NotAdmin
+    )]
+    pub game_config: Account<'info, GameConfig>,
+    pub admin: Signer<'info>,
+}
+
+// Error codes, from esport_types::errors::offsets::CONFIG
+#[error_code(offset = 6900)]
+pub enum ConfigError {
+    #[msg("Signer is not the config admin")]
+    NotAdmin,
+
+    #[msg("Fee share must be at most 10000 basis points")]
+    InvalidShare,
+
+    #[msg("Account is not the game config")]
+    InvalidGameConfig,
+
+    #[msg("Entry fee must be zero or cover the fee vault's rent exemption")]
+    EntryFeeTooLow,
+
+    #[msg("Game config not provided")]
+    GameConfigNotProvided,
+
+    #[msg("Minimum stake must cover the stake vault's rent exemption")]
+    StakeTooLow,
+
+    #[msg("Roster size must be between 1 and 5")]
+    InvalidRosterSize,
+
+    #[msg("Timeouts must be positive")]
+    InvalidTimeout,
+
+    #[msg("Game is paused")]
+    Paused,
+
+    #[msg("This action is paused")]
+    ActionPaused,
+
+    #[msg("Instructions sysvar account missing or invalid")]
+    InvalidInstructionsSysvar,
+
+    #[msg("System must be applied through the world program")]
+    NotAppliedByWorld,
+
+    #[msg("Account is not the esport program's program data")]
+    InvalidProgramData,
+
+    #[msg("Signer is not the program's upgrade authority")]
+    NotUpgradeAuthority,
+}
@@ -1,7 +1,16 @@
-import { PublicKey, Keypair } from "@solana/web3.js";
+import {
+  PublicKey,
+  Keypair,
+  SystemProgram,
+  LAMPORTS_PER_SOL,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  Ed25519Program,
+  Transaction,
+  TransactionInstruction,
+} from "@solana/web3.js";
 import {
     InitializeNewWorld,
-    AddEntity, 
+    AddEntity,
     InitializeComponent,
     ApplySystem,
     Program
@@ -11,24 +20,28 @@ import * as anchor from "@coral-xyz/anchor";
 
 // Import Metaplex Umi
 import { createUmi } from '@metaplex-foundation/umi-bundle-defaults';
-import { 
+import {
   createProgrammableNft,
+  findMetadataPda,
   mplTokenMetadata,
-  TokenStandard 
+  verifyCollectionV1,
+  TokenStandard
 } from '@metaplex-foundation/mpl-token-metadata';
-import { 
+import {
   createGenericFile,
   generateSigner,
   percentAmount,
   publicKey as umiPublicKey,
   signerIdentity,
   sol,
+  some,
   createSignerFromKeypair
 } from '@metaplex-foundation/umi';
 import { irysUploader } from '@metaplex-foundation/umi-uploader-irys';
 import { base58 } from '@metaplex-foundation/umi/serializers';
 import fs from 'fs';
 import path from 'path';
+import { createHash, randomBytes } from 'crypto';
 
 // Helper function to convert Solana Keypair to Umi Signer
 function solanaKeypairToUmiSigner(umi: any, keypair: Keypair) {
@@ -38,6 +51,89 @@ function solanaKeypairToUmiSigner(umi: any, keypair: Keypair) {
   });
 }
 
+// Commitment a team owner submits before a match: sha256(seed || owner)
+function seedCommitment(seed: Buffer, owner: PublicKey): number[] {
+  return Array.from(createHash("sha256").update(seed).update(owner.toBuffer()).digest());
+}
+
+// Owner's associated token account for a mint
+const TOKEN_PROGRAM_ID = new PublicKey("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+const ASSOCIATED_TOKEN_PROGRAM_ID = new PublicKey("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+function associatedTokenAccount(owner: PublicKey, mint: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [owner.toBuffer(), TOKEN_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+    ASSOCIATED_TOKEN_PROGRAM_ID
+  )[0];
+}
+
+// Metaplex metadata PDA of a mint
+function metadataAccount(mint: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.toBuffer()],
+    TOKEN_METADATA_PROGRAM_ID
+  )[0];
+}
+
+// Player record of an NFT mint, naming the one PlayerStats minted for it
+function playerRecord(playerSystemId: PublicKey, mint: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync([Buffer.from("player"), mint.toBuffer()], playerSystemId)[0];
+}
+
+// Test-only keys the game config is initialized with; never used outside tests
+function fixtureKeypair(name: string): Keypair {
+  return Keypair.fromSecretKey(Uint8Array.from(JSON.parse(
+    fs.readFileSync(path.join(__dirname, "fixtures", name), "utf8")
+  )));
+}
+const mintAuthority = fixtureKeypair("mint-authority-keypair.json");
+const oracle = fixtureKeypair("oracle-keypair.json");
+// The provider wallet: upgrade authority, config admin and treasury
+function walletKeypair(): Keypair {
+  return Keypair.fromSecretKey(Buffer.from(JSON.parse(fs.readFileSync(
+    process.env.ANCHOR_WALLET || path.join(process.env.HOME, '.config/solana/id.json'),
+    'utf-8'
+  ))));
+}
+const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111");
+
+// Lamport vault holding a team's stake, owned by the team system
+function stakeVault(teamSystemId: PublicKey, teamDataPda: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("stake_vault"), teamDataPda.toBuffer()],
+    teamSystemId
+  )[0];
+}
+
+// Lamport vault holding a match's entry fees, owned by the match system
+function feeVault(matchSystemId: PublicKey, matchStatePda: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("fee_vault"), matchStatePda.toBuffer()],
+    matchSystemId
+  )[0];
+}
+
+// Pause bits of `esport::pause`
+const PAUSE_SIMULATE_MATCH = 1 << 9;
+
+// Lineup positions, in roster order
+const POSITIONS = ["Top", "Jungle", "Mid", "Carry", "Support"];
+
+// Fail unless `send` fails with the named error
+async function expectError(send: Promise<unknown>, error: string) {
+  try {
+    await send;
+  } catch (e) {
+    const logs = (e.logs ?? []).join("\n");
+    expect(`${e}\n${logs}`).to.include(error);
+    return;
+  }
+  expect.fail(`expected ${error}`);
+}
+
+const writable = (pubkey: PublicKey) => ({ pubkey, isWritable: true, isSigner: false });
+const readonly = (pubkey: PublicKey) => ({ pubkey, isWritable: false, isSigner: false });
+
 describe("5VS5dotGG - Core Gameplay", () => {
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
@@ -48,28 +144,286 @@ describe("5VS5dotGG - Core Gameplay", () => {
   // Test state
   let worldPda: PublicKey;
   let collectionNftMint;
-  
-  // Actor keypairs
+
+  // Actor keypairs: the NFT creator, both team owners and the match operator's crank.
+  // The provider wallet is the config admin and the treasury.
   let creator1: Keypair;
   let player1: Keypair;
-  
-  // NFT mints and signers
-  let creatorPlayer1Mint;
-  let creatorPlayer1Signer;
-  
-  // Entities
-  let creatorPlayer1Entity: PublicKey;
-  let team1Entity: PublicKey;
-  
-  // Component PDAs
-  let player1StatsComponentPda: PublicKey;
-  let team1DataComponentPda: PublicKey;
+  let player2: Keypair;
+  let operator: Keypair;
+
+  // A team: its owner, entity, TeamData and five player NFTs with their entities and PlayerStats
+  type Team = {
+    owner: Keypair;
+    entity: PublicKey;
+    data: PublicKey;
+    mints: PublicKey[];
+    players: PublicKey[];
+    stats: PublicKey[];
+  };
+  let team1: Team;
+  let team2: Team;
+  // Teams of player1's made after team 1 disbands, to fill the match queue
+  let team3: Team;
+  let reserves: Team[] = [];
+  // The team team 1's released first player is on
+  let spareTeam: Team;
+
+  // A scheduled match: its entity and MatchState
+  type Match = { entity: PublicKey; state: PublicKey };
+  let matchQueueEntity: PublicKey;
+  let matchQueuePda: PublicKey;
+  let playedMatch: Match;
 
   // Programs
   const playerStatsComponent = anchor.workspace.PlayerStats;
   const teamDataComponent = anchor.workspace.TeamData;
+  const matchQueueComponent = anchor.workspace.MatchQueue;
+  const matchStateComponent = anchor.workspace.MatchState;
   const matchSystem = anchor.workspace.MatchSystem;
+  const matchmakingSystem = anchor.workspace.MatchmakingSystem;
+  const playerSystem = anchor.workspace.PlayerSystem;
+  const queueSystem = anchor.workspace.QueueSystem;
   const teamSystem = anchor.workspace.TeamSystem;
+  const esportProgram = anchor.workspace.Esport;
+  const [gameConfigPda] = PublicKey.findProgramAddressSync([Buffer.from("game_config")], esportProgram.programId);
+  // Every system takes the game config as its first extra account
+  const configAccount = { pubkey: gameConfigPda, isWritable: false, isSigner: false };
+  // Systems that move lamports check through the instructions sysvar that the world applied them
+  const instructionsSysvar = { pubkey: SYSVAR_INSTRUCTIONS_PUBKEY, isWritable: false, isSigner: false };
+  const systemProgram = readonly(SystemProgram.programId);
+  const treasury = writable(provider.wallet.publicKey);
+  const entryFee = 0.01 * LAMPORTS_PER_SOL;
+
+  // Create an entity and one component on it; returns both addresses
+  async function newComponent(payer: Keypair, componentId: PublicKey) {
+    const entity = await AddEntity({
+      payer: payer.publicKey,
+      world: worldPda,
+      connection: provider.connection,
+    });
+    await provider.sendAndConfirm(entity.transaction, [payer]);
+    const component = await InitializeComponent({
+      payer: payer.publicKey,
+      entity: entity.entityPda,
+      componentId,
+    });
+    await provider.sendAndConfirm(component.transaction, [payer]);
+    return { entity: entity.entityPda, component: component.componentPda };
+  }
+
+  // Run a system action signed by `authority`, one component per entity, after any
+  // `preInstructions` in the same transaction
+  async function apply(
+    authority: Keypair,
+    systemId: PublicKey,
+    components: [PublicKey, PublicKey][],
+    extraAccounts: { pubkey: PublicKey; isWritable: boolean; isSigner: boolean }[],
+    args: object,
+    preInstructions: TransactionInstruction[] = []
+  ) {
+    const system = await ApplySystem({
+      authority: authority.publicKey,
+      systemId,
+      world: worldPda,
+      entities: components.map(([entity, componentId]) => ({
+        entity,
+        components: [{ componentId }],
+      })),
+      extraAccounts: [configAccount, ...extraAccounts],
+      args: JSON.stringify(args),
+    });
+    const transaction = new Transaction().add(...preInstructions, ...system.transaction.instructions);
+    return provider.sendAndConfirm(transaction, [authority]);
+  }
+
+  const teamAction = (team: Team, args: object, extraAccounts = [], player?: PublicKey) =>
+    apply(
+      team.owner,
+      teamSystem.programId,
+      [[team.entity, teamDataComponent.programId], ...(player ? [[player, playerStatsComponent.programId] as [PublicKey, PublicKey]] : [])],
+      extraAccounts,
+      args
+    );
+
+  // The NFT must be held by the team owner, and the stats must be the ones recorded for it
+  const addPlayer = (team: Team, mint: PublicKey, player: PublicKey, position: string) =>
+    teamAction(
+      team,
+      { action: "addPlayerToTeam", playerNftMint: mint.toString(), position },
+      [
+        readonly(associatedTokenAccount(team.owner.publicKey, mint)),
+        readonly(playerRecord(playerSystem.programId, mint)),
+      ],
+      player
+    );
+
+  // A new team with the minimum stake and a strategy, but no players yet
+  async function createTeam(owner: Keypair, name: string): Promise<Team> {
+    const { entity, component } = await newComponent(owner, teamDataComponent.programId);
+    const team = { owner, entity, data: component, mints: [], players: [], stats: [] };
+    await teamAction(team, { action: "createTeam", teamName: name }, [
+      writable(stakeVault(teamSystem.programId, team.data)),
+      systemProgram,
+      instructionsSysvar,
+    ]);
+    await teamAction(team, { action: "setStrategy", strategyType: "teamfight", strategyDescription: `${name} game plan` });
+    return team;
+  }
+
+  // Move the spare player to `team`: challenges and tickets only need a rostered player
+  // when they are made
+  async function lend(team: Team) {
+    if (team === spareTeam) return;
+    await teamAction(
+      spareTeam,
+      { action: "removePlayerFromTeam", playerNftMint: team1.mints[0].toString() },
+      [],
+      team1.players[0]
+    );
+    await addPlayer(team, team1.mints[0], team1.players[0], "Top");
+    spareTeam = team;
+  }
+
+  // queue_system takes the queue, the challenger and the opponent
+  const queueAction = (authority: Keypair, args: object, [challenger, opponent]: [Team, Team] = [team1, team2]) =>
+    apply(
+      authority,
+      queueSystem.programId,
+      [
+        [matchQueueEntity, matchQueueComponent.programId],
+        [challenger.entity, teamDataComponent.programId],
+        [opponent.entity, teamDataComponent.programId],
+      ],
+      [],
+      args
+    );
+
+  // matchmaking_system takes the queue and the team joining or leaving it
+  const matchmakingAction = (authority: Keypair, team: Team, args: object) =>
+    apply(
+      authority,
+      matchmakingSystem.programId,
+      [
+        [matchQueueEntity, matchQueueComponent.programId],
+        [team.entity, teamDataComponent.programId],
+      ],
+      [],
+      args
+    );
+
+  // match_system takes all fourteen components: the queue, the match, both teams and
+  // both five-player lineups
+  const matchAction = (authority: Keypair, m: Match, args: object, extraAccounts = [], preInstructions = []) =>
+    apply(
+      authority,
+      matchSystem.programId,
+      [
+        [matchQueueEntity, matchQueueComponent.programId],
+        [m.entity, matchStateComponent.programId],
+        [team1.entity, teamDataComponent.programId],
+        [team2.entity, teamDataComponent.programId],
+        ...team1.players.map((p): [PublicKey, PublicKey] => [p, playerStatsComponent.programId]),
+        ...team2.players.map((p): [PublicKey, PublicKey] => [p, playerStatsComponent.programId]),
+      ],
+      extraAccounts,
+      args,
+      preInstructions
+    );
+
+  // The owners' token accounts for both lineups, team 1 first
+  const lineupTokenAccounts = () => [
+    ...team1.mints.map((mint) => readonly(associatedTokenAccount(team1.owner.publicKey, mint))),
+    ...team2.mints.map((mint) => readonly(associatedTokenAccount(team2.owner.publicKey, mint))),
+  ];
+
+  const fetchMatch = (m: Match) => matchStateComponent.account.matchState.fetch(m.state);
+  const fetchTeam = (team: Team) => teamDataComponent.account.teamData.fetch(team.data);
+  const fetchQueue = () => matchQueueComponent.account.matchQueue.fetch(matchQueuePda);
+
+  // Game config timeouts, in seconds
+  const setTimeouts = (challenge: number, acceptedMatch: number, reveal: number, ticket: number) =>
+    esportProgram.methods
+      .setTimeouts(new anchor.BN(challenge), new anchor.BN(acceptedMatch), new anchor.BN(reveal), new anchor.BN(ticket))
+      .accounts({ admin: provider.wallet.publicKey })
+      .rpc();
+
+  // Challenge, accept and schedule a new match between the two teams
+  async function scheduleMatch(matchType: string, randomness = "commitReveal"): Promise<Match> {
+    await queueAction(team1.owner, { action: "challenge", matchType });
+    const queue = await fetchQueue();
+    const pendingMatchId = queue.pendingMatches[queue.pendingMatches.length - 1].id.toNumber();
+    await queueAction(team2.owner, { action: "accept", pendingMatchId });
+
+    const { entity, component } = await newComponent(team1.owner, matchStateComponent.programId);
+    const m = { entity, state: component };
+    await matchAction(team1.owner, m, { action: "scheduleMatch", pendingMatchId, randomness });
+    return m;
+  }
+
+  const payEntryFee = (owner: Keypair, m: Match) =>
+    matchAction(owner, m, { action: "payEntryFee" }, [
+      writable(feeVault(matchSystem.programId, m.state)),
+      systemProgram,
+      instructionsSysvar,
+    ]);
+
+  const lockLineups = (m: Match) => matchAction(team1.owner, m, { action: "lockLineups" }, lineupTokenAccounts());
+
+  // Both owners commit, then reveal as many as `reveals` of their seeds
+  async function commitSeeds(m: Match, reveals = 2) {
+    const seeds = [team1.owner, team2.owner].map((owner) => ({ owner, seed: randomBytes(32) }));
+    for (const { owner, seed } of seeds) {
+      await matchAction(owner, m, { action: "commitSeed", seedCommitment: seedCommitment(seed, owner.publicKey) });
+    }
+    for (const { owner, seed } of seeds.slice(0, reveals)) {
+      await matchAction(owner, m, { action: "revealSeed", seed: Array.from(seed) });
+    }
+  }
+
+  // Message the oracle signs for a VRF match, as match_system's `randomness::vrf_message`
+  // builds it: the match, its teams, when the lineups were locked and a hash of them
+  async function vrfMessage(m: Match): Promise<Buffer> {
+    const state = await fetchMatch(m);
+    const lineups = Buffer.concat([
+      ...[...state.team1Lineup, ...state.team2Lineup].map((mint: PublicKey) => mint.toBuffer()),
+      matchStateComponent.coder.types.encode("LockedLineup", state.team1Locked),
+      matchStateComponent.coder.types.encode("LockedLineup", state.team2Locked),
+    ]);
+    const lockedAt = Buffer.alloc(8);
+    lockedAt.writeBigInt64LE(BigInt(state.lockedAt.toString()));
+    return Buffer.concat([
+      Buffer.from("5vs5dotgg:vrf"),
+      m.state.toBuffer(),
+      state.team1.toBuffer(),
+      state.team2.toBuffer(),
+      lockedAt,
+      createHash("sha256").update(lineups).digest(),
+    ]);
+  }
+
+  // Simulate a VRF match after the given Ed25519 precompile instruction
+  const simulateVrf = (m: Match, signature: TransactionInstruction[]) =>
+    matchAction(team1.owner, m, { action: "simulateMatch" }, [...lineupTokenAccounts(), instructionsSysvar], signature);
+
+  // Payees of a played match: the ten lineup metadata accounts, then each one's creator
+  const creatorPayees = () => [
+    ...[...team1.mints, ...team2.mints].map((mint) => readonly(metadataAccount(mint))),
+    ...[...team1.mints, ...team2.mints].map(() => writable(creator1.publicKey)),
+  ];
+  // Payees of a refunded match: both team owners
+  const ownerPayees = () => [writable(team1.owner.publicKey), writable(team2.owner.publicKey)];
+
+  const settleFees = (m: Match, payees) =>
+    matchAction(team1.owner, m, { action: "settleFees" }, [
+      writable(feeVault(matchSystem.programId, m.state)),
+      systemProgram,
+      treasury,
+      instructionsSysvar,
+      ...payees,
+    ]);
+
+  const balance = (key: PublicKey) => provider.connection.getBalance(key);
 
   before(async () => {
     // Setup Umi
@@ -78,23 +432,27 @@ describe("5VS5dotGG - Core Gameplay", () => {
       .use(irysUploader({
         address: "https://devnet.irys.xyz",
       }));
-    
+
     // Setup keypairs
     creator1 = Keypair.generate();
     player1 = Keypair.generate();
-    
+    player2 = Keypair.generate();
+    operator = Keypair.generate();
+
     // Convert keypairs to Umi signers using the helper function
     const creator1Signer = solanaKeypairToUmiSigner(umi, creator1);
-    const player1Signer = solanaKeypairToUmiSigner(umi, player1);
-    
+
     // Airdrop SOL
     await Promise.all([
       provider.connection.requestAirdrop(creator1.publicKey, 5 * anchor.web3.LAMPORTS_PER_SOL),
       provider.connection.requestAirdrop(player1.publicKey, 5 * anchor.web3.LAMPORTS_PER_SOL),
+      provider.connection.requestAirdrop(player2.publicKey, 5 * anchor.web3.LAMPORTS_PER_SOL),
+      provider.connection.requestAirdrop(operator.publicKey, 1 * anchor.web3.LAMPORTS_PER_SOL),
+      // The mint authority pays for the player records it creates
+      provider.connection.requestAirdrop(mintAuthority.publicKey, 1 * anchor.web3.LAMPORTS_PER_SOL),
       umi.rpc.airdrop(creator1Signer.publicKey, sol(2)),
-      umi.rpc.airdrop(player1Signer.publicKey, sol(2)),
     ]);
-    
+
     // Wait for confirmations
     await new Promise(resolve => setTimeout(resolve, 3000));
   });
@@ -110,23 +468,15 @@ describe("5VS5dotGG - Core Gameplay", () => {
   });
 
   it("Create NFT Collection", async () => {
-    // Get wallet keypair from file for admin operations
-    const walletKeypairData = JSON.parse(fs.readFileSync(
-      process.env.ANCHOR_WALLET || path.join(process.env.HOME, '.config/solana/id.json'), 
-      'utf-8'
-    ));
-    
-    const walletKeypair = Keypair.fromSecretKey(Buffer.from(walletKeypairData));
-    
     // Convert admin keypair to Umi format
-    const adminUmiSigner = solanaKeypairToUmiSigner(umi, walletKeypair);
-    
+    const adminUmiSigner = solanaKeypairToUmiSigner(umi, walletKeypair());
+
     // Use Admin for the Umi instance
     const adminUmi = umi.use(signerIdentity(adminUmiSigner));
-    
+
     // Create collection NFT signer
     const collectionNftSigner = generateSigner(adminUmi);
-    
+
     // Create sample metadata for collection
     const collectionMetadata = {
       name: "5VS5dotGG Collection",
@@ -134,10 +484,10 @@ describe("5VS5dotGG - Core Gameplay", () => {
       image: "https://arweave.net/placeholder-collection-uri",
       external_url: "https://5vs5dotgg.io",
     };
-    
+
     // Upload metadata
     const collectionMetadataUri = await adminUmi.uploader.uploadJson(collectionMetadata);
-    
+
     // Create collection NFT
     const createCollectionTx = await createProgrammableNft(adminUmi, {
       mint: collectionNftSigner,
@@ -146,73 +496,105 @@ describe("5VS5dotGG - Core Gameplay", () => {
       sellerFeeBasisPoints: percentAmount(5),
       isCollection: true,
     }).sendAndConfirm(adminUmi);
-    
+
     collectionNftMint = collectionNftSigner.publicKey;
     console.log(`Created NFT Collection: ${collectionNftMint}`);
   });
 
-  it("Creator creates NFT player", async () => {
-    // Setup Umi with creator identity
+  it("Initialize game config", async () => {
+    // Only the upgrade authority (the provider wallet) can create the config, which also
+    // makes it the admin and the treasury
+    const [programData] = PublicKey.findProgramAddressSync(
+      [esportProgram.programId.toBuffer()],
+      BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+    );
+    await esportProgram.methods
+      .initialize(
+        provider.wallet.publicKey,
+        new anchor.BN(entryFee),
+        oracle.publicKey,
+        new PublicKey(collectionNftMint.toString()),
+        mintAuthority.publicKey
+      )
+      .accounts({ admin: provider.wallet.publicKey, programData })
+      .rpc();
+
+    // The operator's crank may simulate any match
+    await esportProgram.methods
+      .setMatchOperator(operator.publicKey)
+      .accounts({ admin: provider.wallet.publicKey })
+      .rpc();
+
+    const config = await esportProgram.account.gameConfig.fetch(gameConfigPda);
+    expect(config.creatorShareBps).to.equal(6000);
+    expect(config.platformShareBps).to.equal(4000);
+    expect(config.entryFee.toNumber()).to.equal(entryFee);
+    expect(config.playerCollection.toString()).to.equal(collectionNftMint.toString());
+    expect(config.matchOperator.toString()).to.equal(operator.publicKey.toString());
+  });
+
+  it("Creator creates NFT players for both team owners", async () => {
+    const adminUmiSigner = solanaKeypairToUmiSigner(umi, walletKeypair());
     const creator1Signer = solanaKeypairToUmiSigner(umi, creator1);
-    const creatorUmi = umi.use(signerIdentity(creator1Signer));
-    
-    // Create NFT signer
-    creatorPlayer1Signer = generateSigner(creatorUmi);
-    
-    // Prepare sample metadata
-    const playerMetadata = {
-      name: "Pro Midlaner #1",
-      description: "5VS5dotGG Pro Player NFT - Midlane Specialist",
-      image: "https://arweave.net/placeholder-player-uri",
-      external_url: "https://5vs5dotgg.io/players/1",
-      attributes: [
-        { trait_type: "Position", value: "Midlane" },
-        { trait_type: "Mechanical", value: "90" },
-        { trait_type: "Game Knowledge", value: "85" },
-        { trait_type: "Team Communication", value: "80" },
-        { trait_type: "Adaptability", value: "75" },
-        { trait_type: "Consistency", value: "85" },
-      ],
+
+    // Each owner gets five pNFTs of the verified player collection, created by creator1
+    const createPlayers = async (owner: Keypair, label: string) => {
+      const mints: PublicKey[] = [];
+      for (const [index, position] of POSITIONS.entries()) {
+        const mint = generateSigner(umi);
+        umi.use(signerIdentity(creator1Signer));
+        await createProgrammableNft(umi, {
+          mint,
+          name: `${label} ${position} #${index + 1}`,
+          uri: `https://5vs5dotgg.io/players/${label.toLowerCase()}-${index + 1}.json`,
+          sellerFeeBasisPoints: percentAmount(10),
+          collection: some({ key: collectionNftMint, verified: false }),
+          tokenOwner: umiPublicKey(owner.publicKey.toString()),
+        }).sendAndConfirm(umi);
+
+        // The collection's update authority verifies the NFT
+        umi.use(signerIdentity(adminUmiSigner));
+        await verifyCollectionV1(umi, {
+          metadata: findMetadataPda(umi, { mint: mint.publicKey }),
+          collectionMint: collectionNftMint,
+          authority: adminUmiSigner,
+        }).sendAndConfirm(umi);
+
+        mints.push(new PublicKey(mint.publicKey.toString()));
+      }
+      return mints;
     };
-    
-    // Upload metadata
-    const playerMetadataUri = await creatorUmi.uploader.uploadJson(playerMetadata);
-    
-    // Create programmable NFT
-    const createNftTx = await createProgrammableNft(creatorUmi, {
-      mint: creatorPlayer1Signer,
-      name: playerMetadata.name,
-      uri: playerMetadataUri,
-      sellerFeeBasisPoints: percentAmount(10),
-      collection: collectionNftMint,
-    }).sendAndConfirm(creatorUmi);
-    
-    creatorPlayer1Mint = creatorPlayer1Signer.publicKey;
-    console.log(`Creator created NFT player: ${creatorPlayer1Mint}`);
-    
-    // Create Bolt entity for player NFT
-    const entity1 = await AddEntity({
-      payer: creator1.publicKey,
-      world: worldPda,
-      connection: provider.connection,
-    });
-    
-    creatorPlayer1Entity = entity1.entityPda;
-    
-    // Initialize player stats component
-    const initStats1 = await InitializeComponent({
-      payer: creator1.publicKey,
-      entity: creatorPlayer1Entity,
-      componentId: playerStatsComponent.programId,
-    });
-    
-    player1StatsComponentPda = initStats1.componentPda;
-    
-    // Set player stats in Bolt
-    await playerStatsComponent.methods
-      .initializeStats({
-        nftMint: new PublicKey(creatorPlayer1Mint.toString()),
-        role: "Midlaner",
+
+    team1 = { owner: player1, entity: null, data: null, mints: await createPlayers(player1, "Dragon"), players: [], stats: [] };
+    team2 = { owner: player2, entity: null, data: null, mints: await createPlayers(player2, "Mock"), players: [], stats: [] };
+
+    console.log(`Created ${team1.mints.length + team2.mints.length} player NFTs`);
+  });
+
+  it("Mint authority and holders mint the players", async () => {
+    const mintPlayer = async (team: Team, authority: Keypair, mint: PublicKey, attributes: object, holderAccounts) => {
+      const { entity, component } = await newComponent(authority, playerStatsComponent.programId);
+      await apply(
+        authority,
+        playerSystem.programId,
+        [[entity, playerStatsComponent.programId]],
+        [
+          readonly(metadataAccount(mint)),
+          writable(playerRecord(playerSystem.programId, mint)),
+          systemProgram,
+          instructionsSysvar,
+          ...holderAccounts,
+        ],
+        { action: "mintPlayer", playerNftMint: mint.toString(), ...attributes }
+      );
+      team.players.push(entity);
+      team.stats.push(component);
+    };
+
+    // The mint authority picks team 1's attributes and doesn't need to hold the NFTs
+    for (const [index, mint] of team1.mints.entries()) {
+      await mintPlayer(team1, mintAuthority, mint, {
+        role: POSITIONS[index],
         mechanical: 90,
         gameKnowledge: 85,
         teamCommunication: 80,
@@ -220,251 +602,714 @@ describe("5VS5dotGG - Core Gameplay", () => {
         consistency: 85,
         form: 90,
         potential: 95,
-      })
-      .accounts({
-        component: player1StatsComponentPda,
-        authority: creator1.publicKey,
-      })
-      .signers([creator1])
-      .rpc();
-    
-    console.log("Created NFT player entity in Bolt");
+      }, []);
+    }
+
+    // Team 2's holder mints its own players, with attributes rolled from the NFTs
+    for (const mint of team2.mints) {
+      await mintPlayer(team2, player2, mint, {}, [
+        readonly(associatedTokenAccount(player2.publicKey, mint)),
+      ]);
+    }
+
+    const stats = await playerStatsComponent.account.playerStats.fetch(team1.stats[0]);
+    expect(stats.nftMint.toString()).to.equal(team1.mints[0].toString());
+    expect(stats.mechanical).to.equal(90);
+    expect(stats.role).to.equal("Top");
+
+    const rolled = await playerStatsComponent.account.playerStats.fetch(team2.stats[0]);
+    expect(rolled.nftMint.toString()).to.equal(team2.mints[0].toString());
   });
 
-  it("Transfer NFT to player", async () => {
-    // Setup creator and player Umi instances
-    const creator1Signer = solanaKeypairToUmiSigner(umi, creator1);
-    const creatorUmi = umi.use(signerIdentity(creator1Signer));
-    
-    // Transfer NFT to player
-    const transferInstructions = await creatorUmi.programs.getTransferPnft({
-      mint: creatorPlayer1Mint,
-      authority: creator1Signer.publicKey,
-      destination: umiPublicKey(player1.publicKey.toString()),
-      amount: 1,
-    });
-    
-    // Execute transfer
-    await transferInstructions.sendAndConfirm(creatorUmi);
-    
-    console.log(`NFT transferred to player: ${player1.publicKey.toString()}`);
+  it("Refuses a second PlayerStats for the same NFT", async () => {
+    const mint = team1.mints[0];
+    const { entity } = await newComponent(player1, playerStatsComponent.programId);
+
+    await expectError(
+      apply(
+        player1,
+        playerSystem.programId,
+        [[entity, playerStatsComponent.programId]],
+        [
+          readonly(metadataAccount(mint)),
+          writable(playerRecord(playerSystem.programId, mint)),
+          systemProgram,
+          instructionsSysvar,
+          readonly(associatedTokenAccount(player1.publicKey, mint)),
+        ],
+        { action: "mintPlayer", playerNftMint: mint.toString() }
+      ),
+      "PlayerAlreadyMinted"
+    );
   });
 
-  it("Player creates team", async () => {
-    // Create team entity
-    const teamEntity = await AddEntity({
-      payer: player1.publicKey,
-      world: worldPda,
-      connection: provider.connection,
-    });
-    
-    team1Entity = teamEntity.entityPda;
-    
-    // Initialize team data component
-    const initTeamData = await InitializeComponent({
-      payer: player1.publicKey,
-      entity: team1Entity,
-      componentId: teamDataComponent.programId,
-    });
-    
-    team1DataComponentPda = initTeamData.componentPda;
-    
-    // Create team (without staking)
-    const createTeam = await ApplySystem({
-      authority: player1.publicKey,
-      systemId: teamSystem.programId,
-      world: worldPda,
-      entities: [
-        {
-          entity: team1Entity,
-          components: [{ componentId: teamDataComponent.programId }],
-        }
-      ],
-      args: JSON.stringify({
-        action: "createTeam",
-        teamName: "Dragon Slayers",
-      })
-    });
-    
-    await provider.sendAndConfirm(createTeam.transaction, [player1]);
-    
-    // Verify team creation
-    const teamData = await teamDataComponent.account.teamData.fetch(
-      team1DataComponentPda
+  it("Owners create teams and add their players", async () => {
+    for (const [team, name, strategyType] of [
+      [team1, "Dragon Slayers", "aggressive"],
+      [team2, "Mock Opponent", "defensive"],
+    ] as [Team, string, string][]) {
+      const { entity, component } = await newComponent(team.owner, teamDataComponent.programId);
+      team.entity = entity;
+      team.data = component;
+
+      // Stake 0.1 SOL into the team's stake vault
+      await teamAction(team, { action: "createTeam", teamName: name, stakeAmount: 0.1 * LAMPORTS_PER_SOL }, [
+        writable(stakeVault(teamSystem.programId, team.data)),
+        systemProgram,
+        instructionsSysvar,
+      ]);
+
+      for (const [index, mint] of team.mints.entries()) {
+        await addPlayer(team, mint, team.players[index], POSITIONS[index]);
+      }
+
+      await teamAction(team, { action: "setStrategy", strategyType, strategyDescription: `${name} game plan` });
+
+      const teamData = await fetchTeam(team);
+      expect(teamData.name).to.equal(name);
+      expect(teamData.stakeAmount.toNumber()).to.equal(0.1 * LAMPORTS_PER_SOL);
+      expect(teamData.roster.length).to.equal(5);
+    }
+
+    // Shared match queue
+    const queue = await newComponent(player1, matchQueueComponent.programId);
+    matchQueueEntity = queue.entity;
+    matchQueuePda = queue.component;
+  });
+
+  it("Matchmaking pairs the two teams and schedules their match", async () => {
+    // Both teams start at the same rating, well inside each other's window
+    await matchmakingAction(team1.owner, team1, { action: "enqueue", matchType: "friendly-bo3" });
+    await expectError(
+      matchmakingAction(team1.owner, team1, { action: "enqueue", matchType: "friendly-bo3" }),
+      "AlreadyQueued"
     );
-    
-    expect(teamData.name).to.equal("Dragon Slayers");
-    
-    console.log(`Team created: ${teamData.name}`);
+    await expectError(matchmakingAction(team1.owner, team2, { action: "enqueue", matchType: "friendly-bo3" }), "NotTeamOwner");
+    await matchmakingAction(team2.owner, team2, { action: "enqueue", matchType: "friendly-bo3" });
+    expect((await fetchQueue()).tickets.length).to.equal(2);
+
+    // Anyone can run the crank; the older ticket's team is team 1
+    await matchmakingAction(operator, team1, { action: "matchmake" });
+    const queue = await fetchQueue();
+    expect(queue.tickets.length).to.equal(0);
+    const pending = queue.pendingMatches[queue.pendingMatches.length - 1];
+    expect(pending.status).to.have.property("accepted");
+    expect(pending.team1.toString()).to.equal(team1.data.toString());
+    expect(pending.team2.toString()).to.equal(team2.data.toString());
+    expect(pending.matchType).to.equal("friendly-bo3");
+
+    // The pair is already accepted, so it is scheduled right away
+    const pendingMatchId = pending.id.toNumber();
+    const { entity, component } = await newComponent(team1.owner, matchStateComponent.programId);
+    playedMatch = { entity, state: component };
+    await matchAction(team1.owner, playedMatch, { action: "scheduleMatch", pendingMatchId });
+    const scheduled = await fetchQueue();
+    expect(scheduled.pendingMatches.map((m) => m.id.toNumber())).to.not.include(pendingMatchId);
+
+    const matchState = await fetchMatch(playedMatch);
+    expect(matchState.status).to.have.property("scheduled");
+    expect(matchState.pendingMatchId.toNumber()).to.equal(pendingMatchId);
   });
 
-  it("Player adds NFT to team", async () => {
-    // Add owned NFT to team
-    const addPlayer = await ApplySystem({
-      authority: player1.publicKey,
-      systemId: teamSystem.programId,
-      world: worldPda,
-      entities: [
-        {
-          entity: team1Entity,
-          components: [{ componentId: teamDataComponent.programId }],
-        },
-        {
-          entity: creatorPlayer1Entity,
-          components: [{ componentId: playerStatsComponent.programId }],
-        }
-      ],
-      args: JSON.stringify({
-        action: "addPlayerToTeam",
-        playerNftMint: new PublicKey(creatorPlayer1Mint.toString()),
-        position: "Midlaner",
-      })
-    });
-    
-    await provider.sendAndConfirm(addPlayer.transaction, [player1]);
-    
-    // Verify player added
-    const teamData = await teamDataComponent.account.teamData.fetch(
-      team1DataComponentPda
+  it("Owners pay the entry fees", async () => {
+    // Nothing can be committed or consented to before the lineups are locked
+    await expectError(
+      matchAction(team1.owner, playedMatch, { action: "commitSeed", seedCommitment: seedCommitment(randomBytes(32), team1.owner.publicKey) }),
+      "InvalidStatus"
     );
-    
-    expect(teamData.roster.length).to.equal(1);
-    expect(teamData.roster[0].nftMint.toString()).to.equal(creatorPlayer1Mint.toString());
-    
-    console.log("Player added to team");
-  });
-
-  it("Select strategy and schedule match", async () => {
-    // Select strategy
-    const selectStrategy = await ApplySystem({
-      authority: player1.publicKey,
-      systemId: teamSystem.programId,
-      world: worldPda,
-      entities: [{
-        entity: team1Entity,
-        components: [{ componentId: teamDataComponent.programId }],
-      }],
-      args: JSON.stringify({
-        action: "setStrategy",
-        strategy: {
-          type: "Aggro",
-          description: "Punch fast and hard early",
-        }
-      })
-    });
-    
-    await provider.sendAndConfirm(selectStrategy.transaction, [player1]);
-    
-    // For the sake of the test, let's create a mock opponent team
-    const opponentTeam = await AddEntity({
-      payer: provider.wallet.publicKey,
-      world: worldPda,
-      connection: provider.connection,
-    });
-    
-    const initOpponentTeam = await InitializeComponent({
-      payer: provider.wallet.publicKey,
-      entity: opponentTeam.entityPda,
-      componentId: teamDataComponent.programId,
-    });
-    
-    await teamDataComponent.methods
-      .initialize({
-        name: "Mock Opponent",
-        owner: provider.wallet.publicKey,
-      })
-      .accounts({
-        component: initOpponentTeam.componentPda,
-        authority: provider.wallet.publicKey,
-      })
+    await expectError(matchAction(team2.owner, playedMatch, { action: "consentMatch" }), "InvalidStatus");
+
+    // Lineups lock only once both fees are paid
+    await payEntryFee(team1.owner, playedMatch);
+    await expectError(lockLineups(playedMatch), "FeesNotPaid");
+    await payEntryFee(team2.owner, playedMatch);
+
+    const matchState = await fetchMatch(playedMatch);
+    expect(matchState.status).to.have.property("scheduled");
+    expect(matchState.team1FeePaid).to.equal(true);
+    expect(matchState.team2FeePaid).to.equal(true);
+    expect(await balance(feeVault(matchSystem.programId, playedMatch.state))).to.equal(2 * entryFee);
+  });
+
+  it("Lock lineups", async () => {
+    await lockLineups(playedMatch);
+
+    const matchState = await fetchMatch(playedMatch);
+    expect(matchState.status).to.have.property("lineupsLocked");
+    expect(matchState.team1Lineup.map((m) => m.toString())).to.deep.equal(team1.mints.map((m) => m.toString()));
+    expect(matchState.team1Locked.players[0].attributes[0]).to.equal(90);
+
+    // Rosters and strategies are frozen while the match is locked, and neither team can
+    // disband while it is open
+    await expectError(
+      teamAction(team1, { action: "setStrategy", strategyType: "defensive", strategyDescription: "Too late" }),
+      "MatchLocked"
+    );
+    await expectError(
+      teamAction(team1, { action: "removePlayerFromTeam", playerNftMint: team1.mints[0].toString() }, [], team1.players[0]),
+      "MatchLocked"
+    );
+    await expectError(
+      teamAction(team1, { action: "disbandTeam" }, [
+        writable(stakeVault(teamSystem.programId, team1.data)),
+        systemProgram,
+        treasury,
+        instructionsSysvar,
+      ]),
+      "OpenMatches"
+    );
+    const teamData = await fetchTeam(team1);
+    expect(teamData.lockedMatches).to.equal(1);
+    expect(teamData.openMatches).to.equal(1);
+  });
+
+  it("Only team 2's owner can consent", async () => {
+    await expectError(matchAction(team1.owner, playedMatch, { action: "consentMatch" }), "NotOpponentOwner");
+    await matchAction(team2.owner, playedMatch, { action: "consentMatch" });
+    await expectError(matchAction(team2.owner, playedMatch, { action: "consentMatch" }), "AlreadyConsented");
+
+    const matchState = await fetchMatch(playedMatch);
+    expect(matchState.team2Consented).to.equal(true);
+  });
+
+  it("Owners commit and reveal match seeds", async () => {
+    await commitSeeds(playedMatch);
+
+    const matchState = await fetchMatch(playedMatch);
+    expect(matchState.status).to.have.property("inProgress");
+    expect(matchState.team1Seed.revealed).to.equal(true);
+    expect(matchState.team2Seed.revealed).to.equal(true);
+  });
+
+  it("Paused simulations are refused", async () => {
+    await esportProgram.methods
+      .setPausedActions(new anchor.BN(PAUSE_SIMULATE_MATCH))
+      .accounts({ admin: provider.wallet.publicKey })
       .rpc();
-    
-    // Schedule match
-    const scheduleMatch = await ApplySystem({
-      authority: player1.publicKey,
-      systemId: matchSystem.programId,
-      world: worldPda,
-      entities: [
-        {
-          entity: team1Entity,
-          components: [{ componentId: teamDataComponent.programId }],
-        },
-        {
-          entity: opponentTeam.entityPda,
-          components: [{ componentId: teamDataComponent.programId }],
-        }
-      ],
-      args: JSON.stringify({
-        action: "scheduleMatch",
-        matchType: "friendly",
-      })
-    });
-    
-    await provider.sendAndConfirm(scheduleMatch.transaction, [player1]);
-    console.log("Strategy selected and match scheduled");
-  });
-
-  it("Simulate match", async () => {
-    // Simulate the previously scheduled match
-    const matchId = "match-" + Date.now().toString();
-    
-    const simulateMatch = await ApplySystem({
-      authority: provider.wallet.publicKey,
-      systemId: matchSystem.programId,
-      world: worldPda,
-      entities: [
-        {
-          entity: team1Entity,
-          components: [{ componentId: teamDataComponent.programId }],
-        }
-      ],
-      args: JSON.stringify({
-        action: "simulateMatch",
-        matchId: matchId,
-      })
+
+    await expectError(
+      matchAction(operator, playedMatch, { action: "simulateMatch" }, lineupTokenAccounts()),
+      "ActionPaused"
+    );
+
+    await esportProgram.methods
+      .setPausedActions(new anchor.BN(0))
+      .accounts({ admin: provider.wallet.publicKey })
+      .rpc();
+  });
+
+  it("Match operator simulates the match", async () => {
+    // Team 2's owner can't run the simulation
+    await expectError(
+      matchAction(team2.owner, playedMatch, { action: "simulateMatch" }, lineupTokenAccounts()),
+      "NotTeamOwnerOrOperator"
+    );
+
+    await matchAction(operator, playedMatch, { action: "simulateMatch" }, lineupTokenAccounts());
+
+    const matchState = await fetchMatch(playedMatch);
+    expect(matchState.status).to.have.property("finished");
+
+    // Both histories record the match under the same key
+    const teamData = await fetchTeam(team1);
+    expect(teamData.matchHistory.length).to.equal(1);
+    const latestMatch = teamData.matchHistory[teamData.matchHistory.length - 1];
+    expect(latestMatch.matchKey.toString()).to.equal(playedMatch.state.toString());
+    expect(Math.max(latestMatch.teamScore, latestMatch.opponentScore)).to.equal(2);
+    expect(teamData.lockedMatches).to.equal(0);
+    expect(teamData.openMatches).to.equal(0);
+
+    const opponentData = await fetchTeam(team2);
+    expect(opponentData.matchHistory[0].matchKey.toString()).to.equal(playedMatch.state.toString());
+    expect(opponentData.matchHistory[0].win).to.equal(!latestMatch.win);
+
+    // A finished match's fees wait for the dispute window
+    await expectError(settleFees(playedMatch, creatorPayees()), "DisputeWindowOpen");
+
+    console.log(`Match simulated: ${latestMatch.win ? "Won" : "Lost"} with score ${latestMatch.teamScore}-${latestMatch.opponentScore}`);
+  });
+
+  it("Matchmaking keeps teams outside each other's rating window apart", async () => {
+    // Fresh tickets accept a 50 point difference, widening by 25 a minute up to 400; the
+    // simulated match moved the ratings further apart than that
+    const rating1 = (await fetchTeam(team1)).rating;
+    const rating2 = (await fetchTeam(team2)).rating;
+    expect(Math.abs(rating1 - rating2)).to.be.gt(50);
+
+    await matchmakingAction(team1.owner, team1, { action: "enqueue", matchType: "friendly" });
+    await matchmakingAction(team2.owner, team2, { action: "enqueue", matchType: "friendly" });
+    const nextMatchId = (await fetchQueue()).nextMatchId.toNumber();
+    await matchmakingAction(operator, team1, { action: "matchmake" });
+
+    // No match was made and both teams are still waiting, at the ratings they queued with
+    const queue = await fetchQueue();
+    expect(queue.nextMatchId.toNumber()).to.equal(nextMatchId);
+    expect(queue.tickets.map((t) => t.team.toString())).to.deep.equal([team1.data.toString(), team2.data.toString()]);
+    expect(queue.tickets.map((t) => t.rating)).to.deep.equal([rating1, rating2]);
+
+    // Owners leave the queue
+    await expectError(matchmakingAction(team2.owner, team1, { action: "leave" }), "NotTeamOwner");
+    await matchmakingAction(team1.owner, team1, { action: "leave" });
+    await matchmakingAction(team2.owner, team2, { action: "leave" });
+    await expectError(matchmakingAction(team1.owner, team1, { action: "leave" }), "NotQueued");
+    expect((await fetchQueue()).tickets.length).to.equal(0);
+  });
+
+  it("Matchmaking tickets expire", async () => {
+    // Tickets wait a second for an opponent
+    await setTimeouts(24 * 60 * 60, 24 * 60 * 60, 60 * 60, 1);
+    await matchmakingAction(team1.owner, team1, { action: "enqueue", matchType: "friendly" });
+    await new Promise(resolve => setTimeout(resolve, 3000));
+
+    // Anyone can expire the stale ticket
+    await queueAction(operator, { action: "expire" });
+    expect((await fetchQueue()).tickets.length).to.equal(0);
+
+    // The crank prunes stale tickets before pairing
+    await matchmakingAction(team1.owner, team1, { action: "enqueue", matchType: "friendly" });
+    await matchmakingAction(team2.owner, team2, { action: "enqueue", matchType: "friendly" });
+    await new Promise(resolve => setTimeout(resolve, 3000));
+    const nextMatchId = (await fetchQueue()).nextMatchId.toNumber();
+    await matchmakingAction(operator, team1, { action: "matchmake" });
+    const queue = await fetchQueue();
+    expect(queue.tickets.length).to.equal(0);
+    expect(queue.nextMatchId.toNumber()).to.equal(nextMatchId);
+
+    await setTimeouts(24 * 60 * 60, 24 * 60 * 60, 60 * 60, 60 * 60);
+  });
+
+  it("Forfeit before lineups are locked refunds the fees", async () => {
+    const m = await scheduleMatch("friendly");
+    await payEntryFee(team1.owner, m);
+    await payEntryFee(team2.owner, m);
+
+    await matchAction(team2.owner, m, { action: "forfeit" });
+
+    const matchState = await fetchMatch(m);
+    expect(matchState.status).to.have.property("forfeited");
+    expect(matchState.winner.toString()).to.equal(team1.data.toString());
+
+    // Fees of a match that never locked its lineups go back to the owners
+    const team2Before = await balance(team2.owner.publicKey);
+    await settleFees(m, ownerPayees());
+    expect(await balance(team2.owner.publicKey)).to.equal(team2Before + entryFee);
+    expect(await balance(feeVault(matchSystem.programId, m.state))).to.equal(0);
+    await expectError(settleFees(m, ownerPayees()), "FeesAlreadySettled");
+
+    const teamData = await fetchTeam(team2);
+    expect(teamData.penalties.toNumber()).to.be.gt(0);
+  });
+
+  it("An owner who revealed claims the forfeit of one who didn't", async () => {
+    const m = await scheduleMatch("friendly");
+    await payEntryFee(team1.owner, m);
+    await payEntryFee(team2.owner, m);
+    await lockLineups(m);
+
+    // Only team 1 reveals
+    await commitSeeds(m, 1);
+    await expectError(matchAction(team1.owner, m, { action: "claimForfeit" }), "RevealWindowOpen");
+
+    // Shorten the reveal timeout to a second and let it pass
+    await setTimeouts(24 * 60 * 60, 24 * 60 * 60, 1, 60 * 60);
+    await new Promise(resolve => setTimeout(resolve, 3000));
+
+    await expectError(matchAction(team2.owner, m, { action: "claimForfeit" }), "OpponentNotStalled");
+    await matchAction(team1.owner, m, { action: "claimForfeit" });
+
+    const matchState = await fetchMatch(m);
+    expect(matchState.status).to.have.property("forfeited");
+    expect(matchState.winner.toString()).to.equal(team1.data.toString());
+
+    // The locked NFTs played, so their creators get their share
+    const creatorBefore = await balance(creator1.publicKey);
+    await settleFees(m, creatorPayees());
+    expect(await balance(creator1.publicKey)).to.equal(creatorBefore + (2 * entryFee * 6000) / 10000);
+    expect(await balance(feeVault(matchSystem.programId, m.state))).to.equal(0);
+
+    const teamData = await fetchTeam(team1);
+    expect(teamData.lockedMatches).to.equal(0);
+    expect(teamData.openMatches).to.equal(0);
+  });
+
+  // The reveal timeout is still a second, so consent and commitments are due a second
+  // after the lineups lock
+  it("An owner claims a match the opponent never consented to", async () => {
+    const m = await scheduleMatch("friendly");
+    await payEntryFee(team1.owner, m);
+    await payEntryFee(team2.owner, m);
+    await lockLineups(m);
+
+    // Team 1 commits; team 2 neither consents nor commits
+    await matchAction(team1.owner, m, {
+      action: "commitSeed",
+      seedCommitment: seedCommitment(randomBytes(32), team1.owner.publicKey),
     });
-    
-    await provider.sendAndConfirm(simulateMatch.transaction);
-    
-    // Verify match was recorded
-    const teamData = await teamDataComponent.account.teamData.fetch(
-      team1DataComponentPda
+    await new Promise(resolve => setTimeout(resolve, 3000));
+
+    const penaltiesBefore = (await fetchTeam(team2)).penalties.toNumber();
+    await expectError(matchAction(team2.owner, m, { action: "claimForfeit" }), "OpponentNotStalled");
+    await matchAction(team1.owner, m, { action: "claimForfeit" });
+
+    const matchState = await fetchMatch(m);
+    expect(matchState.status).to.have.property("forfeited");
+    expect(matchState.winner.toString()).to.equal(team1.data.toString());
+    expect((await fetchTeam(team2)).penalties.toNumber()).to.be.gt(penaltiesBefore);
+
+    // Neither team is held by the match any more
+    for (const team of [team1, team2]) {
+      const teamData = await fetchTeam(team);
+      expect(teamData.lockedMatches).to.equal(0);
+      expect(teamData.openMatches).to.equal(0);
+    }
+    await settleFees(m, creatorPayees());
+  });
+
+  it("A match neither owner prepared in time is voided and refunded", async () => {
+    const m = await scheduleMatch("friendly");
+    await payEntryFee(team1.owner, m);
+    await payEntryFee(team2.owner, m);
+    await lockLineups(m);
+    await new Promise(resolve => setTimeout(resolve, 3000));
+
+    const team1Data = await fetchTeam(team1);
+    await matchAction(team2.owner, m, { action: "claimForfeit" });
+
+    const matchState = await fetchMatch(m);
+    expect(matchState.status).to.have.property("voided");
+    const teamData = await fetchTeam(team1);
+    expect(teamData.rating).to.equal(team1Data.rating);
+    expect(teamData.openMatches).to.equal(0);
+
+    const team2Before = await balance(team2.owner.publicKey);
+    await settleFees(m, ownerPayees());
+    expect(await balance(team2.owner.publicKey)).to.equal(team2Before + entryFee);
+    expect(await balance(feeVault(matchSystem.programId, m.state))).to.equal(0);
+  });
+
+  it("A VRF match is simulated from the oracle's signature", async () => {
+    const m = await scheduleMatch("friendly", "vrf");
+    await payEntryFee(team1.owner, m);
+    await payEntryFee(team2.owner, m);
+    await lockLineups(m);
+    await matchAction(team2.owner, m, { action: "consentMatch" });
+    await expectError(
+      matchAction(team1.owner, m, { action: "commitSeed", seedCommitment: seedCommitment(randomBytes(32), team1.owner.publicKey) }),
+      "WrongRandomnessSource"
     );
-    
-    expect(teamData.match_history.length).to.be.gt(0);
-    const latestMatch = teamData.match_history[teamData.match_history.length - 1];
-    expect(latestMatch.match_id).to.equal(matchId);
-    
-    console.log(`Match simulated: ${latestMatch.win ? "Won" : "Lost"} with score ${latestMatch.team_score}-${latestMatch.opponent_score}`);
+
+    const message = await vrfMessage(m);
+    const sign = (privateKey: Uint8Array, signed = message) =>
+      Ed25519Program.createInstructionWithPrivateKey({ privateKey, message: signed });
+
+    await expectError(simulateVrf(m, []), "MissingOracleSignature");
+    // Signed by someone other than the config's oracle
+    await expectError(simulateVrf(m, [sign(Keypair.generate().secretKey)]), "InvalidOracleSignature");
+    // The oracle's signature over another match's message
+    const otherMatch = Buffer.from(message);
+    otherMatch[Buffer.from("5vs5dotgg:vrf").length] ^= 1;
+    await expectError(simulateVrf(m, [sign(oracle.secretKey, otherMatch)]), "InvalidOracleSignature");
+    // A tampered signature fails the precompile before the system runs
+    const tampered = sign(oracle.secretKey);
+    tampered.data[48] ^= 1;
+    await expectError(simulateVrf(m, [tampered]), "precompile verification failure");
+
+    const signature = sign(oracle.secretKey);
+    await simulateVrf(m, [signature]);
+
+    // The match seed is derived from the signature, which sits after the header and public key
+    const matchState = await fetchMatch(m);
+    expect(matchState.status).to.have.property("finished");
+    const seed = createHash("sha256")
+      .update(Buffer.from("5vs5dotgg:match-seed"))
+      .update(m.state.toBuffer())
+      .update(signature.data.subarray(48, 112))
+      .digest();
+    expect(Buffer.from(matchState.seed).equals(seed)).to.be.true;
+  });
+
+  // The reveal timeout is still a second, so team 1 has a second to simulate once team 2 consents
+  it("Team 2 claims a VRF match team 1 didn't simulate in time", async () => {
+    const m = await scheduleMatch("friendly", "vrf");
+    await payEntryFee(team1.owner, m);
+    await payEntryFee(team2.owner, m);
+    await lockLineups(m);
+    await matchAction(team2.owner, m, { action: "consentMatch" });
+    await new Promise(resolve => setTimeout(resolve, 3000));
+
+    await expectError(matchAction(team1.owner, m, { action: "claimForfeit" }), "OpponentNotStalled");
+    await matchAction(team2.owner, m, { action: "claimForfeit" });
+
+    const matchState = await fetchMatch(m);
+    expect(matchState.status).to.have.property("forfeited");
+    expect(matchState.winner.toString()).to.equal(team2.data.toString());
+    await settleFees(m, creatorPayees());
+  });
+
+  it("Settling with a high creator share still empties the fee vault", async () => {
+    // The smallest entry fee, nearly all of it to the creators: each NFT's cut is below
+    // the rent-exempt minimum
+    const minimumFee = await provider.connection.getMinimumBalanceForRentExemption(0);
+    const setFees = (fee: number, creatorShareBps: number) =>
+      esportProgram.methods
+        .setFees(new anchor.BN(fee), creatorShareBps)
+        .accounts({ admin: provider.wallet.publicKey })
+        .rpc();
+    await setFees(minimumFee, 9_999);
+
+    const m = await scheduleMatch("friendly");
+    await payEntryFee(team1.owner, m);
+    await payEntryFee(team2.owner, m);
+    await lockLineups(m);
+    await matchAction(team2.owner, m, { action: "forfeit" });
+    await setFees(entryFee, 6_000);
+
+    // creator1 already holds lamports, so every cut is paid; the rounding goes to the treasury
+    const pot = 2 * minimumFee;
+    const toCreators = 10 * Math.floor(Math.floor((pot * 9_999) / 10_000) / 10);
+    const creatorBefore = await balance(creator1.publicKey);
+    const treasuryBefore = await balance(treasury.pubkey);
+    await settleFees(m, creatorPayees());
+
+    expect(await balance(creator1.publicKey)).to.equal(creatorBefore + toCreators);
+    expect(await balance(feeVault(matchSystem.programId, m.state))).to.equal(0);
+    // The provider wallet is the treasury and pays the transaction fee
+    expect(await balance(treasury.pubkey)).to.be.closeTo(treasuryBefore + pot - toCreators, 10_000);
+  });
+
+  it("A dispute the admin rejects pays the creators and the treasury", async () => {
+    // The operator-simulated match is still inside its dispute window; nothing to resolve yet
+    await expectError(matchAction(walletKeypair(), playedMatch, { action: "resolveDispute", refund: false }), "InvalidStatus");
+    await matchAction(team2.owner, playedMatch, { action: "disputeMatch" });
+    await expectError(settleFees(playedMatch, creatorPayees()), "InvalidStatus");
+
+    // Only the admin or the operator resolves disputes
+    await expectError(matchAction(team1.owner, playedMatch, { action: "resolveDispute", refund: false }), "NotAdminOrOperator");
+    await matchAction(walletKeypair(), playedMatch, { action: "resolveDispute", refund: false });
+
+    const matchState = await fetchMatch(playedMatch);
+    expect(matchState.status).to.have.property("finished");
+    expect(matchState.disputeResolved).to.equal(true);
+    await expectError(matchAction(team2.owner, playedMatch, { action: "disputeMatch" }), "DisputeAlreadyResolved");
+
+    // The result stands, so the fees are settled without waiting out the window
+    const creatorBefore = await balance(creator1.publicKey);
+    const treasuryBefore = await balance(treasury.pubkey);
+    await settleFees(playedMatch, creatorPayees());
+    expect(await balance(creator1.publicKey)).to.equal(creatorBefore + (2 * entryFee * 6000) / 10000);
+    // The treasury is the provider wallet, which also pays for the transaction
+    expect(await balance(treasury.pubkey)).to.be.closeTo(treasuryBefore + (2 * entryFee * 4000) / 10000, 10_000);
+    expect(await balance(feeVault(matchSystem.programId, playedMatch.state))).to.equal(0);
+  });
+
+  it("A dispute the operator upholds voids the match and refunds the fees", async () => {
+    const m = await scheduleMatch("ranked-bo3");
+    await payEntryFee(team1.owner, m);
+    await payEntryFee(team2.owner, m);
+    await lockLineups(m);
+    await matchAction(team2.owner, m, { action: "consentMatch" });
+    await commitSeeds(m);
+
+    const fetchPlayer = (team: Team) => playerStatsComponent.account.playerStats.fetch(team.stats[0]);
+    const records = async () =>
+      Promise.all([team1, team2].map(async (team) => {
+        const { rating, ratingDeviation, volatility, matchHistory } = await fetchTeam(team);
+        const { matchesPlayed, wins, losses } = await fetchPlayer(team);
+        return {
+          rating,
+          ratingDeviation,
+          volatility: volatility.toString(),
+          history: matchHistory.map((result) => result.matchKey.toString()),
+          matchesPlayed,
+          wins,
+          losses,
+        };
+      }));
+    const before = await records();
+    await matchAction(operator, m, { action: "simulateMatch" }, lineupTokenAccounts());
+    expect(await records()).to.not.deep.equal(before);
+
+    await matchAction(team1.owner, m, { action: "disputeMatch" });
+    await matchAction(operator, m, { action: "resolveDispute", refund: true });
+
+    const matchState = await fetchMatch(m);
+    expect(matchState.status).to.have.property("voided");
+    expect(matchState.disputeResolved).to.equal(true);
+
+    // The voided result no longer counts for the teams or their players
+    expect(await records()).to.deep.equal(before);
+
+    // Voided fees go back to the owners even though the lineups were locked, and the
+    // creators' payee list is refused
+    await expectError(settleFees(m, creatorPayees()), "InvalidOwnerAccount");
+    const team1Before = await balance(team1.owner.publicKey);
+    const team2Before = await balance(team2.owner.publicKey);
+    await settleFees(m, ownerPayees());
+    expect(await balance(feeVault(matchSystem.programId, m.state))).to.equal(0);
+    expect(await balance(team2.owner.publicKey)).to.equal(team2Before + entryFee);
+    expect(await balance(team1.owner.publicKey)).to.equal(team1Before + entryFee);
+  });
+
+  it("Challenges are declined, cancelled and expired", async () => {
+    const challenge = async () => {
+      await queueAction(team1.owner, { action: "challenge", matchType: "friendly" });
+      const { pendingMatches } = await fetchQueue();
+      return pendingMatches[pendingMatches.length - 1].id.toNumber();
+    };
+    const pendingIds = async () => (await fetchQueue()).pendingMatches.map((m) => m.id.toNumber());
+
+    // Only the challenged team's owner declines
+    let pendingMatchId = await challenge();
+    await expectError(queueAction(team1.owner, { action: "decline", pendingMatchId }), "NotTeamOwner");
+    await queueAction(team2.owner, { action: "decline", pendingMatchId });
+    expect(await pendingIds()).to.not.include(pendingMatchId);
+    await expectError(queueAction(team2.owner, { action: "accept", pendingMatchId }), "PendingMatchNotFound");
+
+    // Only the challenger cancels, and only until the challenge is accepted
+    pendingMatchId = await challenge();
+    await expectError(queueAction(team2.owner, { action: "cancel", pendingMatchId }), "NotTeamOwner");
+    await queueAction(team1.owner, { action: "cancel", pendingMatchId });
+    expect(await pendingIds()).to.not.include(pendingMatchId);
+
+    // Challenges now stay open five seconds, accepted matches one
+    await setTimeouts(5, 1, 1, 60 * 60);
+    pendingMatchId = await challenge();
+    await queueAction(team2.owner, { action: "accept", pendingMatchId });
+    await expectError(queueAction(team1.owner, { action: "cancel", pendingMatchId }), "MatchAlreadyAccepted");
+    await new Promise(resolve => setTimeout(resolve, 3000));
+
+    // An expired match can't be scheduled, and anyone can remove it
+    const { entity, component } = await newComponent(team1.owner, matchStateComponent.programId);
+    await expectError(
+      matchAction(team1.owner, { entity, state: component }, { action: "scheduleMatch", pendingMatchId }),
+      "MatchExpired"
+    );
+    await queueAction(operator, { action: "expire" });
+    expect(await pendingIds()).to.not.include(pendingMatchId);
+
+    // An unanswered challenge can't be accepted once it expires
+    pendingMatchId = await challenge();
+    await new Promise(resolve => setTimeout(resolve, 7000));
+    await expectError(queueAction(team2.owner, { action: "accept", pendingMatchId }), "MatchExpired");
+    await queueAction(operator, { action: "expire" });
+    expect(await pendingIds()).to.not.include(pendingMatchId);
+
+    await setTimeouts(24 * 60 * 60, 24 * 60 * 60, 1, 60 * 60);
   });
 
   it("Disband team", async () => {
-    // Disband team
-    const disbandTeam = await ApplySystem({
-      authority: player1.publicKey,
-      systemId: teamSystem.programId,
-      world: worldPda,
-      entities: [
-        {
-          entity: team1Entity,
-          components: [{ componentId: teamDataComponent.programId }],
-        }
-      ],
-      args: JSON.stringify({
-        action: "disbandTeam",
-      })
-    });
-    
-    await provider.sendAndConfirm(disbandTeam.transaction, [player1]);
-    
+    // Stake goes back to the owner, penalties to the treasury
+    await teamAction(team1, { action: "disbandTeam" }, [
+      writable(stakeVault(teamSystem.programId, team1.data)),
+      systemProgram,
+      treasury,
+      instructionsSysvar,
+    ]);
+
     // Verify team is disbanded
-    const teamData = await teamDataComponent.account.teamData.fetch(
-      team1DataComponentPda
-    );
-    
+    const teamData = await fetchTeam(team1);
     expect(teamData.roster.length).to.equal(0);
     expect(teamData.active).to.equal(false);
-    
+
+    // A disbanded team can't be challenged
+    await expectError(queueAction(team1.owner, { action: "challenge", matchType: "friendly" }), "TeamInactive");
+
     console.log("Team disbanded");
   });
-});
\ No newline at end of file
+
+  it("A player stays locked to its disbanded team until released", async () => {
+    // Only the passed player is released when a team disbands; team 1's others stay on it
+    const [mint, player, stats] = [team1.mints[0], team1.players[0], team1.stats[0]];
+    expect((await playerStatsComponent.account.playerStats.fetch(stats)).currentTeam.toString()).to.equal(
+      team1.data.toString()
+    );
+
+    team3 = await createTeam(player1, "Phoenix Rising");
+    await expectError(addPlayer(team3, mint, player, "Top"), "PlayerOnAnotherTeam");
+
+    // An active team's owner can't release its own rostered player
+    await expectError(teamAction(team2, { action: "releasePlayer" }, [], team2.players[0]), "PlayerStillRostered");
+
+    await teamAction(team1, { action: "releasePlayer" }, [], player);
+    expect((await playerStatsComponent.account.playerStats.fetch(stats)).currentTeam.toString()).to.equal(
+      PublicKey.default.toString()
+    );
+
+    await addPlayer(team3, mint, player, "Top");
+    expect((await playerStatsComponent.account.playerStats.fetch(stats)).currentTeam.toString()).to.equal(
+      team3.data.toString()
+    );
+    expect((await fetchTeam(team3)).roster.map((p) => p.nftMint.toString())).to.deep.equal([mint.toString()]);
+    spareTeam = team3;
+  });
+
+  it("The matchmaking queue holds at most 20 tickets", async () => {
+    // Nineteen reserve teams, all at the starting rating like team 3
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(player1.publicKey, 5 * LAMPORTS_PER_SOL)
+    );
+    for (let i = 1; i <= 19; i++) {
+      reserves.push(await createTeam(player1, `Reserve ${i}`));
+    }
+
+    for (const team of [team3, ...reserves]) {
+      await lend(team);
+      await matchmakingAction(team.owner, team, { action: "enqueue", matchType: "friendly" });
+    }
+    expect((await fetchQueue()).tickets.length).to.equal(20);
+    await expectError(matchmakingAction(team2.owner, team2, { action: "enqueue", matchType: "friendly" }), "QueueFull");
+  });
+
+  it("A team has at most three open challenges", async () => {
+    const opponents = [team3, ...reserves.slice(0, 3)];
+    const challenge = async (opponent: Team) => {
+      await lend(opponent);
+      await queueAction(team2.owner, { action: "challenge", matchType: "friendly" }, [team2, opponent]);
+    };
+    for (const opponent of opponents.slice(0, 3)) {
+      await challenge(opponent);
+    }
+    await expectError(challenge(opponents[3]), "TooManyChallenges");
+
+    // Cancelling a challenge frees its slot
+    const open = (await fetchQueue()).pendingMatches.filter((m) => m.team1.toString() === team2.data.toString());
+    expect(open.map((m) => m.team2.toString())).to.deep.equal(opponents.slice(0, 3).map((t) => t.data.toString()));
+    await queueAction(team2.owner, { action: "cancel", pendingMatchId: open[0].id.toNumber() }, [team2, opponents[0]]);
+    await challenge(opponents[3]);
+
+    // Withdraw them all
+    const pendingMatches = (await fetchQueue()).pendingMatches.filter((m) => m.team1.toString() === team2.data.toString());
+    for (const m of pendingMatches) {
+      const opponent = opponents.find((t) => t.data.toString() === m.team2.toString());
+      await queueAction(team2.owner, { action: "cancel", pendingMatchId: m.id.toNumber() }, [team2, opponent]);
+    }
+    expect((await fetchQueue()).pendingMatches.filter((m) => m.team1.toString() === team2.data.toString())).to.be.empty;
+  });
+
+  it("The match queue holds at most 20 pending matches", async () => {
+    // The twenty queued teams share a rating, so they pair into ten accepted matches
+    const before = (await fetchQueue()).pendingMatches.length;
+    await matchmakingAction(operator, team2, { action: "matchmake" });
+    let queue = await fetchQueue();
+    expect(queue.tickets.length).to.equal(0);
+    expect(queue.pendingMatches.length).to.equal(before + 10);
+
+    // Team 2 fills the rest with accepted challenges, which don't count as open ones
+    const opponents = [team3, ...reserves];
+    const free = 20 - queue.pendingMatches.length;
+    for (const opponent of opponents.slice(0, free)) {
+      await lend(opponent);
+      await queueAction(team2.owner, { action: "challenge", matchType: "friendly" }, [team2, opponent]);
+      queue = await fetchQueue();
+      const pendingMatchId = queue.pendingMatches[queue.pendingMatches.length - 1].id.toNumber();
+      await queueAction(opponent.owner, { action: "accept", pendingMatchId }, [team2, opponent]);
+    }
+    expect((await fetchQueue()).pendingMatches.length).to.equal(20);
+
+    await lend(opponents[free]);
+    await expectError(
+      queueAction(team2.owner, { action: "challenge", matchType: "friendly" }, [team2, opponents[free]]),
+      "QueueFull"
+    );
+  });
+});